@@ -0,0 +1,26 @@
+//! Build script turning the `disabled` feature into the `scientisto_disabled` cfg.
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    // declared here rather than in the `[lints]` table, which the Cargo of the MSRV does not know,
+    // and only from 1.80 on, which checks the cfgs without the unstable `-Zcheck-cfg`
+    if rustc_minor().is_some_and(|minor| minor >= 80) {
+        println!("cargo:rustc-check-cfg=cfg(scientisto_disabled)");
+    }
+
+    if std::env::var_os("CARGO_FEATURE_DISABLED").is_some() {
+        println!("cargo:rustc-cfg=scientisto_disabled");
+    }
+}
+
+/// The minor version of the compiler, e.g. `71` for `rustc 1.71.1`.
+fn rustc_minor() -> Option<u32> {
+    let rustc = std::env::var_os("RUSTC")?;
+    let output = std::process::Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+
+    version.split('.').nth(1)?.parse().ok()
+}
@@ -0,0 +1,126 @@
+//! Counting of the allocations of the branches, with the `alloc-counting` feature.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Counting allocator
+///
+/// Global allocator shim counting the allocations of the current thread, so that the
+/// observations record the `control_allocs` and `experiment_allocs` of the branches. The
+/// allocations are delegated to the inner allocator, the system one by default. The counts stay
+/// unrecorded unless the shim is installed as the `#[global_allocator]` of the application.
+///
+/// The allocations are counted on the thread executing the experiment only, the branches of the
+/// `async` experiments and the experiment branch moved to a pool by `with_thread_pool` are not
+/// counted.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::alloc::CountingAllocator;
+/// use scientisto::{Experiment,Observation};
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator::system();
+///
+/// Experiment::new("Allocations")
+///     .control(|| vec![1, 2, 3])
+///     .experiment(|| {
+///         let mut v = Vec::new();
+///         v.extend([1, 2, 3]);
+///         v
+///     })
+///     .publish(|o: &Observation<Vec<i32>, Vec<i32>>| {
+///         println!("allocated {:?} times as much", o.alloc_ratio())
+///     })
+///     .run();
+/// ```
+#[derive(Debug, Default)]
+pub struct CountingAllocator<A = System> {
+    inner: A,
+}
+
+impl CountingAllocator {
+    /// Count the allocations of the system allocator.
+    pub const fn system() -> Self {
+        Self::new(System)
+    }
+}
+
+impl<A> CountingAllocator<A> {
+    /// Count the allocations of the inner allocator.
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+fn count_allocation() {
+    INSTALLED.store(true, Ordering::Relaxed);
+    // the counter is unavailable while the thread local storage of the thread is destroyed
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+}
+
+// SAFETY: the allocations are delegated to the inner allocator as they are, the counter does not
+// allocate itself.
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        self.inner.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout)
+    }
+}
+
+/// Execute the branch, counting its allocations if the `CountingAllocator` is installed.
+pub(crate) fn count<R>(f: impl FnOnce() -> R) -> (R, Option<u64>) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    let allocations = ALLOCATIONS.with(Cell::get) - before;
+
+    (
+        result,
+        INSTALLED.load(Ordering::Relaxed).then_some(allocations),
+    )
+}
+
+#[cfg(all(test, not(scientisto_disabled)))]
+mod tests {
+    use super::*;
+    use crate::{Experiment, Observation};
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator::system();
+
+    #[test]
+    fn counting_allocator_should_record_the_allocations_of_the_branches() {
+        let allocs = Cell::new(None);
+
+        Experiment::new("Test")
+            .control(|| Box::new(1))
+            .experiment(|| (Box::new(1), Box::new(2)).0)
+            .publish(|o: &Observation<Box<i32>, Box<i32>>| {
+                allocs.set(o.control_allocs().zip(o.experiment_allocs()))
+            })
+            .run();
+
+        assert_eq!(allocs.get(), Some((1, 2)));
+    }
+}
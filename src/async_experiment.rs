@@ -1,11 +1,23 @@
+use std::panic::{resume_unwind, AssertUnwindSafe};
+
+use futures::FutureExt;
+
+use crate::compare::{self, Comparator, Equality};
+use crate::concurrent::ConcurrentReport;
+use crate::description::ExperimentDescription;
+use crate::instrument::{in_branch_async, in_publish, stamped_async, Branch};
+use crate::timeout::{self, NoTimeout, Timeout, TimeoutOr};
+use crate::Returned;
+
 /// `async` Experiment
 /// Basic struct defining the conducted `async` experiment. Initialized using type definitions instead of
 /// allocations. The `AsyncExperiment` is a consumable, once executed, it will consume the constituent
 /// futures defined for the experiment.
 ///
 /// The results of the `async` experiment, if run and awaited, are input into the publisher. The default
-/// publisher is a `noop`, whereas a custom publisher can be used either as a passed function or
-/// closure. Publisher can contain any logic, as long as it returns a `Unit` type.
+/// publisher is the one set by `set_default_publisher` (a `noop` until set), whereas a custom
+/// publisher can be used either as a passed function or closure. Publisher can contain any logic,
+/// as long as it returns a `Unit` type.
 ///
 /// # Operation
 /// - decides whether or not to run the experiment block
@@ -13,7 +25,8 @@
 /// - publishes all this information
 ///
 /// # Panics
-/// If any of the constituent futures panics
+/// If the **control** future panics, once the observation is published. A panic of the
+/// **experiment** future is caught and recorded in the observation as an `Err`.
 ///
 /// # Errors
 /// None
@@ -55,7 +68,29 @@
 /// })
 /// ```
 ///
+/// ## Reusing the experiment
+/// Futures passed to `control` and `experiment` are consumed by the single run. Passing functions
+/// creating the futures to `control_fn` and `experiment_fn` instead produces an experiment, which
+/// can be stored and run repeatedly, also from multiple tasks at once.
+/// ```rust
+/// use scientisto::{AsyncExperiment,Observation};
+/// use std::sync::Arc;
+///
+/// async_std::task::block_on(async {
+///     let experiment = Arc::new(
+///         AsyncExperiment::new("Shared")
+///             .control_fn(|| async { 3.0 })
+///             .experiment_fn(|| async { 3.0 })
+///             .publish(|o: &Observation<f32, f32>| assert!(o.is_matching())),
+///     );
+///
+///     let shared = experiment.clone();
+///     async_std::task::spawn(async move { shared.run().await }).await;
+///     experiment.run().await;
+/// })
+/// ```
 #[derive(Debug, Clone)]
+#[must_use = "experiments do nothing unless .run() is called"]
 pub struct AsyncExperiment {
     /// The name under which the experiment is registered.
     name: &'static str,
@@ -70,6 +105,21 @@ impl AsyncExperiment {
         Self { name }
     }
 
+    /// Create the experiment with a strict name of the `[a-zA-Z0-9_.-]` charset only.
+    ///
+    /// See `Experiment::try_new` of the synchronous experiment.
+    pub fn try_new(name: &'static str) -> Result<Self, crate::ExperimentError> {
+        Self::try_new_with(name, crate::error::is_metric_name_char)
+    }
+
+    /// Create the experiment with a name of the characters satisfying the predicate only.
+    pub fn try_new_with(
+        name: &'static str,
+        allowed: impl Fn(char) -> bool,
+    ) -> Result<Self, crate::ExperimentError> {
+        crate::error::validate_name(name, allowed).map(Self::new)
+    }
+
     pub fn name(&self) -> &'static str {
         self.name
     }
@@ -83,8 +133,79 @@ impl AsyncExperiment {
             control: f,
         }
     }
+
+    /// Define the control as a blocking closure, e.g. a synchronous reference implementation.
+    ///
+    /// The closure runs on a dedicated thread once the run starts, so it does not block the
+    /// executor while the experiment future is awaited, see `blocking::Blocking`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{AsyncExperiment,Observation};
+    ///
+    /// async_std::task::block_on(async {
+    ///     AsyncExperiment::new("Sync reference")
+    ///         .control_blocking(|| 3.0)
+    ///         .experiment(async { 3.0 })
+    ///         .publish(|o: &Observation<f32, f32>| assert!(o.is_matching()))
+    ///         .run().await;
+    /// })
+    /// ```
+    pub fn control_blocking<T, F>(self, f: F) -> AsyncControlOnly<T, crate::blocking::Blocking<T>>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.control(crate::blocking::Blocking::new(f))
+    }
+
+    /// Define the control as a function creating a new future for each run.
+    ///
+    /// Unlike `control`, which stores a single future consumed by the run, the experiment defined
+    /// using `control_fn` and `experiment_fn` is not consumed and can be run repeatedly.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{AsyncExperiment,Observation};
+    ///
+    /// async_std::task::block_on(async {
+    ///     let experiment = AsyncExperiment::new("Reusable")
+    ///         .control_fn(|| async { 3.0 })
+    ///         .experiment_fn(|| async { 3.0 })
+    ///         .publish(|o: &Observation<f32, f32>| assert!(o.is_matching()));
+    ///
+    ///     for _ in 0..3 {
+    ///         assert_eq!(experiment.run().await, 3.0);
+    ///     }
+    /// })
+    /// ```
+    pub fn control_fn<T, F, Fut>(self, f: F) -> AsyncControlFnOnly<F>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = T>,
+    {
+        AsyncControlFnOnly {
+            name: self.name,
+            control: f,
+        }
+    }
+
+    /// Define the control as a function creating a new future for each of the inputs.
+    ///
+    /// See `AsyncStreamExperiment::run_stream`.
+    pub fn control_with<I, T, F, Fut>(self, f: F) -> AsyncControlWithOnly<F>
+    where
+        F: Fn(I) -> Fut,
+        Fut: std::future::Future<Output = T>,
+    {
+        AsyncControlWithOnly {
+            name: self.name,
+            control: f,
+        }
+    }
 }
 
+#[must_use = "experiments do nothing unless .run() is called"]
 pub struct AsyncControlOnly<TC, FC>
 where
     FC: std::future::Future<Output = TC>,
@@ -104,7 +225,7 @@ where
     pub fn experiment<T, F>(
         self,
         f: F,
-    ) -> AsyncCompleteExperiment<TC, FC, T, F, impl Fn(&crate::Observation<TC, T>)>
+    ) -> AsyncCompleteExperiment<TC, FC, T, F, crate::publisher::DefaultPublisher>
     where
         F: std::future::Future<Output = T>,
     {
@@ -112,210 +233,2473 @@ where
             name: self.name,
             control: self.control,
             experiment: f,
-            publish: |_: &crate::Observation<TC, T>| {},
+            publish: crate::publisher::DefaultPublisher,
+            comparator: Equality,
+            timeout: NoTimeout,
+            settings: crate::settings::Settings::default(),
+        }
+    }
+
+    /// Define the experiment as a blocking closure, e.g. a synchronous reference implementation.
+    ///
+    /// The closure runs on a dedicated thread concurrently with the control future and the values
+    /// are compared once both complete. A panic of the closure is recorded in the observation.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{AsyncExperiment,Observation};
+    ///
+    /// fn reference() -> f32 { 3.0 }
+    ///
+    /// async_std::task::block_on(async {
+    ///     AsyncExperiment::new("Sync reference")
+    ///         .control(async { 3.0 })
+    ///         .experiment_blocking(reference)
+    ///         .publish(|o: &Observation<f32, f32>| assert!(o.is_matching()))
+    ///         .run().await;
+    /// })
+    /// ```
+    pub fn experiment_blocking<T, F>(
+        self,
+        f: F,
+    ) -> AsyncCompleteExperiment<
+        TC,
+        FC,
+        T,
+        crate::blocking::Blocking<T>,
+        crate::publisher::DefaultPublisher,
+    >
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.experiment(crate::blocking::Blocking::new(f))
+    }
+
+    /// Verify the control value using a dependent `async` experiment.
+    ///
+    /// Unlike `experiment`, the verification is started only once the control has completed and
+    /// receives the control value, e.g. to cross-check it against an external service. The output
+    /// of the verification is recorded and compared as the experiment value.
+    ///
+    /// The returned future cannot borrow the control value, copy the needed data into it instead.
+    ///
+    /// The values are compared by `Equality` unless another `comparator` is set. A panicking
+    /// control leaves nothing to verify. The observation then records the control panic next to
+    /// an uncompared experiment `Err`, and the panic is resumed once published like by `run`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{AsyncExperiment,Observation};
+    ///
+    /// async_std::task::block_on(async {
+    ///     AsyncExperiment::new("Cross-check")
+    ///         .control(async { 4 })
+    ///         .verify_async(|c: &i32| {
+    ///             let c = *c;
+    ///             async move { c * c / c }
+    ///         })
+    ///         .publish(|o: &Observation<i32, i32>| assert!(o.is_matching()))
+    ///         .run().await;
+    /// })
+    /// ```
+    pub fn verify_async<T, F, Fut>(
+        self,
+        f: F,
+    ) -> AsyncVerifiedExperiment<TC, FC, F, crate::publisher::DefaultPublisher>
+    where
+        F: Fn(&TC) -> Fut,
+        Fut: std::future::Future<Output = T>,
+    {
+        AsyncVerifiedExperiment {
+            name: self.name,
+            control: self.control,
+            verify: f,
+            publish: crate::publisher::DefaultPublisher,
+            comparator: Equality,
+            settings: crate::settings::Settings::default(),
         }
     }
 }
 
-pub struct AsyncCompleteExperiment<TC, FC, TE, FE, FP>
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct AsyncVerifiedExperiment<TC, FC, FV, FP, FM = Equality>
 where
     FC: std::future::Future<Output = TC>,
-    FE: std::future::Future<Output = TE>,
 {
     name: &'static str,
     control: FC,
-    experiment: FE,
+    verify: FV,
     publish: FP,
+    comparator: FM,
+    settings: crate::settings::Settings,
 }
 
-impl<TC, FC, TE, FE, FP> AsyncCompleteExperiment<TC, FC, TE, FE, FP>
+impl<TC, FC, FV, FP, FM> AsyncVerifiedExperiment<TC, FC, FV, FP, FM>
 where
     FC: std::future::Future<Output = TC>,
-    FE: std::future::Future<Output = TE>,
 {
     pub fn name(&self) -> &'static str {
         self.name
     }
 
-    pub fn publish<F>(self, f: F) -> AsyncCompleteExperiment<TC, FC, TE, FE, F>
+    pub fn publish<TE, Fut, F>(self, f: F) -> AsyncVerifiedExperiment<TC, FC, FV, F, FM>
     where
-        FC: std::future::Future<Output = TC>,
-        FE: std::future::Future<Output = TE>,
+        FV: Fn(&TC) -> Fut,
+        Fut: std::future::Future<Output = TE>,
         F: Fn(&crate::Observation<TC, TE>),
-        TE: PartialEq<TC>,
     {
-        AsyncCompleteExperiment::<TC, FC, TE, FE, F> {
+        AsyncVerifiedExperiment {
             name: self.name,
             control: self.control,
-            experiment: self.experiment,
+            verify: self.verify,
             publish: f,
+            comparator: self.comparator,
+            settings: self.settings,
         }
     }
 
-    pub async fn run(self) -> TC
+    /// Compare the control and the verified values using the comparator instead of `Equality`.
+    ///
+    /// See `AsyncCompleteExperiment::comparator`.
+    pub fn comparator<C>(self, c: C) -> AsyncVerifiedExperiment<TC, FC, FV, FP, C> {
+        AsyncVerifiedExperiment {
+            name: self.name,
+            control: self.control,
+            verify: self.verify,
+            publish: self.publish,
+            comparator: c,
+            settings: self.settings,
+        }
+    }
+
+    /// Skip the verification if the environment variable is set to a falsy value.
+    ///
+    /// See `CompleteExperiment::enabled_by_env` of the synchronous experiment.
+    pub fn enabled_by_env(mut self, variable: impl Into<String>) -> Self {
+        self.settings.enabled_by_env = Some(variable.into());
+        self
+    }
+
+    /// Verify only the given percentage of the runs.
+    ///
+    /// See `CompleteExperiment::run_percentage` of the synchronous experiment.
+    pub fn run_percentage(mut self, percentage: f64) -> Self {
+        self.settings.sample_rate(percentage / 100.0);
+        self
+    }
+
+    pub async fn run<TE, Fut>(self) -> TC
     where
-        FC: std::future::Future<Output = TC>,
-        FE: std::future::Future<Output = TE>,
-        FP: Fn(&crate::Observation<TC, TE>),
+        FV: Fn(&TC) -> Fut,
+        Fut: std::future::Future<Output = TE>,
+        FM: Comparator<TC, TE>,
+        FP: crate::Publisher<TC, TE>,
     {
         self.run_if(|| true).await
     }
 
-    pub async fn run_if<P>(self, predicate: P) -> TC
+    pub async fn run_if<TE, Fut, P>(self, predicate: P) -> TC
     where
-        FC: std::future::Future<Output = TC>,
-        FE: std::future::Future<Output = TE>,
-        FP: Fn(&crate::Observation<TC, TE>),
+        FV: Fn(&TC) -> Fut,
+        Fut: std::future::Future<Output = TE>,
+        FM: Comparator<TC, TE>,
+        FP: crate::Publisher<TC, TE>,
         P: Fn() -> bool,
     {
-        let should_run_experiment = predicate();
-        if should_run_experiment {
-            let (control, experiment) = futures::join!(self.control, self.experiment);
-            let observation = crate::Observation::<TC, TE> {
-                control: Ok(control),
-                experiment: Ok(experiment),
-            };
+        if cfg!(scientisto_disabled) {
+            return self.control.await;
+        }
 
-            (self.publish)(&observation);
+        let control = in_branch_async(
+            &self.settings,
+            self.name,
+            Branch::Control,
+            AssertUnwindSafe(self.control).catch_unwind(),
+        )
+        .await;
+        let sampled = predicate() && self.settings.should_run();
 
-            observation.control.ok().unwrap()
-        } else {
-            self.control.await
-        }
+        let observation = match control {
+            Ok(control) if !sampled => return control,
+            Err(e) if !sampled => self.settings.resume(e),
+            Ok(control) => {
+                let experiment = in_branch_async(
+                    &self.settings,
+                    self.name,
+                    Branch::Experiment,
+                    AssertUnwindSafe(async { (self.verify)(&control).await }).catch_unwind(),
+                )
+                .await;
+                crate::Observation::compared_by(
+                    self.name,
+                    Ok(control),
+                    experiment,
+                    &self.comparator,
+                )
+            }
+            Err(e) => crate::Observation::uncompared(
+                self.name,
+                Err(e),
+                Err(Box::new("the control panicked, nothing to verify")),
+            ),
+        };
+
+        settle(self.name, &self.settings, observation, &self.publish).0
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn experiment_should_derive_the_debug_trait() {
-        let experiment = AsyncExperiment::new("empty experiment");
+/// `async` experiment with both of the **control** and **experiment** futures defined.
+///
+/// The experiment, like the intermediate builder states, is `#[must_use]`, so a chain missing its
+/// `run` is reported by the `unused_must_use` lint:
+/// ```rust,compile_fail
+/// #![deny(unused_must_use)]
+/// use scientisto::AsyncExperiment;
+///
+/// AsyncExperiment::new("Forgotten").control(async { 1 }).experiment(async { 1 });
+/// ```
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct AsyncCompleteExperiment<TC, FC, TE, FE, FP, FM = Equality, FT = NoTimeout>
+where
+    FC: std::future::Future<Output = TC>,
+    FE: std::future::Future<Output = TE>,
+{
+    name: &'static str,
+    control: FC,
+    experiment: FE,
+    publish: FP,
+    comparator: FM,
+    timeout: FT,
+    settings: crate::settings::Settings,
+}
 
-        assert_ne!(format!("{:?}", experiment), "");
+impl<TC, FC, TE, FE, FP, FM, FT> AsyncCompleteExperiment<TC, FC, TE, FE, FP, FM, FT>
+where
+    FC: std::future::Future<Output = TC>,
+    FE: std::future::Future<Output = TE>,
+{
+    pub fn name(&self) -> &'static str {
+        self.name
     }
 
-    #[test]
-    #[should_panic]
-    fn experiment_should_panic_on_empty_string_name() {
-        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+    /// Map the experiment value used for the comparison and publishing.
+    ///
+    /// The mapping is applied to the output of the experiment future, as a part of the experiment.
+    pub fn map_experiment<R, M>(
+        self,
+        m: M,
+    ) -> AsyncCompleteExperiment<TC, FC, R, futures::future::Map<FE, M>, FP, FM, FT>
+    where
+        M: FnOnce(TE) -> R,
+    {
+        AsyncCompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment.map(m),
+            publish: self.publish,
+            comparator: self.comparator,
+            timeout: self.timeout,
+            settings: self.settings,
+        }
+    }
 
-        AsyncExperiment::new("");
+    /// Compare the control and experiment values using a custom comparator instead of `PartialEq`.
+    ///
+    /// Any `Fn(&TC, &TE) -> bool` closure or a ready-made comparator from the `compare` module can
+    /// be used, just like for the synchronous experiment.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{AsyncExperiment,Observation};
+    ///
+    /// async_std::task::block_on(async {
+    ///     AsyncExperiment::new("Tolerant")
+    ///         .control(async { 3.00_f64 })
+    ///         .experiment(async { 3.01_f64 })
+    ///         .comparator(scientisto::compare::approx(0.1))
+    ///         .publish(|o: &Observation<f64, f64>| assert!(o.is_matching()))
+    ///         .run().await;
+    /// })
+    /// ```
+    pub fn comparator<C>(self, c: C) -> AsyncCompleteExperiment<TC, FC, TE, FE, FP, C, FT>
+    where
+        C: Comparator<TC, TE>,
+    {
+        AsyncCompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            comparator: c,
+            timeout: self.timeout,
+            settings: self.settings,
+        }
     }
 
-    #[test]
-    fn async_experiment_should_return_name_if_it_is_valid() {
-        let actual_name: &str = "Any ľšýžľš is OK";
-        let experiment = AsyncExperiment::new(actual_name);
+    /// Compare the canonical forms of the values by the current comparator.
+    ///
+    /// See `CompleteExperiment::canonicalize` of the synchronous experiment.
+    pub fn canonicalize<C, K>(
+        self,
+        c: C,
+    ) -> AsyncCompleteExperiment<TC, FC, TE, FE, FP, compare::Canonicalized<C, FM>, FT>
+    where
+        C: Fn(&TE) -> K,
+        FM: Comparator<K, K>,
+    {
+        AsyncCompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            comparator: compare::canonicalized(c, self.comparator),
+            timeout: self.timeout,
+            settings: self.settings,
+        }
+    }
 
-        assert_eq!(experiment.name(), actual_name);
+    /// Compare `Result` values with unrelated error types by their `Ok` values only.
+    pub fn compare_ok_only(
+        self,
+    ) -> AsyncCompleteExperiment<TC, FC, TE, FE, FP, compare::OkOnly, FT> {
+        AsyncCompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            comparator: compare::ok_only(),
+            timeout: self.timeout,
+            settings: self.settings,
+        }
     }
 
-    #[test]
-    fn async_experiment_should_return_name_with_control_specified() {
-        let actual_name: &str = "Only control callback";
-        let experiment = AsyncExperiment::new(actual_name).control(async { false });
+    /// Return the control without waiting for a hanging experiment longer than the grace period.
+    ///
+    /// Both of the branches are polled concurrently as usual, but once the control completes, the
+    /// experiment gets the grace period to complete before it is abandoned. The abandoned
+    /// experiment is dropped and recorded as a timed out `Err` with the `timeout::Abandoned`
+    /// payload, see `Observation::experiment_timed_out`. Without it, a never resolving experiment
+    /// blocks the control value forever.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    /// use scientisto::{AsyncExperiment,Observation};
+    ///
+    /// async_std::task::block_on(async {
+    ///     let result = AsyncExperiment::new("Hanging")
+    ///         .control(async { 1 })
+    ///         .experiment(futures::future::pending::<i32>())
+    ///         .control_first(Duration::from_millis(10))
+    ///         .publish(|o: &Observation<i32, i32>| assert!(o.experiment_timed_out()))
+    ///         .run().await;
+    ///
+    ///     assert_eq!(result, 1);
+    /// })
+    /// ```
+    pub fn control_first(mut self, grace: std::time::Duration) -> Self {
+        self.settings.control_first = Some(grace);
+        self
+    }
 
-        assert_eq!(experiment.name(), actual_name);
+    /// Bound both of the branches by the absolute deadline, e.g. of the request budget.
+    ///
+    /// Unlike `experiment_timeout_or`, the deadline is shared by the branches. Whatever has not
+    /// completed by the deadline is dropped and recorded as timed out. The experiment is recorded
+    /// with the fallback value of `experiment_timeout_or` if configured, otherwise as a panic with
+    /// the `timeout::DeadlineExceeded` payload. The control is recorded as such a panic, flagged
+    /// by `Observation::control_timed_out`.
+    ///
+    /// # Panics
+    /// The run panics with the `timeout::DeadlineExceeded` payload if the **control** has not
+    /// completed by the deadline, once the observation is published.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::{Duration, Instant};
+    /// use scientisto::{AsyncExperiment,Observation};
+    ///
+    /// async_std::task::block_on(async {
+    ///     let result = AsyncExperiment::new("Budgeted")
+    ///         .control(async { 1 })
+    ///         .experiment(futures::future::pending::<i32>())
+    ///         .deadline(Instant::now() + Duration::from_millis(10))
+    ///         .publish(|o: &Observation<i32, i32>| {
+    ///             assert!(o.experiment_timed_out());
+    ///             assert!(!o.control_timed_out());
+    ///         })
+    ///         .run().await;
+    ///
+    ///     assert_eq!(result, 1);
+    /// })
+    /// ```
+    pub fn deadline(mut self, deadline: std::time::Instant) -> Self {
+        self.settings.deadline = Some(deadline);
+        self
     }
 
-    #[test]
-    fn async_experiment_should_return_name_if_control_and_experiment_are_fully_specified() {
-        let name: &str = "Only control callback";
-        let experiment = AsyncExperiment::new(name)
-            .control(async { 1 })
-            .experiment(async { 1 });
+    /// Time out the experiment future after the duration, recording the fallback value instead.
+    ///
+    /// The fallback is compared against the control like a regular experiment value and the
+    /// observation is flagged by `Observation::experiment_timed_out`. This suits an experiment
+    /// which is allowed to be slow, while still providing a comparison signal. The timed out
+    /// experiment future is dropped. The **control** is never timed out.
+    ///
+    /// The timeout applies to the experiment value as defined so far, so it has to be set after
+    /// `map_experiment`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    /// use scientisto::{AsyncExperiment,Observation};
+    ///
+    /// async_std::task::block_on(async {
+    ///     let result = AsyncExperiment::new("Slow")
+    ///         .control(async { 1 })
+    ///         .experiment(futures::future::pending::<i32>())
+    ///         .experiment_timeout_or(Duration::from_millis(10), 0)
+    ///         .publish(|o: &Observation<i32, i32>| {
+    ///             assert!(o.experiment_timed_out());
+    ///             assert_eq!(o.experiment().ok(), Some(&0));
+    ///         })
+    ///         .run().await;
+    ///
+    ///     assert_eq!(result, 1);
+    /// })
+    /// ```
+    pub fn experiment_timeout_or(
+        self,
+        duration: std::time::Duration,
+        fallback: TE,
+    ) -> AsyncCompleteExperiment<TC, FC, TE, FE, FP, FM, TimeoutOr<TE>>
+    where
+        TE: Clone,
+    {
+        AsyncCompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            comparator: self.comparator,
+            timeout: TimeoutOr::new(duration, fallback),
+            settings: self.settings,
+        }
+    }
 
-        assert_eq!(experiment.name(), name);
+    /// Opt out of publishing the observations explicitly.
+    ///
+    /// See `CompleteExperiment::no_publish` of the synchronous experiment.
+    pub fn no_publish(
+        self,
+    ) -> AsyncCompleteExperiment<TC, FC, TE, FE, crate::publisher::NoopPublisher, FM, FT> {
+        AsyncCompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: crate::publisher::NoopPublisher,
+            comparator: self.comparator,
+            timeout: self.timeout,
+            settings: self.settings,
+        }
     }
 
-    #[async_std::test]
-    async fn async_experiment_should_always_return_the_control_value() {
-        let expected = 1;
-        let actual = AsyncExperiment::new("Test")
-            .control(async { expected })
-            .experiment(async { expected })
-            .run()
-            .await;
+    pub fn publish<F>(self, f: F) -> AsyncCompleteExperiment<TC, FC, TE, FE, F, FM, FT>
+    where
+        FC: std::future::Future<Output = TC>,
+        FE: std::future::Future<Output = TE>,
+        F: Fn(&crate::Observation<TC, TE>),
+    {
+        AsyncCompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: f,
+            comparator: self.comparator,
+            timeout: self.timeout,
+            settings: self.settings,
+        }
+    }
 
-        assert_eq!(actual, expected);
+    /// Enable or disable instrumenting each of the branches with a `tracing` span.
+    ///
+    /// The spans are named after the branch (`control` or `experiment`) and carry the experiment
+    /// name in the `experiment` field. Spans are enabled by default.
+    #[cfg(feature = "tracing")]
+    pub fn with_spans(mut self, enabled: bool) -> Self {
+        self.settings.spans = enabled;
+        self
     }
 
-    #[async_std::test]
-    async fn async_experiment_should_not_run_the_experiment_if_conditioned_not_to() {
-        let expected = 1;
-        let actual = AsyncExperiment::new("Test")
-            .control(async { expected })
-            .experiment(async { expected })
-            .publish(|_o: &crate::Observation<i32, i32>| {})
-            .run_if(|| false)
-            .await;
+    /// Consider both of the branches panicking a match.
+    ///
+    /// See `CompleteExperiment::match_on_both_failed` of the synchronous experiment.
+    pub fn match_on_both_failed(mut self, enabled: bool) -> Self {
+        self.settings.match_on_both_failed = enabled;
+        self
+    }
 
-        assert_eq!(actual, expected);
+    /// Tag the observation with the version of the experiment implementation.
+    ///
+    /// See `CompleteExperiment::version` of the synchronous experiment.
+    pub fn version(mut self, version: &'static str) -> Self {
+        self.settings.version = Some(version);
+        self
     }
 
-    #[async_std::test]
-    async fn async_experiment_should_publish_the_results_when_publish_method_is_specified() {
-        let expected = 1;
-        AsyncExperiment::new("Test")
-            .control(async { expected })
-            .experiment(async { expected })
-            .publish(|o: &crate::Observation<i32, i32>| assert!(o.is_matching()))
-            .run()
-            .await;
+    /// Switch the experiment off using the environment variable read before each run.
+    ///
+    /// See `CompleteExperiment::enabled_by_env` of the synchronous experiment for the parsing.
+    pub fn enabled_by_env(mut self, variable: impl Into<String>) -> Self {
+        self.settings.enabled_by_env = Some(variable.into());
+        self
     }
 
-    #[derive(PartialEq, Copy, Clone)]
-    struct TestI64 {
-        value: i64,
+    /// Run the experiment in the given percentage (`0.0..=100.0`) of the runs only.
+    pub fn run_percentage(mut self, percentage: f64) -> Self {
+        self.settings.sample_rate(percentage / 100.0);
+        self
     }
 
-    impl PartialEq<i32> for TestI64 {
-        fn eq(&self, other: &i32) -> bool {
-            self.value as i32 == *other
-        }
+    /// Tag the experiment with the key-value metadata recorded in the observations.
+    ///
+    /// See `CompleteExperiment::tag` of the synchronous experiment.
+    pub fn tag(mut self, key: &'static str, value: impl Into<String>) -> Self {
+        self.settings.tag(key, value.into());
+        self
     }
 
-    #[async_std::test]
-    async fn async_experiment_should_work_with_different_return_types_if_they_are_comparable() {
-        let expected: i32 = 1;
-        let expected_as_i64 = TestI64 {
-            value: expected as i64,
-        };
+    /// Take each run of the experiment branch from the budget shared with other experiments.
+    ///
+    /// See `CompleteExperiment::with_budget` of the synchronous experiment.
+    pub fn with_budget(mut self, budget: &crate::budget::SamplingBudget) -> Self {
+        self.settings.budget = Some(budget.clone());
+        self
+    }
 
-        assert!(expected_as_i64 == expected_as_i64); // implements PartialEq
+    /// Seed the random generator deciding the sampled runs, so that they are reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.settings.seed(seed);
+        self
+    }
 
-        AsyncExperiment::new("Test")
-            .control(async { expected })
-            .experiment(async { expected_as_i64 })
-            .publish(|o: &crate::Observation<i32, TestI64>| assert!(o.is_matching()))
-            .run()
-            .await;
+    /// Use the random generator deciding the sampled runs instead of the built-in one.
+    ///
+    /// See `CompleteExperiment::with_rng` of the synchronous experiment.
+    #[cfg(feature = "rand")]
+    pub fn with_rng<R>(mut self, rng: R) -> Self
+    where
+        R: rand_core::RngCore + Send + 'static,
+    {
+        self.settings.rng(rng);
+        self
     }
 
-    #[async_std::test]
-    #[should_panic]
-    async fn async_experiment_should_panic_if_control_panics() {
-        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+    /// Sample the runs with a probability decreasing as the load increases.
+    ///
+    /// The experiment is run with the probability `max_rate * (1.0 - load_fn())`, see
+    /// `CompleteExperiment::adaptive_sample` of the synchronous experiment for the details.
+    pub fn adaptive_sample<L>(mut self, load_fn: L, max_rate: f64) -> Self
+    where
+        L: Fn() -> f64 + Send + Sync + 'static,
+    {
+        self.settings.adaptive_sample(load_fn, max_rate);
+        self
+    }
 
-        let expected: i32 = 1;
-        AsyncExperiment::new("Test")
-            .control(async { panic!("Oops") })
-            .experiment(async { expected })
-            .publish(|_o: &crate::Observation<i32, i32>| {})
-            .run()
-            .await;
+    /// Await both of the branches and publish the observation, returning the control value.
+    ///
+    /// The returned future is cancellation-safe, if it is dropped before both of the branches
+    /// complete, the pending branches are dropped and nothing is published.
+    pub async fn run(self) -> TC
+    where
+        FC: std::future::Future<Output = TC>,
+        FE: std::future::Future<Output = TE>,
+        FM: Comparator<TC, TE>,
+        FT: Timeout<TE>,
+        FP: crate::Publisher<TC, TE>,
+    {
+        self.run_if(|| true).await
     }
 
-    #[async_std::test]
-    async fn async_experiment_should_return_control_value_if_the_experiment_value_is_different() {
-        let expected: i32 = 1;
-        AsyncExperiment::new("Test")
+    pub async fn run_if<P>(self, predicate: P) -> TC
+    where
+        FC: std::future::Future<Output = TC>,
+        FE: std::future::Future<Output = TE>,
+        FM: Comparator<TC, TE>,
+        FT: Timeout<TE>,
+        FP: crate::Publisher<TC, TE>,
+        P: Fn() -> bool,
+    {
+        let experiment = if predicate() && self.settings.should_run() {
+            Some(self.experiment)
+        } else {
+            None
+        };
+
+        conduct(
+            self.name,
+            &self.settings,
+            self.control,
+            experiment,
+            &self.comparator,
+            &self.timeout,
+            &self.publish,
+        )
+        .await
+        .0
+    }
+}
+
+impl<TC, FC, FE, FP, FM, FT> AsyncCompleteExperiment<TC, FC, TC, FE, FP, FM, FT>
+where
+    FC: std::future::Future<Output = TC>,
+    FE: std::future::Future<Output = TC>,
+{
+    /// Race the branches, returning the value of whichever completes first.
+    ///
+    /// For the latency-critical paths where the value of either of the implementations is
+    /// acceptable. The returned settling future completes the slower branch, compares the values
+    /// and publishes the observation, with `Observation::returned` telling which of the branches
+    /// returned its value. Spawn the settling future on the executor to finish it in the
+    /// background, dropping it drops the slower branch without publishing anything.
+    ///
+    /// A panicking or timed out branch never wins the race, the value of the other one is returned
+    /// once it completes, so the fallback of `experiment_timeout_or` is only ever recorded. The
+    /// `deadline` bounds both of the branches as in `run`, while `control_first` applies to the
+    /// settling future, abandoning the experiment still pending after the grace period. If the
+    /// experiment is not sampled, or the build disables the experiments, the control value is
+    /// returned and the settling future does nothing.
+    ///
+    /// # Panics
+    /// Panics if both of the branches panic, resuming the panic of the **control** once the
+    /// observation is published.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    /// use scientisto::{AsyncExperiment, Observation, Returned};
+    ///
+    /// async_std::task::block_on(async {
+    ///     let (result, settle) = AsyncExperiment::new("Raced")
+    ///         .control(async {
+    ///             futures_timer::Delay::new(Duration::from_millis(50)).await;
+    ///             1
+    ///         })
+    ///         .experiment(async { 1 })
+    ///         .publish(|o: &Observation<i32, i32>| {
+    ///             assert_eq!(o.returned(), Returned::Experiment);
+    ///             assert!(o.is_matching());
+    ///         })
+    ///         .race_return()
+    ///         .await;
+    ///
+    ///     assert_eq!(result, 1);
+    ///     settle.await;
+    /// });
+    /// ```
+    pub async fn race_return(self) -> (TC, impl std::future::Future<Output = ()>)
+    where
+        TC: Clone,
+        FM: Comparator<TC, TC>,
+        FT: Timeout<TC>,
+        FP: crate::Publisher<TC, TC>,
+    {
+        let Self {
+            name,
+            control,
+            experiment,
+            publish,
+            comparator,
+            timeout,
+            settings,
+        } = self;
+        if cfg!(scientisto_disabled) {
+            let settle = futures::future::Either::Left(futures::future::ready(()));
+            return (control.await, settle);
+        }
+        let sampled = settings.should_run();
+
+        let control_settings = settings.clone();
+        let mut control = Box::pin(async move {
+            stamped_async(in_branch_async(
+                &control_settings,
+                name,
+                Branch::Control,
+                timeout::until(
+                    async { (AssertUnwindSafe(control).catch_unwind().await, false) },
+                    control_settings.deadline,
+                    || (Err(Box::new(timeout::DeadlineExceeded)), true),
+                ),
+            ))
+            .await
+        });
+        let experiment_settings = settings.clone();
+        let mut experiment = Box::pin(async move {
+            stamped_async(in_branch_async(
+                &experiment_settings,
+                name,
+                Branch::Experiment,
+                timeout::until(
+                    timeout::within(AssertUnwindSafe(experiment).catch_unwind(), &timeout),
+                    experiment_settings.deadline,
+                    || match timeout.timeout() {
+                        Some((_, fallback)) => (Ok(fallback), true),
+                        None => (Err(Box::new(timeout::DeadlineExceeded)), true),
+                    },
+                ),
+            ))
+            .await
+        });
+
+        let (mut control_done, mut experiment_done) = (None, None);
+        if !sampled {
+            control_done = Some((&mut control).await);
+        } else {
+            match futures::future::select(&mut control, &mut experiment).await {
+                futures::future::Either::Left((c, _)) => control_done = Some(c),
+                futures::future::Either::Right((e, _)) => experiment_done = Some(e),
+            }
+        }
+
+        // a timed out experiment holds the fallback, which is never returned
+        let winner =
+            |control_done: &Option<((std::thread::Result<TC>, bool), _)>,
+             experiment_done: &Option<((std::thread::Result<TC>, bool), _)>| {
+                match (control_done, experiment_done) {
+                    (Some(((Ok(value), _), _)), _) => Some((value.clone(), Returned::Control)),
+                    (_, Some(((Ok(value), false), _))) => {
+                        Some((value.clone(), Returned::Experiment))
+                    }
+                    _ => None,
+                }
+            };
+
+        let mut returned = winner(&control_done, &experiment_done);
+        if returned.is_none() && sampled {
+            // the first completed branch panicked or timed out, the other one has to be awaited
+            if control_done.is_none() {
+                control_done = Some((&mut control).await);
+            } else {
+                experiment_done = Some((&mut experiment).await);
+            }
+            returned = winner(&control_done, &experiment_done);
+        }
+
+        let branch = returned
+            .as_ref()
+            .map_or(Returned::Control, |(_, branch)| *branch);
+        let settle = async move {
+            if !sampled {
+                return control_done.and_then(|((control, _), _)| control.err());
+            }
+
+            let (
+                ((control, control_expired), control_wallclock),
+                ((experiment, timed_out), experiment_wallclock),
+            ) = timeout::join_control_first(
+                async {
+                    match control_done {
+                        Some(control) => control,
+                        None => control.await,
+                    }
+                },
+                async {
+                    match experiment_done {
+                        Some(experiment) => experiment,
+                        None => experiment.await,
+                    }
+                },
+                settings.control_first,
+                || ((Err(Box::new(timeout::Abandoned)), true), None),
+            )
+            .await;
+            let observation =
+                crate::Observation::compared_by(name, control, experiment, &comparator)
+                    .timed_out(timed_out)
+                    .control_expired(control_expired)
+                    .matching_both_failed(settings.match_on_both_failed)
+                    .graced(settings.in_grace_period())
+                    .versioned(settings.version)
+                    .tagged(&settings.tags)
+                    .stamped(control_wallclock, experiment_wallclock)
+                    .returning(branch);
+
+            settings.record(&observation);
+            in_publish(&settings, name, || settings.publish(&publish, &observation));
+
+            observation.control.err()
+        };
+
+        match returned {
+            Some((value, _)) => (value, futures::future::Either::Right(settle.map(drop))),
+            None => match settle.await {
+                Some(e) => resume_unwind(e),
+                None => unreachable!("neither of the branches returned a value"),
+            },
+        }
+    }
+}
+
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct AsyncControlFnOnly<FC> {
+    name: &'static str,
+    control: FC,
+}
+
+impl<FC> AsyncControlFnOnly<FC> {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Define the experiment as a function creating a new future for each run.
+    pub fn experiment_fn<T, F, Fut>(
+        self,
+        f: F,
+    ) -> AsyncCompleteFnExperiment<FC, F, crate::publisher::DefaultPublisher>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = T>,
+    {
+        AsyncCompleteFnExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: f,
+            publish: crate::publisher::DefaultPublisher,
+            comparator: Equality,
+            timeout: NoTimeout,
+            settings: crate::settings::Settings::default(),
+        }
+    }
+}
+
+/// `async` experiment creating new futures for each run, so that it can be run repeatedly.
+///
+/// The experiment is `Send` and `Sync` as long as the functions and the publisher are, so that it
+/// can be shared between tasks.
+#[derive(Clone)]
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct AsyncCompleteFnExperiment<FC, FE, FP, FM = Equality, FT = NoTimeout> {
+    name: &'static str,
+    control: FC,
+    experiment: FE,
+    publish: FP,
+    comparator: FM,
+    timeout: FT,
+    settings: crate::settings::Settings,
+}
+
+impl<FC, FE, FP, FM, FT> AsyncCompleteFnExperiment<FC, FE, FP, FM, FT> {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Map the experiment value used for the comparison and publishing.
+    ///
+    /// The mapping is applied to the output of each of the created experiment futures.
+    pub fn map_experiment<TE, FutE, R, M>(
+        self,
+        m: M,
+    ) -> AsyncCompleteFnExperiment<FC, impl Fn() -> futures::future::Map<FutE, M>, FP, FM, FT>
+    where
+        FE: Fn() -> FutE,
+        FutE: std::future::Future<Output = TE>,
+        M: FnOnce(TE) -> R + Clone,
+    {
+        let experiment = self.experiment;
+
+        AsyncCompleteFnExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: move || experiment().map(m.clone()),
+            publish: self.publish,
+            comparator: self.comparator,
+            timeout: self.timeout,
+            settings: self.settings,
+        }
+    }
+
+    /// Compare the control and experiment values using a custom comparator instead of `PartialEq`.
+    pub fn comparator<C>(self, c: C) -> AsyncCompleteFnExperiment<FC, FE, FP, C, FT> {
+        AsyncCompleteFnExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            comparator: c,
+            timeout: self.timeout,
+            settings: self.settings,
+        }
+    }
+
+    /// Compare the canonical forms of the values by the current comparator.
+    ///
+    /// See `CompleteExperiment::canonicalize` of the synchronous experiment.
+    pub fn canonicalize<C>(
+        self,
+        c: C,
+    ) -> AsyncCompleteFnExperiment<FC, FE, FP, compare::Canonicalized<C, FM>, FT> {
+        AsyncCompleteFnExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            comparator: compare::canonicalized(c, self.comparator),
+            timeout: self.timeout,
+            settings: self.settings,
+        }
+    }
+
+    /// Compare `Result` values with unrelated error types by their `Ok` values only.
+    pub fn compare_ok_only(self) -> AsyncCompleteFnExperiment<FC, FE, FP, compare::OkOnly, FT> {
+        self.comparator(compare::ok_only())
+    }
+
+    /// Return the control without waiting for a hanging experiment longer than the grace period.
+    ///
+    /// See `AsyncCompleteExperiment::control_first`.
+    pub fn control_first(mut self, grace: std::time::Duration) -> Self {
+        self.settings.control_first = Some(grace);
+        self
+    }
+
+    /// Time out each of the experiment futures after the duration, recording the fallback value.
+    ///
+    /// See `AsyncCompleteExperiment::experiment_timeout_or`, the **control** is never timed out.
+    pub fn experiment_timeout_or<TE>(
+        self,
+        duration: std::time::Duration,
+        fallback: TE,
+    ) -> AsyncCompleteFnExperiment<FC, FE, FP, FM, TimeoutOr<TE>>
+    where
+        TE: Clone,
+    {
+        AsyncCompleteFnExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            comparator: self.comparator,
+            timeout: TimeoutOr::new(duration, fallback),
+            settings: self.settings,
+        }
+    }
+
+    /// Opt out of publishing the observations explicitly.
+    ///
+    /// See `CompleteExperiment::no_publish` of the synchronous experiment.
+    pub fn no_publish(
+        self,
+    ) -> AsyncCompleteFnExperiment<FC, FE, crate::publisher::NoopPublisher, FM, FT> {
+        AsyncCompleteFnExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: crate::publisher::NoopPublisher,
+            comparator: self.comparator,
+            timeout: self.timeout,
+            settings: self.settings,
+        }
+    }
+
+    pub fn publish<TC, TE, FutC, FutE, F>(
+        self,
+        f: F,
+    ) -> AsyncCompleteFnExperiment<FC, FE, F, FM, FT>
+    where
+        FC: Fn() -> FutC,
+        FutC: std::future::Future<Output = TC>,
+        FE: Fn() -> FutE,
+        FutE: std::future::Future<Output = TE>,
+        F: Fn(&crate::Observation<TC, TE>),
+    {
+        AsyncCompleteFnExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: f,
+            comparator: self.comparator,
+            timeout: self.timeout,
+            settings: self.settings,
+        }
+    }
+
+    /// Enable or disable instrumenting each of the branches with a `tracing` span.
+    ///
+    /// The spans are named after the branch (`control` or `experiment`) and carry the experiment
+    /// name in the `experiment` field. Spans are enabled by default.
+    #[cfg(feature = "tracing")]
+    pub fn with_spans(mut self, enabled: bool) -> Self {
+        self.settings.spans = enabled;
+        self
+    }
+
+    /// Consider both of the branches panicking a match.
+    ///
+    /// See `CompleteExperiment::match_on_both_failed` of the synchronous experiment.
+    pub fn match_on_both_failed(mut self, enabled: bool) -> Self {
+        self.settings.match_on_both_failed = enabled;
+        self
+    }
+
+    /// Switch the experiment off using the environment variable read before each run.
+    ///
+    /// See `CompleteExperiment::enabled_by_env` of the synchronous experiment for the parsing.
+    pub fn enabled_by_env(mut self, variable: impl Into<String>) -> Self {
+        self.settings.enabled_by_env = Some(variable.into());
+        self
+    }
+
+    /// Assign the experiment to a group, reported by `describe` only.
+    pub fn group(mut self, group: &'static str) -> Self {
+        self.settings.group = Some(group);
+        self
+    }
+
+    /// Reconfigure the policy of the built experiment in place, keeping its functions.
+    ///
+    /// See `CompleteExperiment::reconfigure` of the synchronous experiment.
+    pub fn reconfigure(&mut self) -> crate::reconfigure::Reconfigure<'_> {
+        crate::reconfigure::Reconfigure::new(&mut self.settings)
+    }
+
+    /// Tag the observations with the version of the experiment implementation.
+    ///
+    /// See `CompleteExperiment::version` of the synchronous experiment.
+    pub fn version(mut self, version: &'static str) -> Self {
+        self.settings.version = Some(version);
+        self
+    }
+
+    /// Describe the configuration of the experiment, without its closures.
+    ///
+    /// See `CompleteExperiment::describe` of the synchronous experiment.
+    pub fn describe(&self) -> ExperimentDescription {
+        self.settings.describe(self.name)
+    }
+
+    /// Ignore the mismatches during the grace period after the process start.
+    ///
+    /// See `CompleteExperiment::strict_after` of the synchronous experiment.
+    pub fn strict_after(mut self, grace_period: std::time::Duration) -> Self {
+        self.settings.strict_after = Some(grace_period);
+        self
+    }
+
+    /// Call the publisher at most `max_per_sec` times per second, dropping the excess observations.
+    ///
+    /// See `CompleteExperiment::publish_rate_limit` of the synchronous experiment.
+    pub fn publish_rate_limit(mut self, max_per_sec: f64) -> Self {
+        self.settings.publish_limit = Some(std::sync::Arc::new(
+            crate::rate_limit::TokenBucket::new(max_per_sec),
+        ));
+        self
+    }
+
+    /// Tag the experiment with the key-value metadata recorded in the observations.
+    ///
+    /// See `CompleteExperiment::tag` of the synchronous experiment.
+    pub fn tag(mut self, key: &'static str, value: impl Into<String>) -> Self {
+        self.settings.tag(key, value.into());
+        self
+    }
+
+    /// Take each run of the experiment branch from the budget shared with other experiments.
+    ///
+    /// See `CompleteExperiment::with_budget` of the synchronous experiment.
+    pub fn with_budget(mut self, budget: &crate::budget::SamplingBudget) -> Self {
+        self.settings.budget = Some(budget.clone());
+        self
+    }
+
+    /// Number of the observations dropped by the `publish_rate_limit`.
+    pub fn dropped_publications(&self) -> u64 {
+        self.settings.dropped_publications()
+    }
+
+    /// Run the experiment in the given percentage (`0.0..=100.0`) of the runs only.
+    pub fn run_percentage(mut self, percentage: f64) -> Self {
+        self.settings.sample_rate(percentage / 100.0);
+        self
+    }
+
+    /// Seed the random generator deciding the sampled runs, so that they are reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.settings.seed(seed);
+        self
+    }
+
+    /// Use the random generator deciding the sampled runs instead of the built-in one.
+    ///
+    /// See `CompleteExperiment::with_rng` of the synchronous experiment.
+    #[cfg(feature = "rand")]
+    pub fn with_rng<R>(mut self, rng: R) -> Self
+    where
+        R: rand_core::RngCore + Send + 'static,
+    {
+        self.settings.rng(rng);
+        self
+    }
+
+    /// Sample the runs with a probability decreasing as the load increases.
+    ///
+    /// The experiment is run with the probability `max_rate * (1.0 - load_fn())`, see
+    /// `CompleteExperiment::adaptive_sample` of the synchronous experiment for the details.
+    pub fn adaptive_sample<L>(mut self, load_fn: L, max_rate: f64) -> Self
+    where
+        L: Fn() -> f64 + Send + Sync + 'static,
+    {
+        self.settings.adaptive_sample(load_fn, max_rate);
+        self
+    }
+
+    /// Track the ratio of the matching runs over a sliding window of the last `window` runs.
+    ///
+    /// The window is shared by the clones of the experiment, see `match_rate`.
+    pub fn track_match_rate(mut self, window: usize) -> Self {
+        self.settings.match_window = Some(std::sync::Arc::new(
+            crate::match_rate::MatchWindow::new(window),
+        ));
+        self
+    }
+
+    /// Ratio of the matching runs within the tracked window, `NaN` if untracked or empty.
+    pub fn match_rate(&self) -> f64 {
+        self.settings.match_rate()
+    }
+
+    pub async fn run<TC, TE, FutC, FutE>(&self) -> TC
+    where
+        FC: Fn() -> FutC,
+        FutC: std::future::Future<Output = TC>,
+        FE: Fn() -> FutE,
+        FutE: std::future::Future<Output = TE>,
+        FM: Comparator<TC, TE>,
+        FT: Timeout<TE>,
+        FP: crate::Publisher<TC, TE>,
+    {
+        self.run_if(|| true).await
+    }
+
+    pub async fn run_if<TC, TE, FutC, FutE, P>(&self, predicate: P) -> TC
+    where
+        FC: Fn() -> FutC,
+        FutC: std::future::Future<Output = TC>,
+        FE: Fn() -> FutE,
+        FutE: std::future::Future<Output = TE>,
+        FM: Comparator<TC, TE>,
+        FT: Timeout<TE>,
+        FP: crate::Publisher<TC, TE>,
+        P: Fn() -> bool,
+    {
+        self.run_verdict(predicate).await.0
+    }
+
+    /// Drive `total` runs with at most `concurrency` of them in flight, aggregating the verdicts
+    /// and the latencies of the runs.
+    ///
+    /// Useful to benchmark the experiment or to validate it in bulk, e.g. against a service. Each
+    /// run is sampled and published as usual, the control values are discarded. A `concurrency`
+    /// of zero runs one at a time.
+    ///
+    /// # Panics
+    /// Panics if the **control** future panics in any of the runs.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::AsyncExperiment;
+    ///
+    /// async_std::task::block_on(async {
+    ///     let report = AsyncExperiment::new("Bulk")
+    ///         .control_fn(|| async { 1 })
+    ///         .experiment_fn(|| async { 1 })
+    ///         .no_publish()
+    ///         .run_concurrent(8, 100)
+    ///         .await;
+    ///
+    ///     assert_eq!(report.matching, 100);
+    ///     println!("p99 latency: {:?}", report.p99);
+    /// })
+    /// ```
+    pub async fn run_concurrent<TC, TE, FutC, FutE>(
+        &self,
+        concurrency: usize,
+        total: usize,
+    ) -> ConcurrentReport
+    where
+        FC: Fn() -> FutC,
+        FutC: std::future::Future<Output = TC>,
+        FE: Fn() -> FutE,
+        FutE: std::future::Future<Output = TE>,
+        FM: Comparator<TC, TE>,
+        FT: Timeout<TE>,
+        FP: crate::Publisher<TC, TE>,
+    {
+        use futures::StreamExt;
+
+        let runs = futures::stream::iter(0..total)
+            .map(|_| async {
+                let started = std::time::Instant::now();
+                let (_, verdict) = self.run_verdict(|| true).await;
+                (verdict, started.elapsed())
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+
+        ConcurrentReport::new(runs)
+    }
+
+    async fn run_verdict<TC, TE, FutC, FutE, P>(&self, predicate: P) -> (TC, Option<bool>)
+    where
+        FC: Fn() -> FutC,
+        FutC: std::future::Future<Output = TC>,
+        FE: Fn() -> FutE,
+        FutE: std::future::Future<Output = TE>,
+        FM: Comparator<TC, TE>,
+        FT: Timeout<TE>,
+        FP: crate::Publisher<TC, TE>,
+        P: Fn() -> bool,
+    {
+        let experiment = if predicate() && self.settings.should_run() {
+            Some((self.experiment)())
+        } else {
+            None
+        };
+
+        conduct(
+            self.name,
+            &self.settings,
+            (self.control)(),
+            experiment,
+            &self.comparator,
+            &self.timeout,
+            &self.publish,
+        )
+        .await
+    }
+}
+
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct AsyncControlWithOnly<FC> {
+    name: &'static str,
+    control: FC,
+}
+
+impl<FC> AsyncControlWithOnly<FC> {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Define the experiment as a function creating a new future for each of the inputs.
+    pub fn experiment_with<I, T, F, Fut>(
+        self,
+        f: F,
+    ) -> AsyncStreamExperiment<FC, F, crate::publisher::DefaultPublisher>
+    where
+        F: Fn(I) -> Fut,
+        Fut: std::future::Future<Output = T>,
+    {
+        AsyncStreamExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: f,
+            publish: crate::publisher::DefaultPublisher,
+            comparator: Equality,
+            settings: crate::settings::Settings::default(),
+        }
+    }
+}
+
+/// `async` experiment run for each of the inputs, e.g. the messages of a queue.
+///
+/// Both of the functions are given a clone of the input, the futures of the control and the
+/// experiment are joined and published for every input, just like in `AsyncCompleteFnExperiment`.
+///
+/// # Examples
+/// ```rust
+/// use futures::StreamExt;
+/// use scientisto::{AsyncExperiment,Observation};
+///
+/// async_std::task::block_on(async {
+///     let results = AsyncExperiment::new("Shadowed queue")
+///         .control_with(|message: u32| async move { message * 2 })
+///         .experiment_with(|message: u32| async move { message << 1 })
+///         .publish(|o: &Observation<u32, u32>| assert!(o.is_matching()))
+///         .run_stream(futures::stream::iter(1..=3))
+///         .collect::<Vec<_>>()
+///         .await;
+///
+///     assert_eq!(results, vec![2, 4, 6]);
+/// })
+/// ```
+#[derive(Clone)]
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct AsyncStreamExperiment<FC, FE, FP, FM = Equality> {
+    name: &'static str,
+    control: FC,
+    experiment: FE,
+    publish: FP,
+    comparator: FM,
+    settings: crate::settings::Settings,
+}
+
+impl<FC, FE, FP, FM> AsyncStreamExperiment<FC, FE, FP, FM> {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Compare the control and experiment values using a custom comparator instead of `PartialEq`.
+    pub fn comparator<C>(self, c: C) -> AsyncStreamExperiment<FC, FE, FP, C> {
+        AsyncStreamExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            comparator: c,
+            settings: self.settings,
+        }
+    }
+
+    pub fn publish<I, TC, TE, FutC, FutE, F>(self, f: F) -> AsyncStreamExperiment<FC, FE, F, FM>
+    where
+        FC: Fn(I) -> FutC,
+        FutC: std::future::Future<Output = TC>,
+        FE: Fn(I) -> FutE,
+        FutE: std::future::Future<Output = TE>,
+        F: Fn(&crate::Observation<TC, TE>),
+    {
+        AsyncStreamExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: f,
+            comparator: self.comparator,
+            settings: self.settings,
+        }
+    }
+
+    /// Run the experiment in the given percentage (`0.0..=100.0`) of the inputs only.
+    pub fn run_percentage(mut self, percentage: f64) -> Self {
+        self.settings.sample_rate(percentage / 100.0);
+        self
+    }
+
+    /// Run the experiment for the input, returning the control value.
+    ///
+    /// # Panics
+    /// Panics if the **control** future panics, once the observation is published.
+    pub async fn run<I, TC, TE, FutC, FutE>(&self, input: I) -> TC
+    where
+        I: Clone,
+        FC: Fn(I) -> FutC,
+        FutC: std::future::Future<Output = TC>,
+        FE: Fn(I) -> FutE,
+        FutE: std::future::Future<Output = TE>,
+        FM: Comparator<TC, TE>,
+        FP: crate::Publisher<TC, TE>,
+    {
+        let experiment = if self.settings.should_run() {
+            Some((self.experiment)(input.clone()))
+        } else {
+            None
+        };
+
+        conduct(
+            self.name,
+            &self.settings,
+            (self.control)(input),
+            experiment,
+            &self.comparator,
+            &NoTimeout,
+            &self.publish,
+        )
+        .await
+        .0
+    }
+
+    /// Run the experiment for each of the inputs concurrently, returning the control values in
+    /// the order of the inputs.
+    ///
+    /// The `async` counterpart of `MatrixExperiment::run_matrix`, e.g. to validate a service
+    /// against a fixed corpus of inputs on startup. The inputs are joined with `join_all`, the
+    /// branches of each input are joined and published just like by `run`, so the observations
+    /// are published in the order the inputs complete.
+    ///
+    /// # Panics
+    /// Panics if the **control** future panics for an input, once the input is published. The
+    /// inputs still in flight are dropped unpublished.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{AsyncExperiment,Observation};
+    ///
+    /// async_std::task::block_on(async {
+    ///     let inputs = (0..3).flat_map(|a| (0..3).map(move |b| (a, b))).collect();
+    ///
+    ///     let results = AsyncExperiment::new("Addition")
+    ///         .control_with(|(a, b): (i32, i32)| async move { a + b })
+    ///         .experiment_with(|(a, b): (i32, i32)| async move { b + a })
+    ///         .publish(|o: &Observation<i32, i32>| assert!(o.is_matching()))
+    ///         .run_matrix_async(inputs)
+    ///         .await;
+    ///
+    ///     assert_eq!(results.len(), 9);
+    /// })
+    /// ```
+    pub async fn run_matrix_async<I, TC, TE, FutC, FutE>(&self, inputs: Vec<I>) -> Vec<TC>
+    where
+        I: Clone,
+        FC: Fn(I) -> FutC,
+        FutC: std::future::Future<Output = TC>,
+        FE: Fn(I) -> FutE,
+        FutE: std::future::Future<Output = TE>,
+        FM: Comparator<TC, TE>,
+        FP: crate::Publisher<TC, TE>,
+    {
+        futures::future::join_all(inputs.into_iter().map(|input| self.run(input))).await
+    }
+
+    /// Run the experiment for each of the inputs in order, streaming the control values.
+    ///
+    /// # Panics
+    /// The stream panics if the **control** future panics for an input.
+    pub fn run_stream<I, S, TC, TE, FutC, FutE>(self, inputs: S) -> impl futures::Stream<Item = TC>
+    where
+        I: Clone,
+        S: futures::Stream<Item = I>,
+        FC: Fn(I) -> FutC,
+        FutC: std::future::Future<Output = TC>,
+        FE: Fn(I) -> FutE,
+        FutE: std::future::Future<Output = TE>,
+        FM: Comparator<TC, TE>,
+        FP: crate::Publisher<TC, TE>,
+    {
+        futures::stream::unfold(
+            (self, Box::pin(inputs)),
+            |(experiment, mut inputs)| async move {
+                let input = futures::StreamExt::next(&mut inputs).await?;
+                let result = experiment.run(input).await;
+
+                Some((result, (experiment, inputs)))
+            },
+        )
+    }
+}
+
+/// Await the control and, if present, the experiment future and publish the observation.
+async fn conduct<TC, FC, TE, FE, FM, FP>(
+    name: &'static str,
+    settings: &crate::settings::Settings,
+    control: FC,
+    experiment: Option<FE>,
+    comparator: &FM,
+    timeout: &impl Timeout<TE>,
+    publish: &FP,
+) -> (TC, Option<bool>)
+where
+    FC: std::future::Future<Output = TC>,
+    FE: std::future::Future<Output = TE>,
+    FM: Comparator<TC, TE>,
+    FP: crate::Publisher<TC, TE>,
+{
+    if cfg!(scientisto_disabled) {
+        return (control.await, None);
+    }
+
+    match experiment {
+        Some(experiment) => {
+            // only the deadline times out the control, the experiment timeout applies to the
+            // experiment alone and its fallback is recorded past the deadline as well
+            let (
+                ((control, control_expired), control_wallclock),
+                ((experiment, timed_out), experiment_wallclock),
+            ) = timeout::join_control_first(
+                stamped_async(in_branch_async(
+                    settings,
+                    name,
+                    Branch::Control,
+                    timeout::until(
+                        async { (AssertUnwindSafe(control).catch_unwind().await, false) },
+                        settings.deadline,
+                        || (Err(Box::new(timeout::DeadlineExceeded)), true),
+                    ),
+                )),
+                stamped_async(in_branch_async(
+                    settings,
+                    name,
+                    Branch::Experiment,
+                    timeout::until(
+                        timeout::within(AssertUnwindSafe(experiment).catch_unwind(), timeout),
+                        settings.deadline,
+                        || match timeout.timeout() {
+                            Some((_, fallback)) => (Ok(fallback), true),
+                            None => (Err(Box::new(timeout::DeadlineExceeded)), true),
+                        },
+                    ),
+                )),
+                settings.control_first,
+                || ((Err(Box::new(timeout::Abandoned)), true), None),
+            )
+            .await;
+            // only reached once both branches completed, a dropped future never publishes
+            let observation =
+                crate::Observation::compared_by(name, control, experiment, comparator)
+                    .timed_out(timed_out)
+                    .control_expired(control_expired)
+                    .matching_both_failed(settings.match_on_both_failed)
+                    .stamped(control_wallclock, experiment_wallclock);
+
+            settle(name, settings, observation, publish)
+        }
+        None => {
+            let control = timeout::until(control.map(Ok), settings.deadline, || {
+                Err(timeout::DeadlineExceeded)
+            });
+            match in_branch_async(settings, name, Branch::Control, control).await {
+                Ok(control) => (control, None),
+                Err(e) => resume_unwind(Box::new(e)),
+            }
+        }
+    }
+}
+
+/// Record and publish the observation of both branches, returning the control and the verdict.
+///
+/// # Panics
+/// Resumes the panic of the control once published.
+fn settle<TC, TE, FP>(
+    name: &'static str,
+    settings: &crate::settings::Settings,
+    observation: crate::Observation<TC, TE>,
+    publish: &FP,
+) -> (TC, Option<bool>)
+where
+    FP: crate::Publisher<TC, TE>,
+{
+    let observation = observation
+        .graced(settings.in_grace_period())
+        .versioned(settings.version)
+        .tagged(&settings.tags);
+
+    settings.record(&observation);
+    in_publish(settings, name, || settings.publish(publish, &observation));
+
+    let verdict = observation.verdict();
+    match observation.control {
+        Ok(control) => (control, verdict),
+        Err(e) => settings.resume(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn experiment_should_derive_the_debug_trait() {
+        let experiment = AsyncExperiment::new("empty experiment");
+
+        assert_ne!(format!("{:?}", experiment), "");
+    }
+
+    #[test]
+    #[should_panic]
+    fn experiment_should_panic_on_empty_string_name() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+
+        let _ = AsyncExperiment::new("");
+    }
+
+    #[test]
+    fn async_experiment_should_return_name_if_it_is_valid() {
+        let actual_name: &str = "Any ľšýžľš is OK";
+        let experiment = AsyncExperiment::new(actual_name);
+
+        assert_eq!(experiment.name(), actual_name);
+    }
+
+    #[test]
+    fn async_experiment_should_return_name_with_control_specified() {
+        let actual_name: &str = "Only control callback";
+        let experiment = AsyncExperiment::new(actual_name).control(async { false });
+
+        assert_eq!(experiment.name(), actual_name);
+    }
+
+    #[test]
+    fn async_experiment_should_return_name_if_control_and_experiment_are_fully_specified() {
+        let name: &str = "Only control callback";
+        let experiment = AsyncExperiment::new(name)
+            .control(async { 1 })
+            .experiment(async { 1 });
+
+        assert_eq!(experiment.name(), name);
+    }
+
+    #[async_std::test]
+    async fn async_experiment_should_always_return_the_control_value() {
+        let expected = 1;
+        let actual = AsyncExperiment::new("Test")
+            .control(async { expected })
+            .experiment(async { expected })
+            .run()
+            .await;
+
+        assert_eq!(actual, expected);
+    }
+
+    #[async_std::test]
+    async fn async_experiment_should_not_run_the_experiment_if_conditioned_not_to() {
+        let expected = 1;
+        let actual = AsyncExperiment::new("Test")
+            .control(async { expected })
+            .experiment(async { expected })
+            .publish(|_o: &crate::Observation<i32, i32>| {})
+            .run_if(|| false)
+            .await;
+
+        assert_eq!(actual, expected);
+    }
+
+    #[async_std::test]
+    async fn async_experiment_should_publish_the_results_when_publish_method_is_specified() {
+        let expected = 1;
+        AsyncExperiment::new("Test")
+            .control(async { expected })
+            .experiment(async { expected })
+            .publish(|o: &crate::Observation<i32, i32>| assert!(o.is_matching()))
+            .run()
+            .await;
+    }
+
+    #[derive(PartialEq, Copy, Clone)]
+    struct TestI64 {
+        value: i64,
+    }
+
+    impl PartialEq<i32> for TestI64 {
+        fn eq(&self, other: &i32) -> bool {
+            self.value as i32 == *other
+        }
+    }
+
+    #[async_std::test]
+    async fn async_experiment_should_work_with_different_return_types_if_they_are_comparable() {
+        let expected: i32 = 1;
+        let expected_as_i64 = TestI64 {
+            value: expected as i64,
+        };
+
+        assert!(expected_as_i64 == expected_as_i64); // implements PartialEq
+
+        AsyncExperiment::new("Test")
+            .control(async { expected })
+            .experiment(async { expected_as_i64 })
+            .publish(|o: &crate::Observation<i32, TestI64>| assert!(o.is_matching()))
+            .run()
+            .await;
+    }
+
+    #[async_std::test]
+    #[should_panic]
+    async fn async_experiment_should_panic_if_control_panics() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+
+        let expected: i32 = 1;
+        AsyncExperiment::new("Test")
+            .control(async { panic!("Oops") })
+            .experiment(async { expected })
+            .publish(|_o: &crate::Observation<i32, i32>| {})
+            .run()
+            .await;
+    }
+
+    #[async_std::test]
+    async fn async_experiment_should_return_control_value_if_the_experiment_value_is_different() {
+        let expected: i32 = 1;
+        AsyncExperiment::new("Test")
             .control(async { expected })
             .experiment(async { expected + 1 })
             .publish(|o: &crate::Observation<i32, i32>| assert!(!o.is_matching()))
             .run()
             .await;
     }
+
+    #[async_std::test]
+    async fn async_experiment_should_verify_the_control_value_after_the_control_completed() {
+        let actual = AsyncExperiment::new("Test")
+            .control(async { 2 })
+            .verify_async(|c: &i32| {
+                let c = *c;
+                async move { c + 1 }
+            })
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert_eq!(o.experiment.as_ref().ok(), Some(&3));
+                assert!(!o.is_matching())
+            })
+            .run()
+            .await;
+
+        assert_eq!(actual, 2);
+    }
+
+    #[async_std::test]
+    async fn async_experiment_should_not_verify_the_control_value_if_conditioned_not_to() {
+        let actual = AsyncExperiment::new("Test")
+            .control(async { 2 })
+            .verify_async(|_: &i32| async { panic!("Verification should not run") })
+            .publish(|_: &crate::Observation<i32, i32>| panic!("Nothing should be published"))
+            .run_if(|| false)
+            .await;
+
+        assert_eq!(actual, 2);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn async_experiment_should_instrument_each_branch_with_its_span() {
+        let subscriber = crate::instrument::tests::EnteredSpans::default();
+        let spans = subscriber.spans.clone();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        AsyncExperiment::new("Test")
+            .control(async { 1 })
+            .experiment(async { 1 })
+            .run()
+            .await;
+
+        let spans = spans.lock().unwrap();
+        assert!(spans.contains(&"control"));
+        assert!(spans.contains(&"experiment"));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn async_experiment_should_publish_within_the_publish_span() {
+        let subscriber = crate::instrument::tests::EnteredSpans::default();
+        let spans = subscriber.spans.clone();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        AsyncExperiment::new("Test")
+            .control(async { 1 })
+            .experiment(async { 1 })
+            .publish(|_: &crate::Observation<i32, i32>| spans.lock().unwrap().push("published"))
+            .run()
+            .await;
+
+        let spans = spans.lock().unwrap();
+        assert_eq!(spans[spans.len() - 2..], ["publish", "published"]);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[async_std::test]
+    async fn async_experiment_should_not_enter_the_publish_span_if_spans_are_disabled() {
+        let subscriber = crate::instrument::tests::EnteredSpans::default();
+        let spans = subscriber.spans.clone();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        AsyncExperiment::new("Test")
+            .control_fn(|| async { 1 })
+            .experiment_fn(|| async { 1 })
+            .with_spans(false)
+            .run()
+            .await;
+
+        assert!(spans.lock().unwrap().is_empty());
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn async_experiment_defined_by_functions_should_be_runnable_repeatedly() {
+        let runs = std::cell::Cell::new(0);
+        let experiment = AsyncExperiment::new("Test")
+            .control_fn(|| async { 1 })
+            .experiment_fn(|| async { 1 })
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert!(o.is_matching());
+                runs.set(runs.get() + 1)
+            });
+
+        assert_eq!(experiment.run().await, 1);
+        assert_eq!(experiment.run().await, 1);
+        assert_eq!(runs.get(), 2);
+    }
+
+    #[async_std::test]
+    async fn async_experiment_defined_by_functions_should_skip_the_experiment_if_told_to() {
+        let actual = AsyncExperiment::new("Test")
+            .control_fn(|| async { 1 })
+            .experiment_fn(|| -> std::future::Ready<i32> { panic!("Should not be created") })
+            .run_if(|| false)
+            .await;
+
+        assert_eq!(actual, 1);
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn async_experiment_defined_by_functions_should_be_shareable_between_tasks() {
+        let runs = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let published = runs.clone();
+        let experiment = std::sync::Arc::new(
+            AsyncExperiment::new("Test")
+                .control_fn(|| async { 1 })
+                .experiment_fn(|| async { 1 })
+                .publish(move |_: &crate::Observation<i32, i32>| {
+                    published.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                }),
+        );
+
+        let tasks = (0..3)
+            .map(|_| {
+                let experiment = experiment.clone();
+                async_std::task::spawn(async move { experiment.run().await })
+            })
+            .collect::<Vec<_>>();
+
+        for task in tasks {
+            assert_eq!(task.await, 1);
+        }
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[async_std::test]
+    async fn async_experiment_should_not_run_the_experiment_under_full_load() {
+        let actual = AsyncExperiment::new("Test")
+            .control_fn(|| async { 1 })
+            .experiment_fn(|| -> std::future::Ready<i32> { panic!("Should not be created") })
+            .adaptive_sample(|| 1.0, 1.0)
+            .run()
+            .await;
+
+        assert_eq!(actual, 1);
+    }
+
+    #[cfg(feature = "wallclock")]
+    #[async_std::test]
+    async fn async_experiment_should_record_the_wallclock_times_of_both_branches() {
+        AsyncExperiment::new("Test")
+            .control(async { 1 })
+            .experiment(async { 1 })
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert!(o.control_finished_at() >= o.control_started_at());
+                assert!(o.experiment_started_at().is_some());
+                assert!(o.experiment_finished_at() >= o.experiment_started_at());
+            })
+            .run()
+            .await;
+    }
+
+    #[async_std::test]
+    async fn async_experiment_should_sample_the_same_runs_with_the_same_seed() {
+        let sampled_runs = |seed| async move {
+            let runs = std::sync::Arc::new(std::sync::Mutex::new(0));
+            let published = runs.clone();
+            let experiment = AsyncExperiment::new("Test")
+                .control_fn(|| async { 1 })
+                .experiment_fn(|| async { 1 })
+                .run_percentage(50.0)
+                .with_seed(seed)
+                .publish(move |_: &crate::Observation<i32, i32>| {
+                    *published.lock().unwrap() += 1;
+                });
+
+            for _ in 0..50 {
+                experiment.run().await;
+            }
+
+            let runs = *runs.lock().unwrap();
+            runs
+        };
+
+        assert_eq!(sampled_runs(42).await, sampled_runs(42).await);
+    }
+
+    #[async_std::test]
+    async fn async_experiment_should_compare_the_mapped_values_using_the_comparator() {
+        let actual = AsyncExperiment::new("Test")
+            .control(async { 1.0_f64 })
+            .experiment(async { "1.01" })
+            .map_experiment(|e: &str| e.parse::<f64>().unwrap())
+            .comparator(crate::compare::approx(0.1))
+            .publish(|o: &crate::Observation<f64, f64>| assert!(o.is_matching()))
+            .run()
+            .await;
+
+        assert_eq!(actual, 1.0);
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn async_experiment_defined_by_functions_should_use_the_comparator() {
+        let runs = std::cell::Cell::new(0);
+        let experiment = AsyncExperiment::new("Test")
+            .control_fn(|| async { 1 })
+            .experiment_fn(|| async { 2 })
+            .map_experiment(|e: i32| e.to_string())
+            .comparator(|c: &i32, e: &String| c.to_string() != *e)
+            .publish(|o: &crate::Observation<i32, String>| {
+                assert!(o.is_matching());
+                runs.set(runs.get() + 1)
+            });
+
+        assert_eq!(experiment.run().await, 1);
+        assert_eq!(experiment.run().await, 1);
+        assert_eq!(runs.get(), 2);
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn async_experiment_defined_by_functions_should_be_awaited_in_a_loop() {
+        let created = std::cell::Cell::new(0);
+        let published = std::cell::Cell::new(0);
+        let experiment = AsyncExperiment::new("Test")
+            .control_fn(|| async { 1 })
+            .experiment_fn(|| {
+                created.set(created.get() + 1);
+                async { 1 }
+            })
+            .publish(|_: &crate::Observation<i32, i32>| published.set(published.get() + 1));
+
+        for _ in 0..3 {
+            assert_eq!(experiment.run().await, 1);
+        }
+
+        assert_eq!(created.get(), 3);
+        assert_eq!(published.get(), 3);
+    }
+
+    #[async_std::test]
+    async fn async_experiment_should_return_control_value_if_the_experiment_panics() {
+        let actual = AsyncExperiment::new("Test")
+            .control(async { 1 })
+            .experiment(async { panic!("Oops") })
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert!(o.control_is_ok());
+                assert!(!o.experiment_is_ok());
+                assert!(!o.is_matching());
+            })
+            .run()
+            .await;
+
+        assert_eq!(actual, 1);
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn async_experiment_should_publish_the_control_panic_before_resuming_it() {
+        let published = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let recorded = published.clone();
+
+        let result = AssertUnwindSafe(
+            AsyncExperiment::new("Test")
+                .control(async { panic!("Oops") })
+                .experiment(async { 1 })
+                .publish(move |o: &crate::Observation<i32, i32>| {
+                    recorded.store(!o.control_is_ok(), std::sync::atomic::Ordering::SeqCst)
+                })
+                .run(),
+        )
+        .catch_unwind()
+        .await;
+
+        assert!(result.is_err());
+        assert!(published.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[async_std::test]
+    async fn async_experiment_should_record_the_panic_of_the_verification() {
+        let actual = AsyncExperiment::new("Test")
+            .control(async { 1 })
+            .verify_async(|_: &i32| async { panic!("Oops") })
+            .publish(|o: &crate::Observation<i32, i32>| assert!(!o.experiment_is_ok()))
+            .run()
+            .await;
+
+        assert_eq!(actual, 1);
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn async_experiment_should_publish_the_control_panic_before_verifying_it() {
+        let published = std::sync::atomic::AtomicBool::new(false);
+
+        let result = AssertUnwindSafe(
+            AsyncExperiment::new("Test")
+                .control(async { panic!("Oops") })
+                .verify_async(|_: &i32| async { panic!("Verification should not run") })
+                .publish(|o: &crate::Observation<i32, i32>| {
+                    assert!(!o.control_is_ok());
+                    assert_eq!(o.verdict(), None);
+                    published.store(true, std::sync::atomic::Ordering::SeqCst);
+                })
+                .run(),
+        )
+        .catch_unwind()
+        .await;
+
+        assert!(result.is_err());
+        assert!(published.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[async_std::test]
+    async fn async_experiment_should_not_verify_the_runs_not_sampled() {
+        let actual = AsyncExperiment::new("Test")
+            .control(async { 2 })
+            .verify_async(|_: &i32| async { panic!("Verification should not run") })
+            .run_percentage(0.0)
+            .publish(|_: &crate::Observation<i32, i32>| panic!("Nothing should be published"))
+            .run()
+            .await;
+
+        assert_eq!(actual, 2);
+    }
+
+    #[async_std::test]
+    async fn async_experiment_defined_by_functions_should_record_the_experiment_panic() {
+        let experiment = AsyncExperiment::new("Test")
+            .control_fn(|| async { 1 })
+            .experiment_fn(|| async { panic!("Oops") })
+            .publish(|o: &crate::Observation<i32, i32>| assert!(!o.experiment_is_ok()));
+
+        assert_eq!(experiment.run().await, 1);
+        assert_eq!(experiment.run().await, 1);
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn async_experiment_should_share_the_match_rate_between_the_clones() {
+        let experiment = AsyncExperiment::new("Test")
+            .control_fn(|| async { 1 })
+            .experiment_fn(|| async { 1 })
+            .track_match_rate(10);
+        let clone = experiment.clone();
+
+        experiment.run().await;
+        clone.run().await;
+
+        assert_eq!(experiment.match_rate(), 1.0);
+    }
+
+    /// Sets the flag once dropped.
+    #[cfg(not(scientisto_disabled))]
+    struct DropFlag(std::sync::Arc<std::sync::atomic::AtomicBool>);
+
+    #[cfg(not(scientisto_disabled))]
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn async_experiment_should_not_publish_if_dropped_before_completion() {
+        let dropped = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let published = std::sync::atomic::AtomicBool::new(false);
+        let guard = DropFlag(dropped.clone());
+
+        let run = AsyncExperiment::new("Test")
+            .control(async { 1 })
+            .experiment(async move {
+                let _guard = guard;
+                futures::future::pending::<i32>().await
+            })
+            .publish(|_: &crate::Observation<i32, i32>| {
+                published.store(true, std::sync::atomic::Ordering::SeqCst)
+            })
+            .run();
+
+        assert_eq!(run.now_or_never(), None);
+        assert!(dropped.load(std::sync::atomic::Ordering::SeqCst));
+        assert!(!published.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn async_fn_experiment_should_not_publish_if_dropped_before_completion() {
+        let published = std::sync::atomic::AtomicBool::new(false);
+        let experiment = AsyncExperiment::new("Test")
+            .control_fn(futures::future::pending::<i32>)
+            .experiment_fn(|| async { 1 })
+            .publish(|_: &crate::Observation<i32, i32>| {
+                published.store(true, std::sync::atomic::Ordering::SeqCst)
+            });
+
+        assert_eq!(experiment.run().now_or_never(), None);
+        assert!(!published.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn async_experiment_should_match_both_panicking_branches_if_enabled() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+        let matching = std::sync::atomic::AtomicBool::new(false);
+
+        let result = AssertUnwindSafe(
+            AsyncExperiment::new("Test")
+                .control(async { panic!("Control") })
+                .experiment(async { panic!("Experiment") })
+                .match_on_both_failed(true)
+                .publish(|o: &crate::Observation<(), ()>| {
+                    matching.store(o.is_matching(), std::sync::atomic::Ordering::SeqCst)
+                })
+                .run(),
+        )
+        .catch_unwind()
+        .await;
+
+        assert!(result.is_err());
+        assert!(matching.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn async_stream_experiment_should_publish_each_input_and_stream_the_control_values() {
+        use futures::StreamExt;
+        let published = std::sync::Mutex::new(Vec::new());
+
+        let results = AsyncExperiment::new("Test")
+            .control_with(|i: i32| async move { i * 2 })
+            .experiment_with(|i: i32| async move { i + 2 })
+            .publish(|o: &crate::Observation<i32, i32>| {
+                published.lock().unwrap().push(o.is_matching())
+            })
+            .run_stream(futures::stream::iter(vec![1, 2, 3]))
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(results, vec![2, 4, 6]);
+        assert_eq!(*published.lock().unwrap(), vec![false, true, false]);
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn async_stream_experiment_should_run_the_matrix_of_inputs() {
+        let published = std::sync::Mutex::new(Vec::new());
+
+        let results = AsyncExperiment::new("Test")
+            .control_with(|i: i32| async move { i * 2 })
+            .experiment_with(|i: i32| async move { i + 2 })
+            .publish(|o: &crate::Observation<i32, i32>| {
+                published.lock().unwrap().push(o.is_matching())
+            })
+            .run_matrix_async(vec![1, 2, 3])
+            .await;
+
+        assert_eq!(results, vec![2, 4, 6]);
+        assert_eq!(*published.lock().unwrap(), vec![false, true, false]);
+    }
+
+    #[async_std::test]
+    async fn experiment_timeout_or_should_compare_the_fallback_of_a_slow_experiment() {
+        let experiment = AsyncExperiment::new("Test")
+            .control_fn(|| async { 1 })
+            .experiment_fn(|| async {
+                futures_timer::Delay::new(std::time::Duration::from_secs(10)).await;
+                2
+            })
+            .experiment_timeout_or(std::time::Duration::from_millis(10), 1)
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert!(o.experiment_timed_out());
+                assert!(o.is_matching());
+            });
+
+        assert_eq!(experiment.run().await, 1);
+    }
+
+    #[async_std::test]
+    async fn experiment_timeout_or_should_never_time_out_the_control() {
+        let result = AsyncExperiment::new("Test")
+            .control(async {
+                futures_timer::Delay::new(std::time::Duration::from_millis(50)).await;
+                1
+            })
+            .experiment(async { 1 })
+            .experiment_timeout_or(std::time::Duration::from_millis(1), 0)
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert!(!o.experiment_timed_out());
+                assert!(o.is_matching());
+            })
+            .run()
+            .await;
+
+        assert_eq!(result, 1);
+    }
+
+    #[async_std::test]
+    async fn experiment_should_tag_the_observation_with_the_version() {
+        AsyncExperiment::new("Test")
+            .control(async { 1 })
+            .experiment(async { 1 })
+            .version("2")
+            .publish(|o: &crate::Observation<i32, i32>| assert_eq!(o.version(), Some("2")))
+            .run()
+            .await;
+    }
+
+    #[async_std::test]
+    async fn control_first_should_keep_an_experiment_completing_within_the_grace_period() {
+        let experiment = AsyncExperiment::new("Test")
+            .control_fn(|| async { 1 })
+            .experiment_fn(|| async {
+                futures_timer::Delay::new(std::time::Duration::from_millis(5)).await;
+                1
+            })
+            .control_first(std::time::Duration::from_secs(10))
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert!(!o.experiment_timed_out());
+                assert!(o.is_matching());
+            });
+
+        assert_eq!(experiment.run().await, 1);
+    }
+
+    #[async_std::test]
+    async fn deadline_should_record_the_experiment_fallback_past_the_deadline() {
+        let result = AsyncExperiment::new("Test")
+            .control(async { 1 })
+            .experiment(futures::future::pending::<i32>())
+            .experiment_timeout_or(std::time::Duration::from_secs(10), 0)
+            .deadline(std::time::Instant::now() + std::time::Duration::from_millis(10))
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert!(o.experiment_timed_out());
+                assert_eq!(o.experiment().ok(), Some(&0));
+            })
+            .run()
+            .await;
+
+        assert_eq!(result, 1);
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn deadline_should_time_out_the_control_and_resume_it_once_published() {
+        let published = std::sync::atomic::AtomicBool::new(false);
+
+        let result = AssertUnwindSafe(
+            AsyncExperiment::new("Test")
+                .control(futures::future::pending::<i32>())
+                .experiment(async { 1 })
+                .deadline(std::time::Instant::now() + std::time::Duration::from_millis(10))
+                .publish(|o: &crate::Observation<i32, i32>| {
+                    assert!(o.control_timed_out());
+                    assert!(!o.experiment_timed_out());
+                    published.store(true, std::sync::atomic::Ordering::SeqCst);
+                })
+                .run(),
+        )
+        .catch_unwind();
+        let result = async_std::future::timeout(std::time::Duration::from_secs(5), result)
+            .await
+            .expect("the deadline must stop the pending control");
+
+        let payload = result.unwrap_err();
+        assert!(payload.is::<timeout::DeadlineExceeded>());
+        assert!(published.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn run_concurrent_should_count_the_verdicts_of_all_the_runs() {
+        let in_flight = std::sync::atomic::AtomicUsize::new(0);
+        let max_in_flight = std::sync::atomic::AtomicUsize::new(0);
+
+        let report = AsyncExperiment::new("Test")
+            .control_fn(|| async {
+                let current = in_flight.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, std::sync::atomic::Ordering::SeqCst);
+                futures_timer::Delay::new(std::time::Duration::from_millis(1)).await;
+                in_flight.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+                1
+            })
+            .experiment_fn(|| async { 2 })
+            .no_publish()
+            .run_concurrent(4, 20)
+            .await;
+
+        assert_eq!((report.runs, report.mismatching), (20, 20));
+        assert!(max_in_flight.load(std::sync::atomic::Ordering::SeqCst) <= 4);
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn race_return_should_return_the_first_value_and_publish_once_settled() {
+        let published = std::sync::Mutex::new(None);
+
+        let (actual, settle) = AsyncExperiment::new("Test")
+            .control(async { 1 })
+            .experiment(async {
+                futures_timer::Delay::new(std::time::Duration::from_millis(10)).await;
+                2
+            })
+            .publish(|o: &crate::Observation<i32, i32>| {
+                *published.lock().unwrap() = Some((o.returned(), o.is_matching()))
+            })
+            .race_return()
+            .await;
+
+        assert_eq!(actual, 1);
+        assert_eq!(*published.lock().unwrap(), None);
+        settle.await;
+        assert_eq!(
+            *published.lock().unwrap(),
+            Some((crate::Returned::Control, false))
+        );
+    }
+
+    #[async_std::test]
+    async fn race_return_should_return_the_slower_value_if_the_faster_branch_panicked() {
+        let (actual, settle) = AsyncExperiment::new("Test")
+            .control(async {
+                futures_timer::Delay::new(std::time::Duration::from_millis(10)).await;
+                1
+            })
+            .experiment(async { panic!("Oops") })
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert_eq!(o.returned(), crate::Returned::Control);
+                assert!(!o.experiment_is_ok());
+            })
+            .race_return()
+            .await;
+
+        assert_eq!(actual, 1);
+        settle.await;
+    }
+
+    #[async_std::test]
+    #[deny(unused_must_use)]
+    async fn async_experiment_run_or_bound_should_not_be_reported_unused() {
+        let experiment = AsyncExperiment::new("Test")
+            .control(async { 1 })
+            .experiment(async { 1 });
+        experiment.run().await;
+
+        AsyncExperiment::new("Test")
+            .control_fn(|| async { 1 })
+            .experiment_fn(|| async { 1 })
+            .no_publish()
+            .run()
+            .await;
+    }
+
+    #[async_std::test]
+    async fn race_return_should_not_return_the_fallback_of_a_timed_out_experiment() {
+        let (actual, settle) = AsyncExperiment::new("Test")
+            .control(async {
+                futures_timer::Delay::new(std::time::Duration::from_millis(50)).await;
+                1
+            })
+            .experiment(futures::future::pending::<i32>())
+            .experiment_timeout_or(std::time::Duration::from_millis(5), 0)
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert_eq!(o.returned(), Returned::Control);
+                assert!(o.experiment_timed_out());
+            })
+            .race_return()
+            .await;
+
+        assert_eq!(actual, 1);
+        settle.await;
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn race_return_should_abandon_the_pending_experiment_after_the_grace_period() {
+        let published = std::sync::atomic::AtomicBool::new(false);
+
+        let (actual, settle) = AsyncExperiment::new("Test")
+            .control(async { 1 })
+            .experiment(futures::future::pending::<i32>())
+            .control_first(std::time::Duration::from_millis(10))
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert!(o.experiment_timed_out());
+                published.store(true, std::sync::atomic::Ordering::SeqCst);
+            })
+            .race_return()
+            .await;
+
+        assert_eq!(actual, 1);
+        async_std::future::timeout(std::time::Duration::from_secs(5), settle)
+            .await
+            .expect("the grace period must abandon the pending experiment");
+        assert!(published.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[async_std::test]
+    async fn async_stream_experiment_should_run_the_matrix_of_inputs_concurrently() {
+        let in_flight = std::sync::atomic::AtomicUsize::new(0);
+        let max_in_flight = std::sync::atomic::AtomicUsize::new(0);
+
+        let results = AsyncExperiment::new("Test")
+            .control_with(|i: i32| {
+                let (in_flight, max_in_flight) = (&in_flight, &max_in_flight);
+                async move {
+                    let current = in_flight.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(current, std::sync::atomic::Ordering::SeqCst);
+                    futures_timer::Delay::new(std::time::Duration::from_millis(5 * (4 - i as u64)))
+                        .await;
+                    in_flight.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+                    i * 2
+                }
+            })
+            .experiment_with(|i: i32| async move { i * 2 })
+            .publish(|_: &crate::Observation<i32, i32>| {})
+            .run_matrix_async(vec![1, 2, 3])
+            .await;
+
+        assert_eq!(results, vec![2, 4, 6]);
+        assert_eq!(max_in_flight.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn verified_experiment_should_compare_by_the_comparator() {
+        let published = std::sync::Mutex::new(None);
+
+        let actual = AsyncExperiment::new("Test")
+            .control(async { 3.00_f64 })
+            .verify_async(|c: &f64| {
+                let c = *c;
+                async move { c + 0.01 }
+            })
+            .comparator(crate::compare::approx(0.1))
+            .publish(|o: &crate::Observation<f64, f64>| {
+                *published.lock().unwrap() = Some(o.is_matching())
+            })
+            .run()
+            .await;
+
+        assert_eq!(actual, 3.00);
+        assert_eq!(*published.lock().unwrap(), Some(true));
+    }
+
+    #[async_std::test]
+    #[cfg(scientisto_disabled)]
+    async fn disabled_async_experiment_should_only_run_the_control() {
+        let actual = AsyncExperiment::new("Test")
+            .control(async { 1 })
+            .experiment(async { panic!("experiment must not run") })
+            .publish(|_: &crate::Observation<i32, i32>| panic!("nothing must be published"))
+            .run()
+            .await;
+
+        assert_eq!(actual, 1);
+    }
+
+    #[async_std::test]
+    #[cfg(scientisto_disabled)]
+    async fn disabled_race_return_should_only_run_the_control() {
+        let (actual, settle) = AsyncExperiment::new("Test")
+            .control(async { 1 })
+            .experiment(async { panic!("experiment must not run") })
+            .publish(|_: &crate::Observation<i32, i32>| panic!("nothing must be published"))
+            .race_return()
+            .await;
+        settle.await;
+
+        assert_eq!(actual, 1);
+    }
 }
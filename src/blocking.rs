@@ -0,0 +1,128 @@
+//! Synchronous branches of the `async` experiments, run on a dedicated thread.
+
+use std::future::Future;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::channel::oneshot;
+
+type Job<T> = Box<dyn FnOnce() -> std::thread::Result<T> + Send>;
+
+/// Blocking branch
+///
+/// Future of a blocking closure passed to `AsyncExperiment::control_blocking` or
+/// `AsyncControlOnly::experiment_blocking`. The closure is started on a dedicated thread once the
+/// future is first polled, so a skipped run never starts it, and the future resolves to its value
+/// without blocking the executor. A panic of the closure is resumed by the future, recording it
+/// like a panic of any other branch.
+#[must_use = "futures do nothing unless polled"]
+pub struct Blocking<T> {
+    job: Option<Job<T>>,
+    receiver: Option<oneshot::Receiver<std::thread::Result<T>>>,
+}
+
+impl<T> Blocking<T>
+where
+    T: Send + 'static,
+{
+    pub(crate) fn new<F>(f: F) -> Self
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        Self {
+            job: Some(Box::new(move || catch_unwind(AssertUnwindSafe(f)))),
+            receiver: None,
+        }
+    }
+}
+
+impl<T> Future for Blocking<T>
+where
+    T: Send + 'static,
+{
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        if let Some(job) = self.job.take() {
+            let (sender, receiver) = oneshot::channel();
+            std::thread::spawn(move || {
+                let _ = sender.send(job());
+            });
+            self.receiver = Some(receiver);
+        }
+
+        let receiver = self
+            .receiver
+            .as_mut()
+            .expect("blocking branch polled after completion");
+        match Pin::new(receiver).poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(result) => {
+                self.receiver = None;
+                match result.expect("blocking branch thread always sends its result") {
+                    Ok(value) => Poll::Ready(value),
+                    Err(e) => resume_unwind(e),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::AsyncExperiment;
+    #[cfg(not(scientisto_disabled))]
+    use crate::Observation;
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn blocking_experiment_should_be_compared_to_the_async_control() {
+        let matching = std::cell::Cell::new(false);
+
+        let actual = AsyncExperiment::new("Test")
+            .control(async { 4 })
+            .experiment_blocking(|| 2 * 2)
+            .publish(|o: &Observation<i32, i32>| matching.set(o.is_matching()))
+            .run()
+            .await;
+
+        assert_eq!(actual, 4);
+        assert!(matching.get());
+    }
+
+    #[async_std::test]
+    #[cfg(not(scientisto_disabled))]
+    async fn blocking_experiment_panic_should_be_recorded() {
+        let failed = std::cell::Cell::new(false);
+
+        let actual = AsyncExperiment::new("Test")
+            .control(async { 4 })
+            .experiment_blocking(|| -> i32 { panic!("Oops") })
+            .publish(|o: &Observation<i32, i32>| failed.set(o.experiment.is_err()))
+            .run()
+            .await;
+
+        assert_eq!(actual, 4);
+        assert!(failed.get());
+    }
+
+    #[async_std::test]
+    async fn blocking_control_should_be_returned_and_its_panic_resumed() {
+        let actual = AsyncExperiment::new("Test")
+            .control_blocking(|| 4)
+            .experiment(async { 5 })
+            .run()
+            .await;
+        assert_eq!(actual, 4);
+
+        let panicking = futures::FutureExt::catch_unwind(std::panic::AssertUnwindSafe(
+            AsyncExperiment::new("Test")
+                .control_blocking(|| -> i32 { panic!("Oops") })
+                .experiment(async { 5 })
+                .run(),
+        ))
+        .await;
+        assert!(panicking.is_err());
+    }
+}
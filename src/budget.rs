@@ -0,0 +1,72 @@
+//! Sampling budget shared by several experiments, bounding their total overhead.
+
+use std::sync::Arc;
+
+use crate::rate_limit::TokenBucket;
+
+/// Sampling budget
+///
+/// Budget of the experiment runs per second shared by all of the experiments it is passed to by
+/// `with_budget`. Each sampled run takes a token from the shared token bucket before running its
+/// experiment branch, the run finding the budget exhausted runs the control only and is skipped
+/// with `SkipReason::Budget`. The bucket allows bursts up to the rate. Cloning the budget shares
+/// the same bucket.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::budget::SamplingBudget;
+/// use scientisto::Experiment;
+///
+/// let budget = SamplingBudget::per_second(1.0);
+/// let first = Experiment::new("First")
+///     .control(|| 1)
+///     .experiment(|| 1)
+///     .with_budget(&budget);
+/// let second = Experiment::new("Second")
+///     .control(|| 2)
+///     .experiment(|| 2)
+///     .with_budget(&budget);
+///
+/// first.run();
+/// second.run();
+///
+/// assert_eq!(budget.exhausted(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SamplingBudget {
+    bucket: Arc<TokenBucket>,
+}
+
+impl SamplingBudget {
+    /// Allow at most `max_runs` runs of the experiment branches per second in total.
+    pub fn per_second(max_runs: f64) -> Self {
+        Self {
+            bucket: Arc::new(TokenBucket::new(max_runs)),
+        }
+    }
+
+    /// Number of the runs skipped so far because the budget was exhausted.
+    pub fn exhausted(&self) -> u64 {
+        self.bucket.dropped()
+    }
+
+    /// Take a run from the budget, if any is left.
+    pub(crate) fn acquire(&self) -> bool {
+        self.bucket.acquire()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampling_budget_should_be_shared_by_its_clones() {
+        let budget = SamplingBudget::per_second(1.0);
+        let clone = budget.clone();
+
+        assert!(clone.acquire());
+        assert!(!budget.acquire());
+        assert_eq!(clone.exhausted(), 1);
+    }
+}
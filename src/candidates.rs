@@ -0,0 +1,228 @@
+//! Experiments comparing several weighted **candidates** to the **control** at once.
+
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+use crate::publisher::{DefaultPublisher, Publisher};
+use crate::Observation;
+
+struct Candidate<'a, T> {
+    name: &'static str,
+    weight: u32,
+    f: Box<dyn Fn() -> T + 'a>,
+}
+
+/// Candidate experiment
+///
+/// Experiment running several named and weighted candidate implementations next to the
+/// **control**. Each candidate is compared to the control using `PartialEq` and published as a
+/// separate observation named after the candidate. The candidates run one after another, a panic
+/// in a candidate is recorded in its observation.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::{Experiment,Observation};
+///
+/// let result = Experiment::new("Promotion")
+///     .control(|| 1)
+///     .candidate("rewrite", 10, || 1)
+///     .candidate("prototype", 1, || 2)
+///     .publish(|o: &Observation<i32, i32>| println!("{}: {}", o.name(), o.is_matching()))
+///     .run_weighted();
+///
+/// assert_eq!(result, 1);
+/// ```
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct CandidateExperiment<'a, TC, FC, FP = DefaultPublisher>
+where
+    FC: Fn() -> TC,
+{
+    name: &'static str,
+    control: FC,
+    candidates: Vec<Candidate<'a, TC>>,
+    publish: FP,
+}
+
+impl<'a, TC, FC, FP> CandidateExperiment<'a, TC, FC, FP>
+where
+    FC: Fn() -> TC,
+{
+    pub(crate) fn new(name: &'static str, control: FC, publish: FP) -> Self {
+        Self {
+            name,
+            control,
+            candidates: Vec::new(),
+            publish,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Add a named candidate with the weight used by `run_weighted`.
+    pub fn candidate<F>(mut self, name: &'static str, weight: u32, f: F) -> Self
+    where
+        F: Fn() -> TC + 'a,
+    {
+        self.candidates.push(Candidate {
+            name,
+            weight,
+            f: Box::new(f),
+        });
+        self
+    }
+
+    pub fn publish<F>(self, f: F) -> CandidateExperiment<'a, TC, FC, F>
+    where
+        F: Fn(&Observation<TC, TC>),
+    {
+        CandidateExperiment {
+            name: self.name,
+            control: self.control,
+            candidates: self.candidates,
+            publish: f,
+        }
+    }
+
+    /// Run the control and all of the candidates, returning the control value.
+    ///
+    /// # Panics
+    /// Panics if the **control** function panics, once all of the candidates are published.
+    pub fn run(&self) -> TC
+    where
+        TC: PartialEq,
+        FP: Publisher<TC, TC>,
+    {
+        match self.conduct(false) {
+            Ok(value) => value,
+            Err(e) => resume_unwind(e),
+        }
+    }
+
+    /// Run the control and all of the candidates, returning the value of the highest-weighted
+    /// matching candidate.
+    ///
+    /// Falls back to the control value if no candidate matches, from the candidates with the same
+    /// weight the first one added wins. All of the candidates are published regardless.
+    ///
+    /// # Panics
+    /// Panics if the **control** function panics, since no candidate can match then.
+    pub fn run_weighted(&self) -> TC
+    where
+        TC: PartialEq,
+        FP: Publisher<TC, TC>,
+    {
+        match self.conduct(true) {
+            Ok(value) => value,
+            Err(e) => resume_unwind(e),
+        }
+    }
+
+    fn conduct(&self, weighted: bool) -> std::thread::Result<TC>
+    where
+        TC: PartialEq,
+        FP: Publisher<TC, TC>,
+    {
+        if cfg!(scientisto_disabled) {
+            return Ok((self.control)());
+        }
+
+        let mut control = catch_unwind(AssertUnwindSafe(&self.control));
+        let mut selected: Option<(u32, TC)> = None;
+
+        for candidate in &self.candidates {
+            let experiment = catch_unwind(AssertUnwindSafe(&candidate.f));
+            let observation = Observation::new(candidate.name, control, experiment);
+
+            self.publish.publish(&observation);
+
+            let matching = observation.is_matching();
+            control = observation.control;
+            if weighted && matching && selected.as_ref().map_or(true, |s| candidate.weight > s.0) {
+                if let Ok(value) = observation.experiment {
+                    selected = Some((candidate.weight, value));
+                }
+            }
+        }
+
+        match selected {
+            Some((_, value)) => Ok(value),
+            None => control,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Experiment;
+    #[cfg(not(scientisto_disabled))]
+    use crate::Observation;
+
+    #[derive(Debug)]
+    struct Tagged(i32, &'static str);
+
+    impl PartialEq for Tagged {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn candidate_experiment_should_publish_each_candidate_and_return_the_control_value() {
+        let published = std::cell::RefCell::new(Vec::new());
+
+        let actual = Experiment::new("Test")
+            .control(|| Tagged(1, "control"))
+            .candidate("first", 1, || Tagged(1, "first"))
+            .candidate("second", 2, || Tagged(2, "second"))
+            .publish(|o: &Observation<Tagged, Tagged>| {
+                published.borrow_mut().push((o.name(), o.is_matching()))
+            })
+            .run();
+
+        assert_eq!(actual.1, "control");
+        assert_eq!(
+            published.into_inner(),
+            vec![("first", true), ("second", false)]
+        );
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn candidate_experiment_should_return_the_highest_weighted_matching_candidate() {
+        let actual = Experiment::new("Test")
+            .control(|| Tagged(1, "control"))
+            .candidate("light", 1, || Tagged(1, "light"))
+            .candidate("heavy", 5, || Tagged(1, "heavy"))
+            .candidate("heaviest", 9, || Tagged(2, "heaviest"))
+            .candidate("tied", 5, || Tagged(1, "tied"))
+            .run_weighted();
+
+        assert_eq!(actual.1, "heavy");
+    }
+
+    #[test]
+    fn candidate_experiment_should_fall_back_to_the_control_if_no_candidate_matches() {
+        let actual = Experiment::new("Test")
+            .control(|| Tagged(1, "control"))
+            .candidate("different", 1, || Tagged(2, "different"))
+            .candidate("panicking", 2, || -> Tagged { panic!("Oops") })
+            .run_weighted();
+
+        assert_eq!(actual.1, "control");
+    }
+
+    #[test]
+    #[cfg(scientisto_disabled)]
+    fn disabled_candidate_experiment_should_only_run_the_control() {
+        let actual = Experiment::new("Test")
+            .control(|| Tagged(1, "control"))
+            .candidate("heavy", 5, || -> Tagged {
+                panic!("candidate must not run")
+            })
+            .run_weighted();
+
+        assert_eq!(actual.1, "control");
+    }
+}
@@ -0,0 +1,1649 @@
+//! Ready-made comparators deciding whether the **control** and **experiment** values match.
+//!
+//! Any `Fn(&T, &TE) -> bool` closure is a `Comparator`, the types in this module cover the common
+//! cases where plain `PartialEq` is too strict.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash};
+use std::time::{Duration, SystemTime};
+
+/// Comparison
+///
+/// Outcome of comparing the control and experiment values, optionally detailing the difference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    pub matching: bool,
+    pub detail: Option<String>,
+}
+
+impl From<bool> for Comparison {
+    fn from(matching: bool) -> Self {
+        Self {
+            matching,
+            detail: None,
+        }
+    }
+}
+
+/// Mismatch detail
+///
+/// Structured shape of the mismatch of two collections, see `Comparator::mismatch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MismatchDetail {
+    /// The collections have different lengths.
+    LengthDiffers {
+        control_len: usize,
+        experiment_len: usize,
+    },
+    /// The collections have the same length, the element at the index is the first differing.
+    ElementDiffers { index: usize },
+    /// The values differ in another way.
+    Other,
+}
+
+/// Comparator
+///
+/// Decides whether the values produced by the control and experiment are matching.
+pub trait Comparator<T, TE> {
+    /// Compare the control and experiment values.
+    fn compare(&self, control: &T, experiment: &TE) -> bool;
+
+    /// Name of the comparison strategy, recorded as the `comparator_name` of the observation.
+    fn name(&self) -> &'static str {
+        "custom"
+    }
+
+    /// Compare the control and experiment values, detailing the difference for the observation.
+    fn describe(&self, control: &T, experiment: &TE) -> Comparison {
+        self.compare(control, experiment).into()
+    }
+
+    /// Compare the control and experiment values, failing with the message of the error if the
+    /// comparison itself failed, e.g. see `Fallible`.
+    fn try_describe(&self, control: &T, experiment: &TE) -> Result<Comparison, String> {
+        Ok(self.describe(control, experiment))
+    }
+
+    /// Whether the mismatch of the values is a known one to be ignored, see `IgnoreWhen`.
+    fn ignores(&self, _control: &T, _experiment: &TE) -> bool {
+        false
+    }
+
+    /// Structured shape of the mismatch, evaluated for the mismatching values only, see
+    /// `ElementWise`.
+    fn mismatch(&self, _control: &T, _experiment: &TE) -> Option<MismatchDetail> {
+        None
+    }
+
+    /// Human-readable diff of the values, evaluated for the mismatching values only, see
+    /// `diff::Diffed`.
+    fn diff(&self, _control: &T, _experiment: &TE) -> Option<String> {
+        None
+    }
+}
+
+impl<T, TE, F> Comparator<T, TE> for F
+where
+    F: Fn(&T, &TE) -> bool,
+{
+    fn compare(&self, control: &T, experiment: &TE) -> bool {
+        self(control, experiment)
+    }
+}
+
+/// Comparator whose comparison itself can fail, e.g. when deserializing both of the values.
+///
+/// A failed comparison is neither matching nor mismatching, the message of the error is recorded
+/// as the `compare_error` of the observation.
+#[derive(Debug, Clone, Copy)]
+pub struct Fallible<F> {
+    compare: F,
+}
+
+impl<T, TE, E, F> Comparator<T, TE> for Fallible<F>
+where
+    F: Fn(&T, &TE) -> Result<bool, E>,
+    E: std::fmt::Display,
+{
+    fn compare(&self, control: &T, experiment: &TE) -> bool {
+        (self.compare)(control, experiment).unwrap_or(false)
+    }
+
+    fn name(&self) -> &'static str {
+        "fallible"
+    }
+
+    fn try_describe(&self, control: &T, experiment: &TE) -> Result<Comparison, String> {
+        (self.compare)(control, experiment)
+            .map(Comparison::from)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Compare the values by a fallible comparison, see `Fallible`.
+pub fn fallible<F>(compare: F) -> Fallible<F> {
+    Fallible { compare }
+}
+
+/// Comparator marking the mismatches satisfying the predicate as ignored.
+///
+/// The values are compared by the inner comparator, the predicate is only evaluated for the
+/// mismatching values. An ignored mismatch is still not matching, see
+/// `Observation::matching_or_ignored`.
+#[derive(Debug, Clone, Copy)]
+pub struct IgnoreWhen<C, P> {
+    inner: C,
+    predicate: P,
+}
+
+impl<T, TE, C, P> Comparator<T, TE> for IgnoreWhen<C, P>
+where
+    C: Comparator<T, TE>,
+    P: Fn(&T, &TE) -> bool,
+{
+    fn compare(&self, control: &T, experiment: &TE) -> bool {
+        self.inner.compare(control, experiment)
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn describe(&self, control: &T, experiment: &TE) -> Comparison {
+        self.inner.describe(control, experiment)
+    }
+
+    fn try_describe(&self, control: &T, experiment: &TE) -> Result<Comparison, String> {
+        self.inner.try_describe(control, experiment)
+    }
+
+    fn ignores(&self, control: &T, experiment: &TE) -> bool {
+        (self.predicate)(control, experiment) || self.inner.ignores(control, experiment)
+    }
+
+    fn mismatch(&self, control: &T, experiment: &TE) -> Option<MismatchDetail> {
+        self.inner.mismatch(control, experiment)
+    }
+
+    fn diff(&self, control: &T, experiment: &TE) -> Option<String> {
+        self.inner.diff(control, experiment)
+    }
+}
+
+/// Ignore the mismatches of the inner comparator satisfying the predicate, see `IgnoreWhen`.
+pub fn ignore_when<C, P>(inner: C, predicate: P) -> IgnoreWhen<C, P> {
+    IgnoreWhen { inner, predicate }
+}
+
+/// Default comparator using the `PartialEq` implementation of the experiment value.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Equality;
+
+impl<T, TE> Comparator<T, TE> for Equality
+where
+    TE: PartialEq<T>,
+{
+    fn compare(&self, control: &T, experiment: &TE) -> bool {
+        experiment == control
+    }
+
+    fn name(&self) -> &'static str {
+        "PartialEq"
+    }
+}
+
+/// Comparator matching `Option`s and `Result`s by their variant only, ignoring the inner values.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::{Experiment,Observation};
+///
+/// Experiment::new("Something vs nothing")
+///     .control(|| Some(1))
+///     .experiment(|| Some(2))
+///     .comparator(scientisto::compare::by_variant())
+///     .publish(|o: &Observation<Option<i32>, Option<i32>>| assert!(o.is_matching()))
+///     .run();
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ByVariant;
+
+impl<T, TE> Comparator<Option<T>, Option<TE>> for ByVariant {
+    fn compare(&self, control: &Option<T>, experiment: &Option<TE>) -> bool {
+        control.is_some() == experiment.is_some()
+    }
+
+    fn name(&self) -> &'static str {
+        "by_variant"
+    }
+}
+
+impl<T, E, TE, EE> Comparator<Result<T, E>, Result<TE, EE>> for ByVariant {
+    fn compare(&self, control: &Result<T, E>, experiment: &Result<TE, EE>) -> bool {
+        control.is_ok() == experiment.is_ok()
+    }
+
+    fn name(&self) -> &'static str {
+        "by_variant"
+    }
+}
+
+/// Compare `Option`s and `Result`s by their variant only, see `ByVariant`.
+pub fn by_variant() -> ByVariant {
+    ByVariant
+}
+
+/// Comparator matching `Result`s with unrelated error types by their `Ok` values only.
+///
+/// Two `Ok` values are compared using `PartialEq`, two errors are matching without being compared
+/// and an error on one side only is a mismatch. The messages of the errors are recorded as the
+/// detail of the comparison, so that the error types need to implement `Display` only.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OkOnly;
+
+impl<T, E, TE, EE> Comparator<Result<T, E>, Result<TE, EE>> for OkOnly
+where
+    TE: PartialEq<T>,
+    E: std::fmt::Display,
+    EE: std::fmt::Display,
+{
+    fn compare(&self, control: &Result<T, E>, experiment: &Result<TE, EE>) -> bool {
+        match (control, experiment) {
+            (Ok(c), Ok(e)) => e == c,
+            (Err(_), Err(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "ok_only"
+    }
+
+    fn describe(&self, control: &Result<T, E>, experiment: &Result<TE, EE>) -> Comparison {
+        let errors = [
+            control
+                .as_ref()
+                .err()
+                .map(|e| format!("control error: {}", e)),
+            experiment
+                .as_ref()
+                .err()
+                .map(|e| format!("experiment error: {}", e)),
+        ];
+        let errors = errors.into_iter().flatten().collect::<Vec<_>>();
+
+        Comparison {
+            matching: self.compare(control, experiment),
+            detail: (!errors.is_empty()).then(|| errors.join(", ")),
+        }
+    }
+}
+
+/// Compare `Result`s by their `Ok` values only, see `OkOnly`.
+pub fn ok_only() -> OkOnly {
+    OkOnly
+}
+
+/// Comparator treating an error returned by the experiment as a mismatch, see `try_experiment`.
+///
+/// The `Ok` value of the experiment is compared to the control by the inner comparator, `PartialEq`
+/// by default. An error is never matching and its message is recorded as the detail.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ExperimentOk<C = Equality> {
+    inner: C,
+}
+
+impl<T, TE, E, C> Comparator<T, Result<TE, E>> for ExperimentOk<C>
+where
+    C: Comparator<T, TE>,
+    E: std::fmt::Display,
+{
+    fn compare(&self, control: &T, experiment: &Result<TE, E>) -> bool {
+        match experiment {
+            Ok(experiment) => self.inner.compare(control, experiment),
+            Err(_) => false,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "experiment_ok"
+    }
+
+    fn describe(&self, control: &T, experiment: &Result<TE, E>) -> Comparison {
+        match experiment {
+            Ok(experiment) => self.inner.describe(control, experiment),
+            Err(e) => Comparison {
+                matching: false,
+                detail: Some(format!("experiment error: {}", e)),
+            },
+        }
+    }
+}
+
+/// Compare the `Ok` value of the experiment by the inner comparator, see `ExperimentOk`.
+pub fn experiment_ok<C>(inner: C) -> ExperimentOk<C> {
+    ExperimentOk { inner }
+}
+
+/// Comparator matching the control to any of the golden values of the experiment, see
+/// `expect_any`.
+///
+/// The goldens are compared by the inner comparator in order, `PartialEq` by default. The index
+/// of the first matching golden is recorded as the detail, `golden: none` if none matched.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::compare::{self, Comparator};
+///
+/// let comparison = compare::any_of(compare::Equality).describe(&2, &vec![1, 2, 3]);
+/// assert!(comparison.matching);
+/// assert_eq!(comparison.detail.as_deref(), Some("golden: 1"));
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AnyOf<C = Equality> {
+    inner: C,
+}
+
+impl<T, TE, C> Comparator<T, Vec<TE>> for AnyOf<C>
+where
+    C: Comparator<T, TE>,
+{
+    fn compare(&self, control: &T, goldens: &Vec<TE>) -> bool {
+        goldens
+            .iter()
+            .any(|golden| self.inner.compare(control, golden))
+    }
+
+    fn name(&self) -> &'static str {
+        "any_of"
+    }
+
+    fn describe(&self, control: &T, goldens: &Vec<TE>) -> Comparison {
+        let matched = goldens
+            .iter()
+            .position(|golden| self.inner.compare(control, golden));
+
+        Comparison {
+            matching: matched.is_some(),
+            detail: Some(match matched {
+                Some(index) => format!("golden: {}", index),
+                None => String::from("golden: none"),
+            }),
+        }
+    }
+}
+
+/// Match the control to any of the goldens by the inner comparator, see `AnyOf`.
+pub fn any_of<C>(inner: C) -> AnyOf<C> {
+    AnyOf { inner }
+}
+
+/// Comparator matching two values by the projections of their selected parts, e.g. a field.
+///
+/// The projections are compared using `PartialEq`, the rest of the values is ignored.
+#[derive(Debug, Clone, Copy)]
+pub struct Projected<P> {
+    projection: P,
+}
+
+impl<T, K, P> Comparator<T, T> for Projected<P>
+where
+    P: Fn(&T) -> K,
+    K: PartialEq,
+{
+    fn compare(&self, control: &T, experiment: &T) -> bool {
+        (self.projection)(control) == (self.projection)(experiment)
+    }
+
+    fn name(&self) -> &'static str {
+        "projected"
+    }
+}
+
+/// Compare values of the same type by their projections, see `Projected`.
+pub fn projected<P>(projection: P) -> Projected<P> {
+    Projected { projection }
+}
+
+/// Comparator matching two values by their canonical forms, e.g. sorted or normalized.
+///
+/// The same canonicalization is applied to both of the values and the canonical forms are compared
+/// by the inner comparator, `PartialEq` by default. Unlike `Projected`, the inner comparator is
+/// kept, e.g. canonical floats can still be compared by `approx`.
+#[derive(Debug, Clone, Copy)]
+pub struct Canonicalized<C, FM = Equality> {
+    canonicalize: C,
+    inner: FM,
+}
+
+impl<T, K, C, FM> Comparator<T, T> for Canonicalized<C, FM>
+where
+    C: Fn(&T) -> K,
+    FM: Comparator<K, K>,
+{
+    fn compare(&self, control: &T, experiment: &T) -> bool {
+        self.inner.compare(
+            &(self.canonicalize)(control),
+            &(self.canonicalize)(experiment),
+        )
+    }
+
+    fn name(&self) -> &'static str {
+        "canonicalized"
+    }
+
+    fn describe(&self, control: &T, experiment: &T) -> Comparison {
+        self.inner.describe(
+            &(self.canonicalize)(control),
+            &(self.canonicalize)(experiment),
+        )
+    }
+
+    fn try_describe(&self, control: &T, experiment: &T) -> Result<Comparison, String> {
+        self.inner.try_describe(
+            &(self.canonicalize)(control),
+            &(self.canonicalize)(experiment),
+        )
+    }
+
+    fn ignores(&self, control: &T, experiment: &T) -> bool {
+        self.inner.ignores(
+            &(self.canonicalize)(control),
+            &(self.canonicalize)(experiment),
+        )
+    }
+
+    fn mismatch(&self, control: &T, experiment: &T) -> Option<MismatchDetail> {
+        self.inner.mismatch(
+            &(self.canonicalize)(control),
+            &(self.canonicalize)(experiment),
+        )
+    }
+
+    fn diff(&self, control: &T, experiment: &T) -> Option<String> {
+        self.inner.diff(
+            &(self.canonicalize)(control),
+            &(self.canonicalize)(experiment),
+        )
+    }
+}
+
+/// Compare the canonical forms of the values by the inner comparator, see `Canonicalized`.
+pub fn canonicalized<C, FM>(canonicalize: C, inner: FM) -> Canonicalized<C, FM> {
+    Canonicalized {
+        canonicalize,
+        inner,
+    }
+}
+
+/// Comparator matching two collections equal once sorted by the key, e.g. unordered query results.
+///
+/// The sort is stable, the elements of the same key are expected in the same relative order. The
+/// keys have to be totally ordered, a key incomparable even to itself (e.g. `NaN`) fails the
+/// comparison with the `compare_error` instead of guessing its position.
+#[derive(Debug, Clone, Copy)]
+pub struct SortedBy<F> {
+    key: F,
+}
+
+impl<F> SortedBy<F> {
+    fn sorted<'a, T, K>(&self, values: &'a [T]) -> Result<Vec<&'a T>, String>
+    where
+        F: Fn(&T) -> K,
+        K: PartialOrd,
+    {
+        let mut keyed = values
+            .iter()
+            .map(|value| ((self.key)(value), value))
+            .collect::<Vec<_>>();
+
+        if let Some(index) = keyed
+            .iter()
+            .position(|(key, _)| key.partial_cmp(key).is_none())
+        {
+            return Err(format!("sort key of index {} is not ordered", index));
+        }
+        keyed.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        Ok(keyed.into_iter().map(|(_, value)| value).collect())
+    }
+}
+
+impl<T, K, F> Comparator<Vec<T>, Vec<T>> for SortedBy<F>
+where
+    T: PartialEq,
+    F: Fn(&T) -> K,
+    K: PartialOrd,
+{
+    fn compare(&self, control: &Vec<T>, experiment: &Vec<T>) -> bool {
+        self.try_describe(control, experiment)
+            .is_ok_and(|comparison| comparison.matching)
+    }
+
+    fn name(&self) -> &'static str {
+        "sorted_by"
+    }
+
+    fn try_describe(&self, control: &Vec<T>, experiment: &Vec<T>) -> Result<Comparison, String> {
+        Ok((self.sorted(control)? == self.sorted(experiment)?).into())
+    }
+}
+
+/// Compare the collections sorted by the key, see `SortedBy`.
+pub fn sorted_by<F>(key: F) -> SortedBy<F> {
+    SortedBy { key }
+}
+
+/// Comparator matching values of different types by the projections of their selected parts.
+///
+/// Each side is projected by its own accessor and the experiment projection is compared to the
+/// control projection using `PartialEq`.
+#[derive(Debug, Clone, Copy)]
+pub struct ProjectedEach<PC, PE> {
+    control: PC,
+    experiment: PE,
+}
+
+impl<T, TE, K, KE, PC, PE> Comparator<T, TE> for ProjectedEach<PC, PE>
+where
+    PC: Fn(&T) -> K,
+    PE: Fn(&TE) -> KE,
+    KE: PartialEq<K>,
+{
+    fn compare(&self, control: &T, experiment: &TE) -> bool {
+        (self.experiment)(experiment) == (self.control)(control)
+    }
+
+    fn name(&self) -> &'static str {
+        "projected_each"
+    }
+}
+
+/// Compare values of different types by their projections, see `ProjectedEach`.
+pub fn projected_each<PC, PE>(control: PC, experiment: PE) -> ProjectedEach<PC, PE> {
+    ProjectedEach {
+        control,
+        experiment,
+    }
+}
+
+/// Reset of an excluded part of a value.
+type Exclusion<T> = Box<dyn Fn(&mut T)>;
+
+/// Comparator excluding the named parts of the values, e.g. generated ids or timestamps.
+///
+/// Each exclusion resets its part on copies of both of the values, which are then compared using
+/// the inner comparator (`PartialEq` by default), e.g. a tolerant `struct_fields!` comparator. The
+/// names of the applied exclusions are recorded as the detail of the comparison.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::compare::{self, Comparator};
+///
+/// #[derive(Clone, PartialEq)]
+/// struct Order { id: u64, total: f64, created_at: u64 }
+///
+/// let comparator = compare::ignoring("id", |o: &mut Order| o.id = 0)
+///     .ignoring("created_at", |o: &mut Order| o.created_at = 0)
+///     .comparing_with(compare::struct_fields!(Order { id, total => compare::approx(0.01), created_at }));
+///
+/// let control = Order { id: 1, total: 9.99, created_at: 10 };
+/// let experiment = Order { id: 2, total: 9.991, created_at: 20 };
+///
+/// let comparison = comparator.describe(&control, &experiment);
+/// assert!(comparison.matching);
+/// assert_eq!(comparison.detail.as_deref(), Some("ignored: id, created_at"));
+/// ```
+pub struct Ignoring<T, C = Equality> {
+    exclusions: Vec<(&'static str, Exclusion<T>)>,
+    comparator: C,
+}
+
+impl<T, C> Ignoring<T, C> {
+    /// Exclude another named part of the values.
+    pub fn ignoring<F>(mut self, name: &'static str, exclude: F) -> Self
+    where
+        F: Fn(&mut T) + 'static,
+    {
+        self.exclusions.push((name, Box::new(exclude)));
+        self
+    }
+
+    /// Compare the values with the exclusions applied using the comparator.
+    pub fn comparing_with<CC>(self, comparator: CC) -> Ignoring<T, CC> {
+        Ignoring {
+            exclusions: self.exclusions,
+            comparator,
+        }
+    }
+
+    fn excluded(&self, value: &T) -> T
+    where
+        T: Clone,
+    {
+        let mut value = value.clone();
+        self.exclusions
+            .iter()
+            .for_each(|(_, exclude)| exclude(&mut value));
+        value
+    }
+}
+
+impl<T, C> Comparator<T, T> for Ignoring<T, C>
+where
+    T: Clone,
+    C: Comparator<T, T>,
+{
+    fn compare(&self, control: &T, experiment: &T) -> bool {
+        self.comparator
+            .compare(&self.excluded(control), &self.excluded(experiment))
+    }
+
+    fn name(&self) -> &'static str {
+        "ignoring"
+    }
+
+    fn describe(&self, control: &T, experiment: &T) -> Comparison {
+        let comparison = self
+            .comparator
+            .describe(&self.excluded(control), &self.excluded(experiment));
+        let names = self
+            .exclusions
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>();
+        let ignored = (!names.is_empty()).then(|| format!("ignored: {}", names.join(", ")));
+
+        Comparison {
+            matching: comparison.matching,
+            detail: match (ignored, comparison.detail) {
+                (Some(ignored), Some(detail)) => Some(format!("{}; {}", ignored, detail)),
+                (ignored, detail) => ignored.or(detail),
+            },
+        }
+    }
+}
+
+/// Compare values excluding the named part, see `Ignoring`.
+pub fn ignoring<T, F>(name: &'static str, exclude: F) -> Ignoring<T>
+where
+    F: Fn(&mut T) + 'static,
+{
+    Ignoring {
+        exclusions: Vec::new(),
+        comparator: Equality,
+    }
+    .ignoring(name, exclude)
+}
+
+/// Comparator matching two values by their hash digests instead of comparing them directly.
+///
+/// Useful for large values, which are cheaper to hash than to compare. Both digests are recorded
+/// as the detail of the comparison. The digests are produced by the hasher built by the
+/// `BuildHasher`, which needs to be deterministic, e.g. the default `DefaultHasher`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ByHash<H> {
+    hasher: H,
+}
+
+impl<T, TE, H> Comparator<T, TE> for ByHash<H>
+where
+    T: Hash,
+    TE: Hash,
+    H: BuildHasher,
+{
+    fn compare(&self, control: &T, experiment: &TE) -> bool {
+        self.hasher.hash_one(control) == self.hasher.hash_one(experiment)
+    }
+
+    fn name(&self) -> &'static str {
+        "by_hash"
+    }
+
+    fn describe(&self, control: &T, experiment: &TE) -> Comparison {
+        let control = self.hasher.hash_one(control);
+        let experiment = self.hasher.hash_one(experiment);
+
+        Comparison {
+            matching: control == experiment,
+            detail: Some(format!(
+                "control digest: {:016x}, experiment digest: {:016x}",
+                control, experiment
+            )),
+        }
+    }
+}
+
+/// Compare values by their digests produced by the hasher, see `ByHash`.
+pub fn by_hash<H: BuildHasher>(hasher: H) -> ByHash<H> {
+    ByHash { hasher }
+}
+
+/// Compare values by their digests produced by the `DefaultHasher`, see `ByHash`.
+pub fn by_default_hash() -> ByHash<BuildHasherDefault<DefaultHasher>> {
+    ByHash::default()
+}
+
+/// Comparator treating two floats as matching when they are equal or both are `NaN`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NanEq;
+
+impl Comparator<f32, f32> for NanEq {
+    fn compare(&self, control: &f32, experiment: &f32) -> bool {
+        control == experiment || (control.is_nan() && experiment.is_nan())
+    }
+
+    fn name(&self) -> &'static str {
+        "nan_eq"
+    }
+}
+
+impl Comparator<f64, f64> for NanEq {
+    fn compare(&self, control: &f64, experiment: &f64) -> bool {
+        control == experiment || (control.is_nan() && experiment.is_nan())
+    }
+
+    fn name(&self) -> &'static str {
+        "nan_eq"
+    }
+}
+
+/// Compare floats treating `NaN`s as equal to each other, see `NanEq`.
+pub fn nan_eq() -> NanEq {
+    NanEq
+}
+
+/// Comparator treating two floats as matching when they differ by at most the absolute epsilon.
+///
+/// Two `NaN`s are matching, a `NaN` never matches a number.
+#[derive(Debug, Clone, Copy)]
+pub struct Approx {
+    abs_eps: f64,
+}
+
+impl Comparator<f32, f32> for Approx {
+    fn compare(&self, control: &f32, experiment: &f32) -> bool {
+        self.compare(&(*control as f64), &(*experiment as f64))
+    }
+
+    fn name(&self) -> &'static str {
+        "approx"
+    }
+}
+
+impl Comparator<f64, f64> for Approx {
+    fn compare(&self, control: &f64, experiment: &f64) -> bool {
+        NanEq.compare(control, experiment) || (control - experiment).abs() <= self.abs_eps
+    }
+
+    fn name(&self) -> &'static str {
+        "approx"
+    }
+}
+
+/// Compare floats within the absolute tolerance, see `Approx`.
+pub fn approx(abs_eps: f64) -> Approx {
+    Approx { abs_eps }
+}
+
+/// Shape of the mismatch of two slices, `None` if all of the elements are matching.
+fn mismatch_of<T, TE>(
+    control: &[T],
+    experiment: &[TE],
+    matching: impl Fn(&T, &TE) -> bool,
+) -> Option<MismatchDetail> {
+    if control.len() != experiment.len() {
+        return Some(MismatchDetail::LengthDiffers {
+            control_len: control.len(),
+            experiment_len: experiment.len(),
+        });
+    }
+
+    control
+        .iter()
+        .zip(experiment)
+        .position(|(c, e)| !matching(c, e))
+        .map(|index| MismatchDetail::ElementDiffers { index })
+}
+
+/// Comparator matching two `Vec`s element by element, detailing the shape of the mismatch.
+///
+/// The elements at the same index are compared by the inner comparator, `PartialEq` by default,
+/// `Vec`s of different lengths never match. On mismatch, the observation records the structured
+/// `MismatchDetail` and its textual form as the detail.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::compare::{self, MismatchDetail};
+/// use scientisto::{Experiment,Observation};
+///
+/// Experiment::new("Shape")
+///     .control(|| vec![1, 2, 3])
+///     .experiment(|| vec![1, 2])
+///     .comparator(compare::elements(compare::Equality))
+///     .publish(|o: &Observation<Vec<i32>, Vec<i32>>| {
+///         assert_eq!(
+///             o.mismatch(),
+///             Some(MismatchDetail::LengthDiffers { control_len: 3, experiment_len: 2 })
+///         );
+///         assert_eq!(o.detail(), Some("length: 3 != 2"));
+///     })
+///     .run();
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ElementWise<C = Equality> {
+    inner: C,
+}
+
+impl<T, TE, C> Comparator<Vec<T>, Vec<TE>> for ElementWise<C>
+where
+    C: Comparator<T, TE>,
+{
+    fn compare(&self, control: &Vec<T>, experiment: &Vec<TE>) -> bool {
+        self.mismatch(control, experiment).is_none()
+    }
+
+    fn name(&self) -> &'static str {
+        "elements"
+    }
+
+    fn describe(&self, control: &Vec<T>, experiment: &Vec<TE>) -> Comparison {
+        let mismatch = self.mismatch(control, experiment);
+
+        Comparison {
+            matching: mismatch.is_none(),
+            detail: mismatch.map(|mismatch| match mismatch {
+                MismatchDetail::LengthDiffers {
+                    control_len,
+                    experiment_len,
+                } => format!("length: {} != {}", control_len, experiment_len),
+                MismatchDetail::ElementDiffers { index } => format!("index {}", index),
+                MismatchDetail::Other => String::from("other"),
+            }),
+        }
+    }
+
+    fn mismatch(&self, control: &Vec<T>, experiment: &Vec<TE>) -> Option<MismatchDetail> {
+        mismatch_of(control, experiment, |c, e| self.inner.compare(c, e))
+    }
+}
+
+/// Compare `Vec`s element by element using the inner comparator, see `ElementWise`.
+pub fn elements<C>(inner: C) -> ElementWise<C> {
+    ElementWise { inner }
+}
+
+/// Comparator treating two numeric slices as matching when all of the elements are matching
+/// within the absolute epsilon, see `Approx`.
+///
+/// Slices of different lengths never match. Two `NaN`s at the same index are matching unless
+/// `nans_equal(false)` is set, a `NaN` never matches a number. The index and the difference of the
+/// first element out of the tolerance are recorded as the detail of the comparison. Implemented
+/// for the `Vec`s and arrays of `f32` and `f64`.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::compare::{self, Comparator};
+///
+/// let comparison = compare::approx_slice(0.1).describe(&vec![1.0, 2.0, 3.0], &vec![1.0, 2.5, 3.0]);
+/// assert!(!comparison.matching);
+/// assert_eq!(comparison.detail.as_deref(), Some("index 1: difference 0.5"));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ApproxSlice {
+    abs_eps: f64,
+    nans_equal: bool,
+}
+
+impl ApproxSlice {
+    /// Whether two `NaN`s at the same index are matching, `true` by default.
+    pub fn nans_equal(mut self, nans_equal: bool) -> Self {
+        self.nans_equal = nans_equal;
+        self
+    }
+
+    fn describe_slices<F: Copy + Into<f64>>(&self, control: &[F], experiment: &[F]) -> Comparison {
+        if control.len() != experiment.len() {
+            return Comparison {
+                matching: false,
+                detail: Some(format!("length: {} != {}", control.len(), experiment.len())),
+            };
+        }
+
+        let outlier = control
+            .iter()
+            .zip(experiment)
+            .map(|(&c, &e)| (c.into(), e.into()))
+            .enumerate()
+            .find(|(_, (c, e))| !self.is_within(*c, *e));
+
+        match outlier {
+            Some((index, (c, e))) => Comparison {
+                matching: false,
+                detail: Some(format!("index {}: difference {}", index, (e - c).abs())),
+            },
+            None => true.into(),
+        }
+    }
+
+    fn mismatch_slices<F: Copy + Into<f64>>(
+        &self,
+        control: &[F],
+        experiment: &[F],
+    ) -> Option<MismatchDetail> {
+        mismatch_of(control, experiment, |&c, &e| {
+            self.is_within(c.into(), e.into())
+        })
+    }
+
+    fn is_within(&self, control: f64, experiment: f64) -> bool {
+        if control.is_nan() && experiment.is_nan() {
+            self.nans_equal
+        } else {
+            control == experiment || (control - experiment).abs() <= self.abs_eps
+        }
+    }
+}
+
+macro_rules! approx_slice_impl {
+    ($($t:ty),*) => {$(
+        impl<const N: usize> Comparator<[$t; N], [$t; N]> for ApproxSlice {
+            fn compare(&self, control: &[$t; N], experiment: &[$t; N]) -> bool {
+                self.describe_slices(control, experiment).matching
+            }
+
+            fn name(&self) -> &'static str {
+                "approx_slice"
+            }
+
+            fn describe(&self, control: &[$t; N], experiment: &[$t; N]) -> Comparison {
+                self.describe_slices(control, experiment)
+            }
+
+            fn mismatch(&self, control: &[$t; N], experiment: &[$t; N]) -> Option<MismatchDetail> {
+                self.mismatch_slices(control, experiment)
+            }
+        }
+
+        impl Comparator<Vec<$t>, Vec<$t>> for ApproxSlice {
+            fn compare(&self, control: &Vec<$t>, experiment: &Vec<$t>) -> bool {
+                self.describe_slices(control, experiment).matching
+            }
+
+            fn name(&self) -> &'static str {
+                "approx_slice"
+            }
+
+            fn describe(&self, control: &Vec<$t>, experiment: &Vec<$t>) -> Comparison {
+                self.describe_slices(control, experiment)
+            }
+
+            fn mismatch(&self, control: &Vec<$t>, experiment: &Vec<$t>) -> Option<MismatchDetail> {
+                self.mismatch_slices(control, experiment)
+            }
+        }
+    )*};
+}
+
+approx_slice_impl!(f32, f64);
+
+/// Compare numeric slices element-wise within the absolute tolerance, see `ApproxSlice`.
+pub fn approx_slice(abs_eps: f64) -> ApproxSlice {
+    ApproxSlice {
+        abs_eps,
+        nans_equal: true,
+    }
+}
+
+/// Comparator treating two timestamps as matching when they differ by at most the maximum skew.
+///
+/// Useful for timestamps produced by separate `now()` calls in the branches. The actual skew is
+/// recorded as the detail of the comparison, to help tuning the maximum. Implemented for the
+/// `SystemTime`, and for the `chrono` and `time` timestamps with the features of the same name.
+///
+/// # Examples
+/// ```rust
+/// use std::time::{Duration, SystemTime};
+/// use scientisto::compare::{self, Comparator};
+///
+/// let control = SystemTime::UNIX_EPOCH;
+/// let experiment = control + Duration::from_millis(3);
+///
+/// let comparison = compare::within_skew(Duration::from_millis(5)).describe(&control, &experiment);
+/// assert!(comparison.matching);
+/// assert_eq!(comparison.detail.as_deref(), Some("skew: 3ms"));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct WithinSkew {
+    max: Duration,
+}
+
+impl WithinSkew {
+    fn comparison(&self, skew: Option<Duration>) -> Comparison {
+        match skew {
+            Some(skew) => Comparison {
+                matching: skew <= self.max,
+                detail: Some(format!("skew: {:?}", skew)),
+            },
+            None => Comparison {
+                matching: false,
+                detail: Some(String::from("skew: out of range")),
+            },
+        }
+    }
+}
+
+impl Comparator<SystemTime, SystemTime> for WithinSkew {
+    fn compare(&self, control: &SystemTime, experiment: &SystemTime) -> bool {
+        self.describe(control, experiment).matching
+    }
+
+    fn name(&self) -> &'static str {
+        "within_skew"
+    }
+
+    fn describe(&self, control: &SystemTime, experiment: &SystemTime) -> Comparison {
+        let skew = experiment
+            .duration_since(*control)
+            .or_else(|_| control.duration_since(*experiment));
+
+        self.comparison(skew.ok())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> Comparator<chrono::DateTime<Tz>, chrono::DateTime<Tz>> for WithinSkew {
+    fn compare(&self, control: &chrono::DateTime<Tz>, experiment: &chrono::DateTime<Tz>) -> bool {
+        self.describe(control, experiment).matching
+    }
+
+    fn name(&self) -> &'static str {
+        "within_skew"
+    }
+
+    fn describe(
+        &self,
+        control: &chrono::DateTime<Tz>,
+        experiment: &chrono::DateTime<Tz>,
+    ) -> Comparison {
+        let skew = experiment.clone().signed_duration_since(control.clone());
+
+        self.comparison(skew.abs().to_std().ok())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Comparator<chrono::NaiveDateTime, chrono::NaiveDateTime> for WithinSkew {
+    fn compare(&self, control: &chrono::NaiveDateTime, experiment: &chrono::NaiveDateTime) -> bool {
+        self.describe(control, experiment).matching
+    }
+
+    fn name(&self) -> &'static str {
+        "within_skew"
+    }
+
+    fn describe(
+        &self,
+        control: &chrono::NaiveDateTime,
+        experiment: &chrono::NaiveDateTime,
+    ) -> Comparison {
+        self.comparison((*experiment - *control).abs().to_std().ok())
+    }
+}
+
+#[cfg(feature = "time")]
+impl Comparator<::time::OffsetDateTime, ::time::OffsetDateTime> for WithinSkew {
+    fn compare(
+        &self,
+        control: &::time::OffsetDateTime,
+        experiment: &::time::OffsetDateTime,
+    ) -> bool {
+        self.describe(control, experiment).matching
+    }
+
+    fn name(&self) -> &'static str {
+        "within_skew"
+    }
+
+    fn describe(
+        &self,
+        control: &::time::OffsetDateTime,
+        experiment: &::time::OffsetDateTime,
+    ) -> Comparison {
+        self.comparison(Some((*experiment - *control).unsigned_abs()))
+    }
+}
+
+#[cfg(feature = "time")]
+impl Comparator<::time::PrimitiveDateTime, ::time::PrimitiveDateTime> for WithinSkew {
+    fn compare(
+        &self,
+        control: &::time::PrimitiveDateTime,
+        experiment: &::time::PrimitiveDateTime,
+    ) -> bool {
+        self.describe(control, experiment).matching
+    }
+
+    fn name(&self) -> &'static str {
+        "within_skew"
+    }
+
+    fn describe(
+        &self,
+        control: &::time::PrimitiveDateTime,
+        experiment: &::time::PrimitiveDateTime,
+    ) -> Comparison {
+        self.comparison(Some((*experiment - *control).unsigned_abs()))
+    }
+}
+
+/// Compare timestamps within the maximum skew, see `WithinSkew`.
+pub fn within_skew(max: Duration) -> WithinSkew {
+    WithinSkew { max }
+}
+
+/// Shared pointer whose identity can short-circuit the comparison, see `PtrEqFastpath`.
+pub trait SharedPtr {
+    /// Address of the pointed value.
+    fn addr(&self) -> *const ();
+}
+
+impl<T: ?Sized> SharedPtr for std::sync::Arc<T> {
+    fn addr(&self) -> *const () {
+        std::sync::Arc::as_ptr(self) as *const ()
+    }
+}
+
+impl<T: ?Sized> SharedPtr for std::rc::Rc<T> {
+    fn addr(&self) -> *const () {
+        std::rc::Rc::as_ptr(self) as *const ()
+    }
+}
+
+impl<T: ?Sized> SharedPtr for &T {
+    fn addr(&self) -> *const () {
+        *self as *const T as *const ()
+    }
+}
+
+/// Comparator matching two pointers to the identical value without comparing the values.
+///
+/// Falls back to the inner comparator for distinct pointers. Only valid if identical pointers
+/// imply matching values, which does not hold e.g. for a `NaN` compared using `PartialEq`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PtrEqFastpath<C = Equality> {
+    inner: C,
+}
+
+impl<T, C> Comparator<T, T> for PtrEqFastpath<C>
+where
+    T: SharedPtr,
+    C: Comparator<T, T>,
+{
+    fn compare(&self, control: &T, experiment: &T) -> bool {
+        std::ptr::eq(control.addr(), experiment.addr()) || self.inner.compare(control, experiment)
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn describe(&self, control: &T, experiment: &T) -> Comparison {
+        if std::ptr::eq(control.addr(), experiment.addr()) {
+            true.into()
+        } else {
+            self.inner.describe(control, experiment)
+        }
+    }
+}
+
+/// Short-circuit the comparator for identical pointers, see `PtrEqFastpath`.
+pub fn ptr_eq_fastpath<C>(inner: C) -> PtrEqFastpath<C> {
+    PtrEqFastpath { inner }
+}
+
+/// Build a comparator matching two values of a struct type field by field.
+///
+/// Each listed field is compared using the comparator following the `=>`, or `PartialEq` if no
+/// comparator is given. Fields which are not listed are ignored.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::compare::{self, Comparator};
+///
+/// struct Measurement {
+///     id: u32,
+///     mean: f64,
+///     deviation: f64,
+/// }
+///
+/// let comparator = compare::struct_fields!(Measurement {
+///     id,
+///     mean => compare::nan_eq(),
+///     deviation => compare::approx(1e-9),
+/// });
+///
+/// let control = Measurement { id: 1, mean: f64::NAN, deviation: 0.1 };
+/// let experiment = Measurement { id: 1, mean: f64::NAN, deviation: 0.1 + 1e-12 };
+///
+/// assert!(comparator.compare(&control, &experiment));
+/// ```
+#[macro_export]
+macro_rules! struct_fields {
+    (@field $control:ident, $experiment:ident, $field:ident) => {
+        $crate::compare::Comparator::compare(
+            &$crate::compare::Equality,
+            &$control.$field,
+            &$experiment.$field,
+        )
+    };
+    (@field $control:ident, $experiment:ident, $field:ident => $comparator:expr) => {
+        $crate::compare::Comparator::compare(&$comparator, &$control.$field, &$experiment.$field)
+    };
+    ($type:ty { $($field:ident $(=> $comparator:expr)?),* $(,)? }) => {
+        |control: &$type, experiment: &$type| -> bool {
+            true $(&& $crate::struct_fields!(@field control, experiment, $field $(=> $comparator)?))*
+        }
+    };
+}
+
+pub use crate::struct_fields;
+
+mod config;
+pub use config::{CompareConfig, ConfigEq};
+
+#[cfg(feature = "serde_json")]
+pub mod json;
+
+#[cfg(feature = "similar")]
+pub mod diff;
+#[cfg(feature = "semver")]
+pub mod semver;
+pub mod stream;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closure_should_be_usable_as_a_comparator() {
+        let comparator = |c: &i32, e: &i64| *c as i64 == *e;
+
+        assert!(comparator.compare(&1, &1));
+        assert!(!comparator.compare(&1, &2));
+    }
+
+    #[test]
+    fn equality_should_compare_using_partial_eq() {
+        assert!(Equality.compare(&1, &1));
+        assert!(!Equality.compare(&1, &2));
+    }
+
+    #[test]
+    fn by_variant_should_match_options_of_the_same_variant() {
+        assert!(by_variant().compare(&Some(1), &Some("other")));
+        assert!(by_variant().compare(&None::<i32>, &None::<i32>));
+        assert!(!by_variant().compare(&Some(1), &None::<i32>));
+        assert!(!by_variant().compare(&None::<i32>, &Some(1)));
+    }
+
+    #[test]
+    fn by_variant_should_match_results_of_the_same_variant() {
+        let ok: Result<i32, &str> = Ok(1);
+        let other_ok: Result<u8, ()> = Ok(2);
+        let err: Result<i32, &str> = Err("error");
+        let other_err: Result<u8, ()> = Err(());
+
+        assert!(by_variant().compare(&ok, &other_ok));
+        assert!(by_variant().compare(&err, &other_err));
+        assert!(!by_variant().compare(&ok, &other_err));
+        assert!(!by_variant().compare(&err, &other_ok));
+    }
+
+    #[test]
+    fn nan_eq_should_match_equal_floats_and_nans() {
+        assert!(nan_eq().compare(&1.0_f64, &1.0_f64));
+        assert!(nan_eq().compare(&f64::NAN, &f64::NAN));
+        assert!(nan_eq().compare(&f32::NAN, &f32::NAN));
+        assert!(!nan_eq().compare(&1.0_f64, &f64::NAN));
+    }
+
+    #[test]
+    fn approx_should_match_floats_within_the_tolerance() {
+        assert!(approx(0.1).compare(&1.0_f64, &1.05_f64));
+        assert!(approx(0.1).compare(&1.0_f32, &1.05_f32));
+        assert!(approx(0.1).compare(&f64::NAN, &f64::NAN));
+        assert!(!approx(0.1).compare(&1.0_f64, &1.2_f64));
+        assert!(!approx(0.1).compare(&1.0_f64, &f64::NAN));
+    }
+
+    #[test]
+    fn any_of_should_record_the_first_matching_golden() {
+        assert_eq!(
+            any_of(Equality)
+                .describe(&1, &vec![2, 1, 1])
+                .detail
+                .as_deref(),
+            Some("golden: 1")
+        );
+        assert_eq!(
+            any_of(Equality).describe(&1, &vec![2, 3]),
+            Comparison {
+                matching: false,
+                detail: Some(String::from("golden: none")),
+            }
+        );
+        assert!(!any_of(Equality).compare(&1, &Vec::<i32>::new()));
+    }
+
+    #[test]
+    fn elements_should_detail_the_shape_of_the_mismatch() {
+        assert_eq!(elements(Equality).mismatch(&vec![1, 2], &vec![1, 2]), None);
+        assert_eq!(
+            elements(Equality).mismatch(&vec![1, 2], &vec![1]),
+            Some(MismatchDetail::LengthDiffers {
+                control_len: 2,
+                experiment_len: 1
+            })
+        );
+        assert_eq!(
+            elements(Equality).describe(&vec![1, 2, 3], &vec![1, 3, 3]),
+            Comparison {
+                matching: false,
+                detail: Some(String::from("index 1")),
+            }
+        );
+    }
+
+    #[test]
+    fn approx_slice_should_match_slices_within_the_tolerance() {
+        assert!(approx_slice(0.1).compare(&vec![1.0, f64::NAN], &vec![1.05, f64::NAN]));
+        assert!(approx_slice(0.1).compare(&[1.0_f32], &[1.05_f32]));
+        assert!(!approx_slice(0.1)
+            .nans_equal(false)
+            .compare(&vec![f64::NAN], &vec![f64::NAN]));
+        assert!(!approx_slice(0.1).compare(&vec![1.0], &vec![f64::NAN]));
+    }
+
+    #[test]
+    fn approx_slice_should_detail_the_first_element_out_of_the_tolerance() {
+        assert_eq!(
+            approx_slice(0.1).describe(&vec![1.0, 2.0, 3.0], &vec![1.0, 1.75, 4.0]),
+            Comparison {
+                matching: false,
+                detail: Some(String::from("index 1: difference 0.25")),
+            }
+        );
+        assert_eq!(
+            approx_slice(0.1)
+                .describe(&vec![1.0, 2.0], &vec![1.0])
+                .detail
+                .as_deref(),
+            Some("length: 2 != 1")
+        );
+        assert_eq!(
+            approx_slice(0.1).mismatch(&[1.0, 2.0], &[1.0, 3.0]),
+            Some(MismatchDetail::ElementDiffers { index: 1 })
+        );
+    }
+
+    struct Sample {
+        id: u32,
+        value: f64,
+        ratio: f32,
+        generated: u64,
+    }
+
+    #[test]
+    fn struct_fields_should_compare_only_the_listed_fields_using_their_comparators() {
+        let comparator = struct_fields!(Sample {
+            id,
+            value => nan_eq(),
+            ratio => approx(0.01),
+        });
+        let control = Sample {
+            id: 1,
+            value: f64::NAN,
+            ratio: 0.5,
+            generated: 1,
+        };
+        let experiment = Sample {
+            id: 1,
+            value: f64::NAN,
+            ratio: 0.501,
+            generated: 2,
+        };
+
+        assert!(comparator.compare(&control, &experiment));
+        assert!(!comparator.compare(
+            &control,
+            &Sample {
+                id: 2,
+                ..experiment
+            }
+        ));
+        assert_eq!(control.generated, 1);
+    }
+
+    #[test]
+    fn ok_only_should_compare_the_ok_values_of_results_with_different_error_types() {
+        let ok: Result<i32, String> = Ok(1);
+        let other_ok: Result<i32, std::fmt::Error> = Ok(1);
+        let different_ok: Result<i32, std::fmt::Error> = Ok(2);
+
+        assert_eq!(ok_only().describe(&ok, &other_ok), Comparison::from(true));
+        assert!(!ok_only().compare(&ok, &different_ok));
+    }
+
+    #[test]
+    fn ok_only_should_record_the_error_messages() {
+        let ok: Result<i32, String> = Ok(1);
+        let err: Result<i32, String> = Err(String::from("timeout"));
+        let other_err: Result<i32, std::fmt::Error> = Err(std::fmt::Error);
+
+        assert_eq!(
+            ok_only().describe(&err, &other_err),
+            Comparison {
+                matching: true,
+                detail: Some(String::from(
+                    "control error: timeout, experiment error: an error occurred when formatting \
+                     an argument"
+                )),
+            }
+        );
+        assert_eq!(
+            ok_only().describe(&ok, &other_err).detail.as_deref(),
+            Some("experiment error: an error occurred when formatting an argument")
+        );
+    }
+
+    #[test]
+    fn by_hash_should_match_values_with_equal_digests_and_record_them() {
+        let control = vec![1u8; 1024];
+        let comparison = by_default_hash().describe(&control, &vec![1u8; 1024]);
+
+        assert!(comparison.matching);
+        assert_eq!(
+            comparison.detail.as_deref().map(|detail| detail.len()),
+            Some("control digest: , experiment digest: ".len() + 32)
+        );
+        assert!(!by_default_hash().compare(&control, &vec![2u8; 1024]));
+    }
+
+    #[test]
+    fn by_hash_should_use_the_supplied_hasher() {
+        let comparator = by_hash(BuildHasherDefault::<DefaultHasher>::default());
+
+        assert!(comparator.compare(&"large", &"large"));
+        assert!(!comparator.compare(&"large", &"other"));
+    }
+
+    #[test]
+    fn projected_should_compare_only_the_projections() {
+        let comparator = projected(|pair: &(u32, &str)| pair.0);
+
+        assert!(comparator.compare(&(1, "control"), &(1, "experiment")));
+        assert!(!comparator.compare(&(1, "control"), &(2, "control")));
+    }
+
+    #[test]
+    fn projected_each_should_compare_the_projections_of_different_types() {
+        let comparator = projected_each(|c: &(u32, &str)| c.0, |e: &String| e.len() as u32);
+
+        assert!(comparator.compare(&(3, "control"), &String::from("abc")));
+        assert!(!comparator.compare(&(1, "control"), &String::from("abc")));
+    }
+
+    #[test]
+    fn ignoring_should_compare_the_values_without_the_excluded_parts() {
+        let comparator = ignoring("id", |pair: &mut (u32, &str)| pair.0 = 0);
+
+        assert_eq!(
+            comparator.describe(&(1, "value"), &(2, "value")),
+            Comparison {
+                matching: true,
+                detail: Some(String::from("ignored: id")),
+            }
+        );
+        assert!(!comparator.compare(&(1, "value"), &(1, "other")));
+    }
+
+    #[test]
+    fn ignoring_should_compose_with_the_inner_comparator_detail() {
+        let comparator =
+            ignoring("name", |pair: &mut (f64, &str)| pair.1 = "").comparing_with(Described);
+
+        assert_eq!(
+            comparator
+                .describe(&(1.0, "a"), &(2.0, "b"))
+                .detail
+                .as_deref(),
+            Some("ignored: name; differs")
+        );
+    }
+
+    struct Described;
+
+    impl Comparator<(f64, &str), (f64, &str)> for Described {
+        fn compare(&self, control: &(f64, &str), experiment: &(f64, &str)) -> bool {
+            approx(0.1).compare(&control.0, &experiment.0) && control.1 == experiment.1
+        }
+
+        fn describe(&self, control: &(f64, &str), experiment: &(f64, &str)) -> Comparison {
+            Comparison {
+                matching: self.compare(control, experiment),
+                detail: Some(String::from("differs")),
+            }
+        }
+    }
+
+    #[test]
+    fn within_skew_should_match_system_times_within_the_skew_in_either_order() {
+        let control = SystemTime::UNIX_EPOCH + Duration::from_secs(1);
+        let comparator = within_skew(Duration::from_millis(5));
+
+        assert!(comparator.compare(&control, &(control + Duration::from_millis(5))));
+        assert!(comparator.compare(&control, &(control - Duration::from_millis(5))));
+        assert_eq!(
+            comparator.describe(&control, &(control + Duration::from_millis(6))),
+            Comparison {
+                matching: false,
+                detail: Some(String::from("skew: 6ms")),
+            }
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn within_skew_should_match_chrono_timestamps_within_the_skew() {
+        let control = chrono::DateTime::from_timestamp(1_000, 0).unwrap();
+        let comparator = within_skew(Duration::from_millis(5));
+
+        assert!(comparator.compare(&control, &(control - chrono::Duration::milliseconds(4))));
+        assert!(!comparator.compare(
+            &control.naive_utc(),
+            &(control + chrono::Duration::milliseconds(6)).naive_utc()
+        ));
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn within_skew_should_match_time_timestamps_within_the_skew() {
+        let control = ::time::OffsetDateTime::UNIX_EPOCH;
+        let comparator = within_skew(Duration::from_millis(5));
+
+        assert!(comparator.compare(&control, &(control + ::time::Duration::milliseconds(4))));
+        assert_eq!(
+            comparator
+                .describe(&control, &(control - ::time::Duration::milliseconds(6)))
+                .detail
+                .as_deref(),
+            Some("skew: 6ms")
+        );
+    }
+
+    #[test]
+    fn ptr_eq_fastpath_should_match_identical_pointers_without_comparing_the_values() {
+        let compared = std::cell::Cell::new(0);
+        let comparator = ptr_eq_fastpath(|c: &std::rc::Rc<f64>, e: &std::rc::Rc<f64>| {
+            compared.set(compared.get() + 1);
+            c == e
+        });
+        let shared = std::rc::Rc::new(f64::NAN);
+
+        assert!(comparator.compare(&shared, &shared.clone()));
+        assert_eq!(compared.get(), 0);
+        assert!(!comparator.compare(&shared, &std::rc::Rc::new(f64::NAN)));
+        assert_eq!(compared.get(), 1);
+    }
+
+    #[test]
+    fn canonicalized_should_compare_the_canonical_forms_by_the_inner_comparator() {
+        let sorted = |v: &Vec<i32>| {
+            let mut v = v.clone();
+            v.sort();
+            v
+        };
+
+        assert!(canonicalized(sorted, Equality).compare(&vec![2, 1], &vec![1, 2]));
+        assert!(!canonicalized(sorted, Equality).compare(&vec![2, 1], &vec![1, 3]));
+
+        let rounded = |x: &f64| x.round();
+        assert!(canonicalized(rounded, approx(1.0)).compare(&1.4, &1.6));
+        assert!(!canonicalized(rounded, Equality).compare(&1.4, &1.6));
+    }
+
+    #[test]
+    fn sorted_by_should_compare_the_sorted_collections() {
+        let comparator = sorted_by(|(id, _): &(u32, f64)| *id);
+
+        assert!(comparator.compare(&vec![(2, 0.5), (1, 1.0)], &vec![(1, 1.0), (2, 0.5)]));
+        assert!(!comparator.compare(&vec![(2, 0.5), (1, 1.0)], &vec![(1, 1.0), (2, 0.7)]));
+    }
+
+    #[test]
+    fn sorted_by_should_fail_the_comparison_of_unordered_keys() {
+        let comparator = sorted_by(|(_, score): &(u32, f64)| *score);
+        let control = vec![(1, 0.5), (2, f64::NAN)];
+
+        assert_eq!(
+            comparator.try_describe(&control, &control).err().as_deref(),
+            Some("sort key of index 1 is not ordered")
+        );
+        assert!(!comparator.compare(&control, &control));
+    }
+
+    #[test]
+    fn experiment_ok_should_never_match_an_experiment_error() {
+        let comparator = experiment_ok(Equality);
+
+        assert!(comparator.compare(&1, &Ok::<i32, String>(1)));
+        assert_eq!(
+            comparator.describe(&1, &Err::<i32, _>("timeout")),
+            Comparison {
+                matching: false,
+                detail: Some(String::from("experiment error: timeout")),
+            }
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Comparison configured by a single set of tolerances, see `CompareConfig`.
+
+use super::Comparator;
+
+/// Compare config
+///
+/// Set of tolerances applied to the values recursively, e.g. to the floats inside of a `Vec` of
+/// `Option`s, used as the comparator by `with_compare_config`. Implemented for the types
+/// implementing `ConfigEq`. The default config compares the values exactly, like `PartialEq`.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::compare::{CompareConfig, Comparator};
+///
+/// let config = CompareConfig {
+///     float_abs_eps: 0.01,
+///     ignore_collection_order: true,
+///     ..CompareConfig::default()
+/// };
+///
+/// assert!(config.compare(&vec![1.0, 2.0], &vec![2.001, 1.0]));
+/// assert!(!config.compare(&vec![f64::NAN], &vec![f64::NAN]));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CompareConfig {
+    /// Floats differing by at most the absolute epsilon are matching.
+    pub float_abs_eps: f64,
+    /// Floats differing by at most the relative epsilon of the larger magnitude are matching.
+    pub float_rel_eps: f64,
+    /// The collections are matching if they hold the matching elements in any order.
+    pub ignore_collection_order: bool,
+    /// Two `NaN`s are matching.
+    pub nan_equal: bool,
+}
+
+impl CompareConfig {
+    fn floats_match(&self, control: f64, experiment: f64) -> bool {
+        if control.is_nan() || experiment.is_nan() {
+            return self.nan_equal && control.is_nan() && experiment.is_nan();
+        }
+
+        let difference = (control - experiment).abs();
+        control == experiment
+            || difference <= self.float_abs_eps
+            || difference <= self.float_rel_eps * control.abs().max(experiment.abs())
+    }
+
+    fn slices_match<T: ConfigEq>(&self, control: &[T], experiment: &[T]) -> bool {
+        if control.len() != experiment.len() {
+            return false;
+        }
+        if !self.ignore_collection_order {
+            return control
+                .iter()
+                .zip(experiment)
+                .all(|(c, e)| c.config_eq(e, self));
+        }
+
+        // the tolerances rule out sorting, each control element claims a matching experiment one
+        let mut claimed = vec![false; experiment.len()];
+        control.iter().all(|c| {
+            let matched =
+                (0..experiment.len()).find(|&i| !claimed[i] && c.config_eq(&experiment[i], self));
+            matched.map(|i| claimed[i] = true).is_some()
+        })
+    }
+}
+
+/// Equality of the values under the tolerances of the `CompareConfig`.
+pub trait ConfigEq {
+    fn config_eq(&self, other: &Self, config: &CompareConfig) -> bool;
+}
+
+impl<T: ConfigEq> Comparator<T, T> for CompareConfig {
+    fn compare(&self, control: &T, experiment: &T) -> bool {
+        control.config_eq(experiment, self)
+    }
+
+    fn name(&self) -> &'static str {
+        "compare_config"
+    }
+}
+
+macro_rules! exact_config_eq {
+    ($($t:ty),*) => {$(
+        impl ConfigEq for $t {
+            fn config_eq(&self, other: &Self, _config: &CompareConfig) -> bool {
+                self == other
+            }
+        }
+    )*};
+}
+
+exact_config_eq!(
+    bool, char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, str, String
+);
+
+impl ConfigEq for f32 {
+    fn config_eq(&self, other: &Self, config: &CompareConfig) -> bool {
+        config.floats_match(f64::from(*self), f64::from(*other))
+    }
+}
+
+impl ConfigEq for f64 {
+    fn config_eq(&self, other: &Self, config: &CompareConfig) -> bool {
+        config.floats_match(*self, *other)
+    }
+}
+
+impl<T: ConfigEq + ?Sized> ConfigEq for &T {
+    fn config_eq(&self, other: &Self, config: &CompareConfig) -> bool {
+        (**self).config_eq(other, config)
+    }
+}
+
+impl<T: ConfigEq + ?Sized> ConfigEq for Box<T> {
+    fn config_eq(&self, other: &Self, config: &CompareConfig) -> bool {
+        (**self).config_eq(other, config)
+    }
+}
+
+impl<T: ConfigEq> ConfigEq for Option<T> {
+    fn config_eq(&self, other: &Self, config: &CompareConfig) -> bool {
+        match (self, other) {
+            (Some(c), Some(e)) => c.config_eq(e, config),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: ConfigEq> ConfigEq for [T] {
+    fn config_eq(&self, other: &Self, config: &CompareConfig) -> bool {
+        config.slices_match(self, other)
+    }
+}
+
+impl<T: ConfigEq, const N: usize> ConfigEq for [T; N] {
+    fn config_eq(&self, other: &Self, config: &CompareConfig) -> bool {
+        config.slices_match(self, other)
+    }
+}
+
+impl<T: ConfigEq> ConfigEq for Vec<T> {
+    fn config_eq(&self, other: &Self, config: &CompareConfig) -> bool {
+        config.slices_match(self, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_config_should_compare_exactly_by_default() {
+        let config = CompareConfig::default();
+
+        assert!(config.compare(&vec![Some(1.0)], &vec![Some(1.0)]));
+        assert!(!config.compare(&1.0, &1.000_001));
+        assert!(!config.compare(&vec![1, 2], &vec![2, 1]));
+        assert!(!config.compare(&f64::NAN, &f64::NAN));
+    }
+
+    #[test]
+    fn compare_config_should_apply_the_float_tolerances() {
+        let absolute = CompareConfig {
+            float_abs_eps: 0.1,
+            ..CompareConfig::default()
+        };
+        let relative = CompareConfig {
+            float_rel_eps: 0.01,
+            ..CompareConfig::default()
+        };
+
+        assert!(absolute.compare(&1.0_f32, &1.05_f32));
+        assert!(!absolute.compare(&1000.0, &1005.0));
+        assert!(relative.compare(&1000.0, &1005.0));
+        assert!(!relative.compare(&0.001, &0.002));
+    }
+
+    #[test]
+    fn compare_config_should_match_nans_and_reordered_collections_if_configured() {
+        let config = CompareConfig {
+            ignore_collection_order: true,
+            nan_equal: true,
+            ..CompareConfig::default()
+        };
+
+        assert!(config.compare(&vec![f64::NAN, 1.0], &vec![1.0, f64::NAN]));
+        assert!(config.compare(&[1, 1, 2], &[1, 2, 1]));
+        assert!(!config.compare(&[1, 1, 2], &[1, 2, 2]));
+    }
+}
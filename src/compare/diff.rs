@@ -0,0 +1,74 @@
+//! Textual diffs of the mismatching values, available with the `similar` feature.
+
+use std::fmt::Debug;
+
+use similar::TextDiff;
+
+use super::{Comparator, Comparison, Equality, MismatchDetail};
+
+/// Comparator recording the line diff of the `Debug` representations of the mismatching values.
+///
+/// The values are compared by the inner comparator, `PartialEq` by default, the unified diff of
+/// their pretty-printed `Debug` representations is only computed for the mismatching values and
+/// recorded as the `diff` of the observation.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::compare::{diff, Comparator, Equality};
+///
+/// let diff = diff::diffed(Equality).diff(&vec![1, 2], &vec![1, 3]).unwrap();
+///
+/// assert!(diff.contains("-    2,"));
+/// assert!(diff.contains("+    3,"));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Diffed<C = Equality> {
+    inner: C,
+}
+
+/// Record the diff of the values mismatching by the inner comparator, see `Diffed`.
+pub fn diffed<C>(inner: C) -> Diffed<C> {
+    Diffed { inner }
+}
+
+impl<T, TE, C> Comparator<T, TE> for Diffed<C>
+where
+    T: Debug,
+    TE: Debug,
+    C: Comparator<T, TE>,
+{
+    fn compare(&self, control: &T, experiment: &TE) -> bool {
+        self.inner.compare(control, experiment)
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn describe(&self, control: &T, experiment: &TE) -> Comparison {
+        self.inner.describe(control, experiment)
+    }
+
+    fn try_describe(&self, control: &T, experiment: &TE) -> Result<Comparison, String> {
+        self.inner.try_describe(control, experiment)
+    }
+
+    fn ignores(&self, control: &T, experiment: &TE) -> bool {
+        self.inner.ignores(control, experiment)
+    }
+
+    fn mismatch(&self, control: &T, experiment: &TE) -> Option<MismatchDetail> {
+        self.inner.mismatch(control, experiment)
+    }
+
+    fn diff(&self, control: &T, experiment: &TE) -> Option<String> {
+        let (control, experiment) = (format!("{:#?}\n", control), format!("{:#?}\n", experiment));
+
+        Some(
+            TextDiff::from_lines(&control, &experiment)
+                .unified_diff()
+                .header("control", "experiment")
+                .to_string(),
+        )
+    }
+}
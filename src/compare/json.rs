@@ -0,0 +1,169 @@
+//! Semantic comparison of `serde_json::Value`s, available with the `serde_json` feature.
+
+use serde_json::{Number, Value};
+
+use super::{Comparator, Comparison};
+
+/// Comparator matching two JSON values semantically.
+///
+/// The order of the object keys is ignored, numbers are compared by their value (`1` matches
+/// `1.0`) within an optional absolute tolerance and the values at the ignored paths are skipped.
+/// Paths use the `$.key[index]` notation, e.g. `$.items[0].timestamp`.
+///
+/// On mismatch, the path of the first difference is recorded as the detail of the observation.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::compare::{json, Comparator};
+/// use serde_json::json;
+///
+/// let comparator = json::json_eq().tolerance(0.01).ignore("$.timestamp");
+/// let control = json!({ "id": 1, "price": 9.99, "timestamp": 1 });
+/// let experiment = json!({ "timestamp": 2, "price": 9.991, "id": 1 });
+///
+/// assert!(comparator.compare(&control, &experiment));
+/// assert_eq!(
+///     comparator.describe(&control, &json!({ "id": 2 })).detail.as_deref(),
+///     Some("$.id")
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct JsonEq {
+    abs_eps: f64,
+    ignored: Vec<String>,
+}
+
+impl JsonEq {
+    /// Match numbers differing by at most the absolute epsilon.
+    pub fn tolerance(mut self, abs_eps: f64) -> Self {
+        self.abs_eps = abs_eps;
+        self
+    }
+
+    /// Skip the value at the path, including its absence on either side.
+    pub fn ignore(mut self, path: &str) -> Self {
+        self.ignored.push(path.to_owned());
+        self
+    }
+
+    fn is_ignored(&self, path: &str) -> bool {
+        self.ignored.iter().any(|ignored| ignored == path)
+    }
+
+    fn numbers_match(&self, control: &Number, experiment: &Number) -> bool {
+        match (control.as_f64(), experiment.as_f64()) {
+            (Some(c), Some(e)) if control.is_f64() || experiment.is_f64() || self.abs_eps > 0.0 => {
+                (c - e).abs() <= self.abs_eps
+            }
+            _ => control == experiment,
+        }
+    }
+
+    /// Find the path of the first difference, visiting the object keys in a sorted order.
+    fn difference(
+        &self,
+        path: String,
+        control: Option<&Value>,
+        experiment: Option<&Value>,
+    ) -> Option<String> {
+        if self.is_ignored(&path) {
+            return None;
+        }
+
+        match (control, experiment) {
+            (Some(Value::Object(c)), Some(Value::Object(e))) => {
+                let mut keys = c.keys().chain(e.keys()).collect::<Vec<_>>();
+                keys.sort();
+                keys.dedup();
+
+                keys.into_iter().find_map(|key| {
+                    self.difference(format!("{}.{}", path, key), c.get(key), e.get(key))
+                })
+            }
+            (Some(Value::Array(c)), Some(Value::Array(e))) => (0..c.len().max(e.len()))
+                .find_map(|i| self.difference(format!("{}[{}]", path, i), c.get(i), e.get(i))),
+            (Some(Value::Number(c)), Some(Value::Number(e))) if self.numbers_match(c, e) => None,
+            (Some(c), Some(e)) if c == e => None,
+            _ => Some(path),
+        }
+    }
+}
+
+/// Compare JSON values semantically, see `JsonEq`.
+pub fn json_eq() -> JsonEq {
+    JsonEq::default()
+}
+
+impl Comparator<Value, Value> for JsonEq {
+    fn compare(&self, control: &Value, experiment: &Value) -> bool {
+        self.describe(control, experiment).matching
+    }
+
+    fn name(&self) -> &'static str {
+        "json_eq"
+    }
+
+    fn describe(&self, control: &Value, experiment: &Value) -> Comparison {
+        let difference = self.difference(String::from("$"), Some(control), Some(experiment));
+
+        Comparison {
+            matching: difference.is_none(),
+            detail: difference,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn json_eq_should_ignore_the_order_of_object_keys() {
+        let control: Value = serde_json::from_str(r#"{"a": 1, "b": [1, 2]}"#).unwrap();
+        let experiment: Value = serde_json::from_str(r#"{"b": [1, 2], "a": 1}"#).unwrap();
+
+        assert!(json_eq().compare(&control, &experiment));
+    }
+
+    #[test]
+    fn json_eq_should_compare_numbers_by_value_within_the_tolerance() {
+        assert!(json_eq().compare(&json!(1), &json!(1.0)));
+        assert!(!json_eq().compare(&json!(1.0), &json!(1.001)));
+        assert!(json_eq()
+            .tolerance(0.01)
+            .compare(&json!(1.0), &json!(1.001)));
+        assert!(!json_eq().tolerance(0.01).compare(&json!(1), &json!(2)));
+    }
+
+    #[test]
+    fn json_eq_should_skip_the_ignored_paths() {
+        let comparator = json_eq().ignore("$.meta.timestamp").ignore("$.items[1]");
+        let control = json!({ "meta": { "timestamp": 1 }, "items": [1, 2] });
+        let experiment = json!({ "meta": {}, "items": [1, 3] });
+
+        assert!(comparator.compare(&control, &experiment));
+    }
+
+    #[test]
+    fn json_eq_should_record_the_path_of_the_first_difference() {
+        let control = json!({ "a": 1, "b": { "c": [1, 2, 3] }, "d": 1 });
+        let experiment = json!({ "a": 1, "b": { "c": [1, 2] }, "d": 2 });
+
+        assert_eq!(
+            json_eq().describe(&control, &experiment),
+            Comparison {
+                matching: false,
+                detail: Some(String::from("$.b.c[2]")),
+            }
+        );
+    }
+
+    #[test]
+    fn json_eq_should_record_the_root_path_for_different_types() {
+        assert_eq!(
+            json_eq().describe(&json!("1"), &json!(1)).detail.as_deref(),
+            Some("$")
+        );
+    }
+}
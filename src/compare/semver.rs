@@ -0,0 +1,90 @@
+//! Semantic comparison of version strings, available with the `semver` feature.
+
+use ::semver::Version;
+
+use super::{Comparator, Comparison};
+
+/// Comparator matching two version strings by their semantic version precedence.
+///
+/// The missing minor and patch numbers default to zero, so `1.0` matches `1.0.0`, and the build
+/// metadata is ignored, so `1.0.0+linux` matches `1.0.0` too. A version failing to parse fails the
+/// comparison, its message is recorded as the `compare_error` of the observation.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::compare::{semver, Comparator};
+///
+/// assert!(semver::semver_eq().compare(&"1.0", &"1.0.0"));
+/// assert!(!semver::semver_eq().compare(&"1.0.0-rc.1", &"1.0.0"));
+/// assert!(semver::semver_eq().try_describe(&"1.0", &"one").is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SemverEq;
+
+/// Compare version strings semantically, see `SemverEq`.
+pub fn semver_eq() -> SemverEq {
+    SemverEq
+}
+
+/// Parse the version, padding the missing minor and patch numbers with zeros.
+fn parse(version: &str) -> Result<Version, String> {
+    let version = version.trim();
+    let core_end = version.find(['-', '+']).unwrap_or(version.len());
+    let (core, suffix) = version.split_at(core_end);
+    let padding = 2_usize.saturating_sub(core.matches('.').count());
+
+    Version::parse(&format!("{}{}{}", core, ".0".repeat(padding), suffix))
+        .map_err(|e| format!("invalid version {:?}: {}", version, e))
+}
+
+impl<T, TE> Comparator<T, TE> for SemverEq
+where
+    T: AsRef<str>,
+    TE: AsRef<str>,
+{
+    fn compare(&self, control: &T, experiment: &TE) -> bool {
+        self.try_describe(control, experiment)
+            .is_ok_and(|comparison| comparison.matching)
+    }
+
+    fn name(&self) -> &'static str {
+        "semver_eq"
+    }
+
+    fn try_describe(&self, control: &T, experiment: &TE) -> Result<Comparison, String> {
+        let (control, experiment) = (parse(control.as_ref())?, parse(experiment.as_ref())?);
+
+        Ok(Comparison {
+            matching: control.cmp_precedence(&experiment).is_eq(),
+            detail: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn semver_eq_should_pad_the_missing_version_numbers() {
+        assert!(semver_eq().compare(&"1", &"1.0.0"));
+        assert!(semver_eq().compare(&String::from("1.0-beta"), &"1.0.0-beta"));
+        assert!(!semver_eq().compare(&"1.1", &"1.0.1"));
+    }
+
+    #[test]
+    fn semver_eq_should_ignore_the_build_metadata() {
+        assert!(semver_eq().compare(&"1.2.3+linux", &"1.2.3+macos"));
+    }
+
+    #[test]
+    fn semver_eq_should_fail_the_comparison_of_an_invalid_version() {
+        assert_eq!(
+            semver_eq().try_describe(&"1.0", &"1.x"),
+            Err(String::from(
+                "invalid version \"1.x\": unexpected character 'x' while parsing minor version number"
+            ))
+        );
+        assert!(!semver_eq().compare(&"1.0", &"1.x"));
+    }
+}
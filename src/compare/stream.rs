@@ -0,0 +1,122 @@
+//! Comparison of the items of two `async` streams, e.g. paginated API responses.
+
+use futures::{Stream, StreamExt};
+
+use super::{Comparator, MismatchDetail};
+
+/// Stream comparison
+///
+/// Outcome of `compare_streams`, the number of the matching pairs of items and the shape of the
+/// first divergence of the streams, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamComparison {
+    /// Number of the pairs of items compared as matching.
+    pub compared: usize,
+    /// The first divergence, `ElementDiffers` with the index of the first mismatching pair or
+    /// `LengthDiffers` if one of the streams ended first.
+    pub mismatch: Option<MismatchDetail>,
+}
+
+impl StreamComparison {
+    /// Whether the streams yielded the same number of pairwise matching items.
+    pub fn is_matching(&self) -> bool {
+        self.mismatch.is_none()
+    }
+}
+
+/// Consume both of the streams concurrently, comparing their items pairwise by the comparator.
+///
+/// The next items of both of the streams are awaited together, so at most one item of each of the
+/// streams is held at a time regardless of their lengths. The comparison stops at the first
+/// mismatching pair, whereas the longer of the streams is drained to count its items once the
+/// other one ends.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::compare::stream::compare_streams;
+/// use scientisto::compare::{Equality, MismatchDetail};
+///
+/// async_std::task::block_on(async {
+///     let pages = |n: u32| futures::stream::iter(1..=n);
+///
+///     let comparison = compare_streams(pages(3), pages(4), &Equality).await;
+///
+///     assert_eq!(comparison.compared, 3);
+///     assert_eq!(
+///         comparison.mismatch,
+///         Some(MismatchDetail::LengthDiffers { control_len: 3, experiment_len: 4 })
+///     );
+/// })
+/// ```
+pub async fn compare_streams<SC, SE, C>(
+    control: SC,
+    experiment: SE,
+    comparator: &C,
+) -> StreamComparison
+where
+    SC: Stream,
+    SE: Stream,
+    C: Comparator<SC::Item, SE::Item> + ?Sized,
+{
+    let (mut control, mut experiment) = (std::pin::pin!(control), std::pin::pin!(experiment));
+    let mut compared = 0;
+
+    let mismatch = loop {
+        match futures::future::join(control.next(), experiment.next()).await {
+            (Some(c), Some(e)) if comparator.compare(&c, &e) => compared += 1,
+            (Some(_), Some(_)) => break Some(MismatchDetail::ElementDiffers { index: compared }),
+            (None, None) => break None,
+            (Some(_), None) => {
+                break Some(MismatchDetail::LengthDiffers {
+                    control_len: compared + 1 + control.count().await,
+                    experiment_len: compared,
+                })
+            }
+            (None, Some(_)) => {
+                break Some(MismatchDetail::LengthDiffers {
+                    control_len: compared,
+                    experiment_len: compared + 1 + experiment.count().await,
+                })
+            }
+        }
+    };
+
+    StreamComparison { compared, mismatch }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compare::Equality;
+
+    #[async_std::test]
+    async fn compare_streams_should_match_the_pairwise_equal_streams() {
+        let comparison = compare_streams(
+            futures::stream::iter(vec![1, 2, 3]),
+            futures::stream::iter(vec![1, 2, 3]),
+            &Equality,
+        )
+        .await;
+
+        assert!(comparison.is_matching());
+        assert_eq!(comparison.compared, 3);
+    }
+
+    #[async_std::test]
+    async fn compare_streams_should_record_the_first_divergence() {
+        let comparison = compare_streams(
+            futures::stream::iter(vec![1, 2, 3, 4]),
+            futures::stream::iter(vec![1, 5, 3]),
+            &Equality,
+        )
+        .await;
+
+        assert_eq!(
+            comparison,
+            StreamComparison {
+                compared: 1,
+                mismatch: Some(MismatchDetail::ElementDiffers { index: 1 }),
+            }
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Aggregates of many concurrent runs of an `async` experiment.
+
+use std::time::Duration;
+
+/// Concurrent report
+///
+/// Aggregate of the runs driven by `AsyncCompleteFnExperiment::run_concurrent`, e.g. to benchmark
+/// the experiment or to validate it in bulk against a service. The latencies are of the whole
+/// runs, including the comparison and publishing, by the nearest rank.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConcurrentReport {
+    /// Number of the runs.
+    pub runs: usize,
+    /// Number of the runs with the matching values.
+    pub matching: usize,
+    /// Number of the runs with the mismatching values.
+    pub mismatching: usize,
+    /// Number of the runs without a verdict, e.g. not sampled.
+    pub undecided: usize,
+    /// Median latency of the runs.
+    pub p50: Duration,
+    /// 90th percentile of the latency of the runs.
+    pub p90: Duration,
+    /// 99th percentile of the latency of the runs.
+    pub p99: Duration,
+    /// Maximal latency of the runs.
+    pub max: Duration,
+}
+
+impl ConcurrentReport {
+    /// Aggregate the verdicts and latencies of the runs.
+    pub(crate) fn new(runs: Vec<(Option<bool>, Duration)>) -> Self {
+        let mut latencies = runs.iter().map(|(_, latency)| *latency).collect::<Vec<_>>();
+        latencies.sort_unstable();
+
+        let count = |verdict: Option<bool>| runs.iter().filter(|(v, _)| *v == verdict).count();
+
+        Self {
+            runs: runs.len(),
+            matching: count(Some(true)),
+            mismatching: count(Some(false)),
+            undecided: count(None),
+            p50: percentile(&latencies, 50),
+            p90: percentile(&latencies, 90),
+            p99: percentile(&latencies, 99),
+            max: latencies.last().copied().unwrap_or_default(),
+        }
+    }
+
+    /// Ratio of the matching runs among the decided ones, `NaN` if none was decided.
+    pub fn match_rate(&self) -> f64 {
+        self.matching as f64 / (self.matching + self.mismatching) as f64
+    }
+}
+
+/// Nearest-rank percentile of the sorted latencies, zero if there are none.
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+
+    let rank = ((percent * sorted.len() + 99) / 100).max(1);
+    sorted[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_should_aggregate_the_verdicts_and_the_latency_percentiles() {
+        let runs = (1..=100)
+            .map(|ms| (Some(ms % 10 != 0), Duration::from_millis(ms)))
+            .chain(std::iter::once((None, Duration::ZERO)))
+            .collect();
+
+        let report = ConcurrentReport::new(runs);
+
+        assert_eq!(
+            (
+                report.runs,
+                report.matching,
+                report.mismatching,
+                report.undecided
+            ),
+            (101, 90, 10, 1)
+        );
+        assert_eq!(report.p50, Duration::from_millis(50));
+        assert_eq!(report.p99, Duration::from_millis(99));
+        assert_eq!(report.max, Duration::from_millis(100));
+        assert_eq!(report.match_rate(), 0.9);
+    }
+
+    #[test]
+    fn report_of_no_runs_should_be_empty() {
+        let report = ConcurrentReport::new(Vec::new());
+
+        assert_eq!(report.max, Duration::ZERO);
+        assert!(report.match_rate().is_nan());
+    }
+}
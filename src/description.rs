@@ -0,0 +1,75 @@
+//! Descriptions of the configured experiments, e.g. for an admin endpoint listing them.
+
+/// Experiment description
+///
+/// Configuration of an experiment without its closures, see `CompleteExperiment::describe`. With
+/// the `serde` feature enabled, the description is serializable.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExperimentDescription {
+    /// The name under which the experiment is registered.
+    pub name: &'static str,
+    /// The group of the experiment, see `CompleteExperiment::group`.
+    pub group: Option<&'static str>,
+    /// The version of the experiment implementation, see `CompleteExperiment::version`.
+    pub version: Option<&'static str>,
+    /// The current probability of running the experiment branch, `1.0` if not sampled.
+    pub sample_rate: f64,
+    /// Whether the experiment branch is currently switched on, see `enabled_by_env`.
+    pub enabled: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Experiment;
+
+    #[test]
+    fn description_should_capture_the_configuration() {
+        let experiment = Experiment::new("Described")
+            .control(|| 1)
+            .experiment(|| 1)
+            .group("pricing")
+            .version("2")
+            .run_percentage(25.0);
+
+        let description = experiment.describe();
+
+        assert_eq!(description.name, "Described");
+        assert_eq!(description.group, Some("pricing"));
+        assert_eq!(description.version, Some("2"));
+        assert_eq!(description.sample_rate, 0.25);
+        assert!(description.enabled);
+    }
+
+    #[test]
+    fn description_of_an_unconfigured_experiment_should_be_always_running() {
+        let description = Experiment::new("Plain")
+            .control(|| 1)
+            .experiment(|| 1)
+            .describe();
+
+        assert_eq!(description.group, None);
+        assert_eq!(description.sample_rate, 1.0);
+        assert!(description.enabled);
+    }
+
+    #[cfg(all(feature = "serde", feature = "serde_json"))]
+    #[test]
+    fn description_should_be_serializable() {
+        let description = Experiment::new("Serialized")
+            .control(|| 1)
+            .experiment(|| 1)
+            .describe();
+
+        assert_eq!(
+            serde_json::to_value(description).unwrap(),
+            serde_json::json!({
+                "name": "Serialized",
+                "group": null,
+                "version": null,
+                "sample_rate": 1.0,
+                "enabled": true,
+            })
+        );
+    }
+}
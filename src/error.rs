@@ -0,0 +1,68 @@
+//! Errors of the experiment definitions.
+
+/// Experiment error
+///
+/// Error of defining an experiment, e.g. by `Experiment::try_new`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExperimentError {
+    /// The name of the experiment is empty.
+    EmptyName,
+    /// The name of the experiment contains a character outside of the allowed charset.
+    InvalidName { name: &'static str, character: char },
+}
+
+impl std::fmt::Display for ExperimentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyName => write!(f, "experiment name cannot be empty"),
+            Self::InvalidName { name, character } => write!(
+                f,
+                "experiment name '{}' contains the disallowed character {:?}",
+                name, character
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExperimentError {}
+
+/// Default charset of the strict names, `[a-zA-Z0-9_.-]`, accepted by the common metric backends.
+pub fn is_metric_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')
+}
+
+/// Validate the name of the experiment against the allowed charset.
+pub(crate) fn validate_name(
+    name: &'static str,
+    allowed: impl Fn(char) -> bool,
+) -> Result<&'static str, ExperimentError> {
+    if name.is_empty() {
+        return Err(ExperimentError::EmptyName);
+    }
+
+    match name.chars().find(|c| !allowed(*c)) {
+        Some(character) => Err(ExperimentError::InvalidName { name, character }),
+        None => Ok(name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_name_should_accept_only_the_allowed_characters() {
+        assert_eq!(
+            validate_name("checkout.v2_fast-path", is_metric_name_char),
+            Ok("checkout.v2_fast-path")
+        );
+        assert_eq!(
+            validate_name("checkout v2", is_metric_name_char),
+            Err(ExperimentError::InvalidName {
+                name: "checkout v2",
+                character: ' '
+            })
+        );
+        assert_eq!(validate_name("", |_| true), Err(ExperimentError::EmptyName));
+    }
+}
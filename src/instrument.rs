@@ -0,0 +1,215 @@
+use std::time::SystemTime;
+
+use crate::settings::Settings;
+
+/// Wall-clock start and end of a branch.
+pub(crate) type Wallclock = (SystemTime, SystemTime);
+
+/// Branch of the experiment, used to attribute the instrumentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Branch {
+    Control,
+    Experiment,
+}
+
+#[cfg(feature = "tracing")]
+fn span(name: &'static str, branch: Branch) -> tracing::Span {
+    match branch {
+        Branch::Control => tracing::info_span!("control", experiment = name),
+        Branch::Experiment => tracing::info_span!("experiment", experiment = name),
+    }
+}
+
+/// Execute the branch inside of its instrumentation.
+#[cfg(feature = "tracing")]
+pub(crate) fn in_branch<R>(
+    settings: &Settings,
+    name: &'static str,
+    branch: Branch,
+    f: impl FnOnce() -> R,
+) -> R {
+    if settings.spans {
+        span(name, branch).in_scope(f)
+    } else {
+        f()
+    }
+}
+
+/// Execute the branch inside of its instrumentation.
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn in_branch<R>(
+    _settings: &Settings,
+    _name: &'static str,
+    _branch: Branch,
+    f: impl FnOnce() -> R,
+) -> R {
+    f()
+}
+
+/// Await the `async` branch inside of its instrumentation.
+#[cfg(feature = "tracing")]
+pub(crate) async fn in_branch_async<F>(
+    settings: &Settings,
+    name: &'static str,
+    branch: Branch,
+    f: F,
+) -> F::Output
+where
+    F: std::future::Future,
+{
+    use tracing::Instrument;
+
+    if settings.spans {
+        f.instrument(span(name, branch)).await
+    } else {
+        f.await
+    }
+}
+
+/// Await the `async` branch inside of its instrumentation.
+#[cfg(not(feature = "tracing"))]
+pub(crate) async fn in_branch_async<F>(
+    _settings: &Settings,
+    _name: &'static str,
+    _branch: Branch,
+    f: F,
+) -> F::Output
+where
+    F: std::future::Future,
+{
+    f.await
+}
+
+/// Invoke the publisher inside of the `publish` span of the experiment.
+///
+/// The branches are awaited within their own spans, so that the publisher would otherwise run in
+/// whatever span polls the joined futures.
+#[cfg(feature = "tracing")]
+pub(crate) fn in_publish<R>(settings: &Settings, name: &'static str, f: impl FnOnce() -> R) -> R {
+    if settings.spans {
+        tracing::info_span!("publish", experiment = name).in_scope(f)
+    } else {
+        f()
+    }
+}
+
+/// Invoke the publisher inside of the `publish` span of the experiment.
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn in_publish<R>(_settings: &Settings, _name: &'static str, f: impl FnOnce() -> R) -> R {
+    f()
+}
+
+/// Execute the branch, recording its wall-clock start and end with the `wallclock` feature.
+#[cfg(feature = "wallclock")]
+pub(crate) fn stamped<R>(f: impl FnOnce() -> R) -> (R, Option<Wallclock>) {
+    let started = SystemTime::now();
+    let result = f();
+    (result, Some((started, SystemTime::now())))
+}
+
+/// Execute the branch, recording its wall-clock start and end with the `wallclock` feature.
+#[cfg(not(feature = "wallclock"))]
+pub(crate) fn stamped<R>(f: impl FnOnce() -> R) -> (R, Option<Wallclock>) {
+    (f(), None)
+}
+
+/// Await the `async` branch, recording its wall-clock start and end with the `wallclock` feature.
+#[cfg(feature = "wallclock")]
+pub(crate) async fn stamped_async<F>(f: F) -> (F::Output, Option<Wallclock>)
+where
+    F: std::future::Future,
+{
+    let started = SystemTime::now();
+    let result = f.await;
+    (result, Some((started, SystemTime::now())))
+}
+
+/// Await the `async` branch, recording its wall-clock start and end with the `wallclock` feature.
+#[cfg(not(feature = "wallclock"))]
+pub(crate) async fn stamped_async<F>(f: F) -> (F::Output, Option<Wallclock>)
+where
+    F: std::future::Future,
+{
+    (f.await, None)
+}
+
+/// Execute the branch, counting its allocations with the `alloc-counting` feature.
+#[cfg(feature = "alloc-counting")]
+pub(crate) fn counted<R>(f: impl FnOnce() -> R) -> (R, Option<u64>) {
+    crate::alloc::count(f)
+}
+
+/// Execute the branch, counting its allocations with the `alloc-counting` feature.
+#[cfg(not(feature = "alloc-counting"))]
+pub(crate) fn counted<R>(f: impl FnOnce() -> R) -> (R, Option<u64>) {
+    (f(), None)
+}
+
+#[cfg(all(test, feature = "tracing"))]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Subscriber recording the names of the entered spans.
+    #[derive(Clone, Default)]
+    pub(crate) struct EnteredSpans {
+        pub spans: Arc<Mutex<Vec<&'static str>>>,
+        names: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl Subscriber for EnteredSpans {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut names = self.names.lock().unwrap();
+            names.push(span.metadata().name());
+            Id::from_u64(names.len() as u64)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &Event<'_>) {}
+
+        fn enter(&self, span: &Id) {
+            let name = self.names.lock().unwrap()[span.into_u64() as usize - 1];
+            self.spans.lock().unwrap().push(name);
+        }
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn branch_should_be_executed_inside_of_its_span() {
+        let subscriber = EnteredSpans::default();
+        let spans = subscriber.spans.clone();
+
+        let result = tracing::subscriber::with_default(subscriber, || {
+            in_branch(&Settings::default(), "Test", Branch::Experiment, || 1)
+        });
+
+        assert_eq!(result, 1);
+        assert_eq!(*spans.lock().unwrap(), vec!["experiment"]);
+    }
+
+    #[test]
+    fn branch_should_not_enter_a_span_if_spans_are_disabled() {
+        let subscriber = EnteredSpans::default();
+        let spans = subscriber.spans.clone();
+        let settings = Settings {
+            spans: false,
+            ..Settings::default()
+        };
+
+        tracing::subscriber::with_default(subscriber, || {
+            in_branch(&settings, "Test", Branch::Control, || 1)
+        });
+
+        assert!(spans.lock().unwrap().is_empty());
+    }
+}
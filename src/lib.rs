@@ -36,11 +36,52 @@
 //!         .run().await;
 //! })
 //! ```
+//!
+//! `Experiment` provides no `async` entry point of its own, `async` code paths are always conducted
+//! through `AsyncExperiment`, which joins the **control** and **experiment** futures.
+//!
+//! # Disabling the experiments
+//! The `disabled` feature strips the experiments from the build, e.g. of a release binary, while
+//! the experiment code keeps type-checking. Every runner then only calls and returns the control,
+//! including `candidates`, `matrix`, `once`, `pair` and `previous`. The experiment is never run,
+//! nothing is published or measured, and the panic of the control is neither caught nor mapped by
+//! `map_control_panic`, nor bounded by a deadline. Features are unified across the dependency
+//! graph, so only the final binary should enable it, as it disables the experiments of every crate
+//! in the build. `RUSTFLAGS="--cfg scientisto_disabled"` has the same effect.
 
+#[cfg(feature = "alloc-counting")]
+pub mod alloc;
 pub mod async_experiment;
+pub mod blocking;
+pub mod budget;
+pub mod candidates;
+pub mod compare;
+pub mod concurrent;
+pub mod description;
+pub mod error;
+mod instrument;
+mod match_rate;
+pub mod matrix;
 pub mod observation;
+pub mod once;
+pub mod pair;
+mod panic_location;
+pub mod previous;
+pub mod projection;
+pub mod publisher;
+mod rate_limit;
+pub mod reconfigure;
+mod sampling;
+mod settings;
+pub mod size;
+pub mod staged;
 pub mod sync_experiment;
+pub mod timeout;
+pub mod verdict;
 
 pub use async_experiment::AsyncExperiment;
-pub use observation::Observation;
+pub use description::ExperimentDescription;
+pub use error::ExperimentError;
+pub use observation::{Observation, ObservationSummary, Outcome, Panic, Returned, SkipReason};
+pub use publisher::{set_default_publisher, Publisher};
 pub use sync_experiment::Experiment;
@@ -0,0 +1,90 @@
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+const EMPTY: u8 = 0;
+const MATCHING: u8 = 1;
+const MISMATCHING: u8 = 2;
+
+/// Sliding window of the verdicts of the most recent runs.
+///
+/// A lock-free ring of atomic slots, each run overwrites the oldest slot. Concurrent runs may race
+/// for the slots, which only makes the window approximate under contention.
+#[derive(Debug)]
+pub(crate) struct MatchWindow {
+    slots: Box<[AtomicU8]>,
+    next: AtomicUsize,
+}
+
+impl MatchWindow {
+    pub fn new(size: usize) -> Self {
+        Self {
+            slots: (0..size.max(1)).map(|_| AtomicU8::new(EMPTY)).collect(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn record(&self, matching: bool) {
+        let slot = self.next.fetch_add(1, Ordering::Relaxed) % self.slots.len();
+        let verdict = if matching { MATCHING } else { MISMATCHING };
+
+        self.slots[slot].store(verdict, Ordering::Relaxed);
+    }
+
+    /// Ratio of the matching runs in the window, `NaN` if nothing was recorded yet.
+    pub fn rate(&self) -> f64 {
+        let (matching, recorded) = self
+            .slots
+            .iter()
+            .map(|slot| slot.load(Ordering::Relaxed))
+            .fold(
+                (0usize, 0usize),
+                |(matching, recorded), verdict| match verdict {
+                    MATCHING => (matching + 1, recorded + 1),
+                    MISMATCHING => (matching, recorded + 1),
+                    _ => (matching, recorded),
+                },
+            );
+
+        if recorded == 0 {
+            f64::NAN
+        } else {
+            matching as f64 / recorded as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_window_should_be_nan_until_recorded() {
+        assert!(MatchWindow::new(4).rate().is_nan());
+    }
+
+    #[test]
+    fn match_window_should_compute_the_rate_over_the_last_runs() {
+        let window = MatchWindow::new(4);
+
+        [true, false, true, true]
+            .iter()
+            .for_each(|m| window.record(*m));
+        assert_eq!(window.rate(), 0.75);
+
+        [false, false].iter().for_each(|m| window.record(*m));
+        assert_eq!(window.rate(), 0.5);
+
+        [true, true, true, true]
+            .iter()
+            .for_each(|m| window.record(*m));
+        assert_eq!(window.rate(), 1.0);
+    }
+
+    #[test]
+    fn match_window_should_compute_the_rate_of_a_partially_filled_window() {
+        let window = MatchWindow::new(10);
+
+        [true, false].iter().for_each(|m| window.record(*m));
+
+        assert_eq!(window.rate(), 0.5);
+    }
+}
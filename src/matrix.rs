@@ -0,0 +1,226 @@
+//! Experiments comparing the **control** and the **experiment** over a matrix of inputs.
+
+use std::marker::PhantomData;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+use crate::publisher::{DefaultPublisher, Publisher};
+use crate::Observation;
+
+/// Signature of a branch taking the input, marking the otherwise unused type parameters.
+type Branch<I, T> = fn(&I) -> T;
+
+/// Control of a matrix experiment, taking the input by reference.
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct MatrixControlOnly<I, TC, FC, FP = DefaultPublisher>
+where
+    FC: Fn(&I) -> TC,
+{
+    name: &'static str,
+    control: FC,
+    publish: FP,
+    phantom: PhantomData<Branch<I, TC>>,
+}
+
+impl<I, TC, FC, FP> MatrixControlOnly<I, TC, FC, FP>
+where
+    FC: Fn(&I) -> TC,
+{
+    pub(crate) fn new(name: &'static str, control: FC, publish: FP) -> Self {
+        Self {
+            name,
+            control,
+            publish,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn experiment_with<TE, FE>(self, f: FE) -> MatrixExperiment<I, TC, FC, TE, FE, FP>
+    where
+        FE: Fn(&I) -> TE,
+    {
+        MatrixExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: f,
+            publish: self.publish,
+            stop_on_mismatch: false,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Matrix experiment
+///
+/// Experiment running the **control** and the **experiment** for each of the inputs, e.g. a
+/// cartesian product of the parameters, turning the experiment into a differential tester. Each
+/// input is compared using `PartialEq` and published as a separate observation, a panic in the
+/// experiment is recorded in its observation.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::{Experiment,Observation};
+///
+/// let inputs = (0..3).flat_map(|a| (0..3).map(move |b| (a, b))).collect();
+///
+/// let results = Experiment::new("Addition")
+///     .control_with(|(a, b): &(i32, i32)| a + b)
+///     .experiment_with(|(a, b): &(i32, i32)| b + a)
+///     .publish(|o: &Observation<i32, i32>| assert!(o.is_matching()))
+///     .run_matrix(inputs);
+///
+/// assert_eq!(results.len(), 9);
+/// ```
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct MatrixExperiment<I, TC, FC, TE, FE, FP = DefaultPublisher>
+where
+    FC: Fn(&I) -> TC,
+    FE: Fn(&I) -> TE,
+{
+    name: &'static str,
+    control: FC,
+    experiment: FE,
+    publish: FP,
+    stop_on_mismatch: bool,
+    phantom: PhantomData<(Branch<I, TC>, Branch<I, TE>)>,
+}
+
+impl<I, TC, FC, TE, FE, FP> MatrixExperiment<I, TC, FC, TE, FE, FP>
+where
+    FC: Fn(&I) -> TC,
+    FE: Fn(&I) -> TE,
+{
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Stop running the remaining inputs after the first mismatching one.
+    pub fn stop_on_mismatch(mut self, enabled: bool) -> Self {
+        self.stop_on_mismatch = enabled;
+        self
+    }
+
+    pub fn publish<F>(self, f: F) -> MatrixExperiment<I, TC, FC, TE, FE, F>
+    where
+        F: Fn(&Observation<TC, TE>),
+    {
+        MatrixExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: f,
+            stop_on_mismatch: self.stop_on_mismatch,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Run the experiment for each of the inputs in order, returning the control values.
+    ///
+    /// With `stop_on_mismatch`, the control values up to and including the first mismatching input
+    /// are returned.
+    ///
+    /// This is a differential runner for the tests, not for production traffic. The experiment has
+    /// no settings, so every input runs both of the branches and is published. The sampling,
+    /// `enabled_by_env`, the publishing rate limit and the tags do not apply to it. The disabled
+    /// build only runs the control for each of the inputs.
+    ///
+    /// # Panics
+    /// Panics if the **control** function panics for an input, once the input is published.
+    pub fn run_matrix(&self, inputs: Vec<I>) -> Vec<TC>
+    where
+        TE: PartialEq<TC>,
+        FP: Publisher<TC, TE>,
+    {
+        if cfg!(scientisto_disabled) {
+            return inputs.iter().map(|input| (self.control)(input)).collect();
+        }
+
+        let mut results = Vec::with_capacity(inputs.len());
+
+        for input in &inputs {
+            let control = catch_unwind(AssertUnwindSafe(|| (self.control)(input)));
+            let experiment = catch_unwind(AssertUnwindSafe(|| (self.experiment)(input)));
+            let observation = Observation::new(self.name, control, experiment);
+
+            self.publish.publish(&observation);
+
+            let matching = observation.is_matching();
+            match observation.control {
+                Ok(value) => results.push(value),
+                Err(e) => resume_unwind(e),
+            }
+
+            if self.stop_on_mismatch && !matching {
+                break;
+            }
+        }
+
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Experiment;
+    use crate::Observation;
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn matrix_experiment_should_publish_each_input_and_return_the_control_values() {
+        let published = std::cell::RefCell::new(Vec::new());
+
+        let actual = Experiment::new("Test")
+            .control_with(|i: &i32| i * 2)
+            .experiment_with(|i: &i32| i + 2)
+            .publish(|o: &Observation<i32, i32>| published.borrow_mut().push(o.is_matching()))
+            .run_matrix(vec![1, 2, 3]);
+
+        assert_eq!(actual, vec![2, 4, 6]);
+        assert_eq!(published.into_inner(), vec![false, true, false]);
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn matrix_experiment_should_stop_on_the_first_mismatch_if_enabled() {
+        let published = std::cell::Cell::new(0);
+
+        let actual = Experiment::new("Test")
+            .control_with(|i: &i32| i * 2)
+            .experiment_with(|i: &i32| i + 2)
+            .stop_on_mismatch(true)
+            .publish(|_: &Observation<i32, i32>| published.set(published.get() + 1))
+            .run_matrix(vec![2, 3, 4]);
+
+        assert_eq!(actual, vec![4, 6]);
+        assert_eq!(published.get(), 2);
+    }
+
+    #[test]
+    fn matrix_experiment_should_record_a_panicking_experiment() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+
+        let actual = Experiment::new("Test")
+            .control_with(|i: &i32| *i)
+            .experiment_with(|i: &i32| -> i32 { panic!("Unsupported {}", i) })
+            .publish(|o: &Observation<i32, i32>| assert!(!o.experiment_is_ok()))
+            .run_matrix(vec![1, 2]);
+
+        assert_eq!(actual, vec![1, 2]);
+    }
+
+    #[test]
+    #[cfg(scientisto_disabled)]
+    fn disabled_matrix_experiment_should_only_run_the_control_for_each_input() {
+        let actual = Experiment::new("Test")
+            .control_with(|i: &i32| i * 2)
+            .experiment_with(|_: &i32| -> i32 { panic!("experiment must not run") })
+            .stop_on_mismatch(true)
+            .publish(|_: &Observation<i32, i32>| panic!("nothing must be published"))
+            .run_matrix(vec![1, 2, 3]);
+
+        assert_eq!(actual, vec![2, 4, 6]);
+    }
+}
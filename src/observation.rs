@@ -1,69 +1,1143 @@
 use std::thread::Result;
+use std::time::{Duration, SystemTime};
+
+use crate::compare::{Comparator, MismatchDetail};
+use crate::instrument::Wallclock;
 
 /// Observation
 ///
 /// Observation aggregating the measurements collected during execution of the control and
 /// experimental functionality.
+///
+/// Prefer the `control` and `experiment` accessors over the public fields of the same names, the
+/// fields expose the representation of the panics and are going to become private.
 #[derive(Debug)]
 pub struct Observation<T, TE> {
+    name: &'static str,
     pub control: Result<T>,
     pub experiment: Result<TE>,
+    matching: Option<bool>,
+    detail: Option<String>,
+    control_duration: Option<Duration>,
+    experiment_duration: Option<Duration>,
+    control_panic_location: Option<(String, u32)>,
+    experiment_panic_location: Option<(String, u32)>,
+    control_wallclock: Option<Wallclock>,
+    experiment_wallclock: Option<Wallclock>,
+    control_allocs: Option<u64>,
+    experiment_allocs: Option<u64>,
+    compare_error: Option<String>,
+    control_size: Option<usize>,
+    experiment_size: Option<usize>,
+    ignored: bool,
+    in_grace_period: bool,
+    timed_out: bool,
+    control_timed_out: bool,
+    version: Option<&'static str>,
+    tags: Vec<(&'static str, String)>,
+    skip_reason: Option<SkipReason>,
+    returned: Returned,
+    mismatch: Option<MismatchDetail>,
+    diff: Option<String>,
+    comparator_name: &'static str,
 }
 
 impl<T, TE> Observation<T, TE> {
-    /// Verify whether the control and experiment output a comparably equal or matching value.
-    pub fn is_matching(&self) -> bool
+    /// Create an observation, comparing the control and experiment values using `PartialEq`.
+    pub fn new(name: &'static str, control: Result<T>, experiment: Result<TE>) -> Self
     where
         TE: PartialEq<T>,
     {
-        match (&self.experiment, &self.control) {
-            (Ok(a), Ok(b)) => a == b,
-            _ => false,
+        Self::compared_by(name, control, experiment, &crate::compare::Equality)
+    }
+
+    /// Create an observation, comparing the control and experiment values using the comparator.
+    pub(crate) fn compared_by<C>(
+        name: &'static str,
+        control: Result<T>,
+        experiment: Result<TE>,
+        comparator: &C,
+    ) -> Self
+    where
+        C: Comparator<T, TE> + ?Sized,
+    {
+        let comparison = match (&control, &experiment) {
+            (Ok(c), Ok(e)) => comparator.try_describe(c, e),
+            _ => Ok(false.into()),
+        };
+
+        let (ignored, mismatch, diff) = match (&comparison, &control, &experiment) {
+            (Ok(comparison), Ok(c), Ok(e)) if !comparison.matching => (
+                comparator.ignores(c, e),
+                comparator.mismatch(c, e),
+                comparator.diff(c, e),
+            ),
+            _ => (false, None, None),
+        };
+
+        match comparison {
+            Ok(comparison) => Self {
+                matching: Some(comparison.matching),
+                detail: comparison.detail,
+                ignored,
+                mismatch,
+                diff,
+                comparator_name: comparator.name(),
+                ..Self::uncompared(name, control, experiment)
+            },
+            Err(error) => Self {
+                compare_error: Some(error),
+                comparator_name: comparator.name(),
+                ..Self::uncompared(name, control, experiment)
+            },
+        }
+    }
+
+    /// Create an observation without comparing the control and experiment values.
+    ///
+    /// Such an observation is neither matching nor mismatching, see `verdict`.
+    pub fn uncompared(name: &'static str, control: Result<T>, experiment: Result<TE>) -> Self {
+        Self {
+            name,
+            control,
+            experiment,
+            matching: None,
+            detail: None,
+            control_duration: None,
+            experiment_duration: None,
+            control_panic_location: None,
+            experiment_panic_location: None,
+            control_wallclock: None,
+            experiment_wallclock: None,
+            control_allocs: None,
+            experiment_allocs: None,
+            compare_error: None,
+            control_size: None,
+            experiment_size: None,
+            ignored: false,
+            in_grace_period: false,
+            timed_out: false,
+            control_timed_out: false,
+            version: None,
+            tags: Vec::new(),
+            skip_reason: None,
+            returned: Returned::Control,
+            mismatch: None,
+            diff: None,
+            comparator_name: "PartialEq",
+        }
+    }
+
+    /// Record the measured durations of the branches.
+    pub(crate) fn timed(mut self, control: Duration, experiment: Duration) -> Self {
+        self.control_duration = Some(control);
+        self.experiment_duration = Some(experiment);
+        self
+    }
+
+    /// Create a matching observation of the values, e.g. to test a publisher.
+    ///
+    /// The values are not compared, the observation is named `manual`, see `with_name`.
+    pub fn matching(control: T, experiment: TE) -> Self {
+        Self::decided(Ok(control), Ok(experiment), true)
+    }
+
+    /// Create a mismatching observation of the values, e.g. to test a publisher.
+    pub fn mismatching(control: T, experiment: TE) -> Self {
+        Self::decided(Ok(control), Ok(experiment), false)
+    }
+
+    /// Create an observation of the control panicking with the message, e.g. to test a publisher.
+    pub fn control_panicked(message: &'static str, experiment: TE) -> Self {
+        Self::decided(Err(Box::new(message)), Ok(experiment), false)
+    }
+
+    /// Create an observation of the experiment panicking with the message, e.g. to test a
+    /// publisher.
+    pub fn experiment_panicked(control: T, message: &'static str) -> Self {
+        Self::decided(Ok(control), Err(Box::new(message)), false)
+    }
+
+    fn decided(control: Result<T>, experiment: Result<TE>, matching: bool) -> Self {
+        Self {
+            matching: Some(matching),
+            ..Self::uncompared("manual", control, experiment)
+        }
+    }
+
+    /// Rename the observation, e.g. one created manually.
+    pub fn with_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Record the wall-clock starts and ends of the branches.
+    pub(crate) fn stamped(
+        mut self,
+        control: Option<Wallclock>,
+        experiment: Option<Wallclock>,
+    ) -> Self {
+        self.control_wallclock = control;
+        self.experiment_wallclock = experiment;
+        self
+    }
+
+    /// Record the allocation counts of the branches.
+    pub(crate) fn counted(mut self, control: Option<u64>, experiment: Option<u64>) -> Self {
+        self.control_allocs = control;
+        self.experiment_allocs = experiment;
+        self
+    }
+
+    /// Decide the observation by the verdict determined elsewhere.
+    pub(crate) fn decided_as(mut self, matching: bool) -> Self {
+        self.matching = Some(matching);
+        self
+    }
+
+    /// Decide the compared observation with both of the branches panicking as matching.
+    pub(crate) fn matching_both_failed(mut self, enabled: bool) -> Self {
+        if enabled && self.matching.is_some() && self.control.is_err() && self.experiment.is_err() {
+            self.matching = Some(true);
+        }
+        self
+    }
+
+    /// Create an observation of a run whose experiment was skipped, see `publish_skips`.
+    ///
+    /// The experiment side holds the reason of the skip as the payload of its `Err`.
+    pub(crate) fn skipped(name: &'static str, control: Result<T>, reason: SkipReason) -> Self {
+        Self {
+            skip_reason: Some(reason),
+            ..Self::uncompared(name, control, Err(Box::new(reason)))
+        }
+    }
+
+    /// Record which of the branches returned its value to the caller, see `race_return`.
+    pub(crate) fn returning(mut self, returned: Returned) -> Self {
+        self.returned = returned;
+        self
+    }
+
+    /// Tag the observation with the version of the experiment.
+    pub(crate) fn versioned(mut self, version: Option<&'static str>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Tag the observation with the key-value metadata of the experiment.
+    pub(crate) fn tagged(mut self, tags: &[(&'static str, String)]) -> Self {
+        self.tags = tags.to_vec();
+        self
+    }
+
+    /// Record whether the experiment value is the fallback of a timed out experiment.
+    pub(crate) fn timed_out(mut self, timed_out: bool) -> Self {
+        self.timed_out = timed_out;
+        self
+    }
+
+    /// Record whether the control exceeded the deadline of the experiment.
+    pub(crate) fn control_expired(mut self, timed_out: bool) -> Self {
+        self.control_timed_out = timed_out;
+        self
+    }
+
+    /// Ignore the mismatch within the grace period, recording the applied policy.
+    pub(crate) fn graced(mut self, in_grace_period: bool) -> Self {
+        self.in_grace_period = in_grace_period;
+        if in_grace_period && self.matching == Some(false) {
+            self.ignored = true;
+        }
+        self
+    }
+
+    /// Record the sizes of the values of the branches.
+    pub(crate) fn sized(mut self, control: Option<usize>, experiment: Option<usize>) -> Self {
+        self.control_size = control;
+        self.experiment_size = experiment;
+        self
+    }
+
+    /// Record the locations of the panics of the branches.
+    pub(crate) fn panicked_at(
+        mut self,
+        control: Option<(String, u32)>,
+        experiment: Option<(String, u32)>,
+    ) -> Self {
+        self.control_panic_location = control;
+        self.experiment_panic_location = experiment;
+        self
+    }
+
+    /// The control value, or the panic of the control branch.
+    pub fn control(&self) -> std::result::Result<&T, Panic<'_>> {
+        self.control
+            .as_ref()
+            .map_err(|payload| Panic(payload.as_ref()))
+    }
+
+    /// The experiment value, or the panic of the experiment branch.
+    pub fn experiment(&self) -> std::result::Result<&TE, Panic<'_>> {
+        self.experiment
+            .as_ref()
+            .map_err(|payload| Panic(payload.as_ref()))
+    }
+
+    /// Whether the control branch returned a value, i.e. did not panic.
+    pub fn control_is_ok(&self) -> bool {
+        self.control.is_ok()
+    }
+
+    /// Whether the experiment branch returned a value, i.e. did not panic.
+    pub fn experiment_is_ok(&self) -> bool {
+        self.experiment.is_ok()
+    }
+
+    /// The payload of the control panic of the type `P`, see `Panic::downcast`.
+    pub fn control_panic_downcast<P: 'static>(&self) -> Option<&P> {
+        self.control().err()?.downcast()
+    }
+
+    /// The payload of the experiment panic of the type `P`, e.g. a structured error.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Invariant { code: u32 }
+    ///
+    /// Experiment::new("Structured panic")
+    ///     .control(|| 1)
+    ///     .experiment(|| -> i32 { std::panic::panic_any(Invariant { code: 7 }) })
+    ///     .publish(|o: &Observation<i32, i32>| {
+    ///         assert_eq!(o.experiment_panic_downcast(), Some(&Invariant { code: 7 }));
+    ///         assert_eq!(o.experiment_panic_downcast::<String>(), None);
+    ///     })
+    ///     .run();
+    /// ```
+    pub fn experiment_panic_downcast<P: 'static>(&self) -> Option<&P> {
+        self.experiment().err()?.downcast()
+    }
+
+    /// The raw payloads of the control and experiment panics, `None` for a branch which returned.
+    ///
+    /// Lets a publisher downcast to several custom payload types of either branch without matching
+    /// on both of the results.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Raw payloads")
+    ///     .control(|| 1)
+    ///     .experiment(|| -> i32 { std::panic::panic_any(7_u8) })
+    ///     .publish(|o: &Observation<i32, i32>| {
+    ///         let (control, experiment) = o.panic_payloads();
+    ///         assert!(control.is_none());
+    ///         assert_eq!(experiment.and_then(|p| p.downcast_ref::<u8>()), Some(&7));
+    ///     })
+    ///     .run();
+    /// ```
+    pub fn panic_payloads(
+        &self,
+    ) -> (
+        Option<&(dyn std::any::Any + Send)>,
+        Option<&(dyn std::any::Any + Send)>,
+    ) {
+        (
+            self.control().err().map(|panic| panic.payload()),
+            self.experiment().err().map(|panic| panic.payload()),
+        )
+    }
+
+    /// The name of the experiment the observation was collected for.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The version of the experiment the observation was collected for, see `version`.
+    pub fn version(&self) -> Option<&'static str> {
+        self.version
+    }
+
+    /// The key-value metadata of the experiment, in the order the keys were first tagged, see
+    /// `tag`.
+    pub fn tags(&self) -> &[(&'static str, String)] {
+        &self.tags
+    }
+
+    /// The value of the tag of the key, see `tag`.
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Why the experiment was skipped, for the runs published by `publish_skips` only.
+    ///
+    /// A skipped run is uncompared and the experiment side holds the reason instead of a value.
+    pub fn skip_reason(&self) -> Option<SkipReason> {
+        self.skip_reason
+    }
+
+    /// Which of the branches returned its value to the caller, always the control unless raced by
+    /// `race_return`.
+    pub fn returned(&self) -> Returned {
+        self.returned
+    }
+
+    /// Verify whether the control and experiment output a comparably equal or matching value.
+    ///
+    /// The values are compared when the observation is created, using the comparator of the
+    /// experiment (`PartialEq` by default). A panic in either of them is never matching, unless both
+    /// panicked with `match_on_both_failed` enabled. An uncompared observation is never matching.
+    pub fn is_matching(&self) -> bool {
+        self.matching == Some(true)
+    }
+
+    /// Whether the values are mismatching, but the mismatch is ignored, see `ignore`.
+    pub fn is_ignored(&self) -> bool {
+        self.ignored
+    }
+
+    /// Whether the experiment exceeded its timeout and the experiment value is the fallback.
+    pub fn experiment_timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    /// Whether the control exceeded the deadline of the experiment, see
+    /// `AsyncCompleteExperiment::deadline`.
+    ///
+    /// The control is then recorded as a panic with the `timeout::DeadlineExceeded` payload.
+    pub fn control_timed_out(&self) -> bool {
+        self.control_timed_out
+    }
+
+    /// Whether the run was within the grace period of `strict_after`, ignoring any mismatch.
+    pub fn in_grace_period(&self) -> bool {
+        self.in_grace_period
+    }
+
+    /// Whether the observation is effectively fine, i.e. matching or an ignored mismatch.
+    ///
+    /// Unlike `is_matching`, which stays strict, so that the problems are `!matching_or_ignored()`.
+    pub fn matching_or_ignored(&self) -> bool {
+        self.is_matching() || self.ignored
+    }
+
+    /// Outcome of the comparison of the control and experiment values, `None` if not compared or
+    /// if the comparison failed, see `compare_error`.
+    pub fn verdict(&self) -> Option<bool> {
+        self.matching
+    }
+
+    /// Detail of the comparison provided by the comparator, e.g. the location of the difference.
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    /// Structured shape of the mismatch, if the comparator provides it, see `compare::ElementWise`.
+    pub fn mismatch(&self) -> Option<MismatchDetail> {
+        self.mismatch
+    }
+
+    /// Diff of the mismatching values, if the comparator provides it, see `compare::diff::Diffed`.
+    pub fn diff(&self) -> Option<&str> {
+        self.diff.as_deref()
+    }
+
+    /// Name of the comparator which produced the verdict, e.g. `approx` for `compare::approx`.
+    ///
+    /// `PartialEq` by default, the wrapping comparators like `ignore_when` keep the inner name and
+    /// the closures are named `custom`.
+    pub fn comparator_name(&self) -> &'static str {
+        self.comparator_name
+    }
+
+    /// Error of a failed comparison, neither matching nor mismatching, see `try_comparator`.
+    pub fn compare_error(&self) -> Option<&str> {
+        self.compare_error.as_deref()
+    }
+
+    /// Duration of the control, if the experiment was run timed.
+    pub fn control_duration(&self) -> Option<Duration> {
+        self.control_duration
+    }
+
+    /// Duration of the experiment, if the experiment was run timed.
+    pub fn experiment_duration(&self) -> Option<Duration> {
+        self.experiment_duration
+    }
+
+    /// Number of the allocations of the control, recorded with the `alloc-counting` feature and
+    /// the `alloc::CountingAllocator` installed.
+    pub fn control_allocs(&self) -> Option<u64> {
+        self.control_allocs
+    }
+
+    /// Number of the allocations of the experiment, see `control_allocs`.
+    pub fn experiment_allocs(&self) -> Option<u64> {
+        self.experiment_allocs
+    }
+
+    /// Ratio of the allocations of the experiment to the ones of the control, infinite if only
+    /// the experiment allocated and `NaN` if neither did.
+    pub fn alloc_ratio(&self) -> Option<f64> {
+        self.control_allocs
+            .zip(self.experiment_allocs)
+            .map(|(control, experiment)| experiment as f64 / control as f64)
+    }
+
+    /// Wall-clock time the control started at, recorded with the `wallclock` feature.
+    pub fn control_started_at(&self) -> Option<SystemTime> {
+        self.control_wallclock.map(|(started, _)| started)
+    }
+
+    /// Wall-clock time the control finished at, recorded with the `wallclock` feature.
+    pub fn control_finished_at(&self) -> Option<SystemTime> {
+        self.control_wallclock.map(|(_, finished)| finished)
+    }
+
+    /// Wall-clock time the experiment started at, recorded with the `wallclock` feature.
+    pub fn experiment_started_at(&self) -> Option<SystemTime> {
+        self.experiment_wallclock.map(|(started, _)| started)
+    }
+
+    /// Wall-clock time the experiment finished at, recorded with the `wallclock` feature.
+    pub fn experiment_finished_at(&self) -> Option<SystemTime> {
+        self.experiment_wallclock.map(|(_, finished)| finished)
+    }
+
+    /// File and line where the control panicked, if the panic locations were captured.
+    pub fn control_panic_location(&self) -> Option<(String, u32)> {
+        self.control_panic_location.clone()
+    }
+
+    /// File and line where the experiment panicked, if the panic locations were captured.
+    pub fn experiment_panic_location(&self) -> Option<(String, u32)> {
+        self.experiment_panic_location.clone()
+    }
+
+    /// Outcome of the observation covering every combination of the branch results.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::observation::{Observation, Outcome};
+    ///
+    /// match Observation::mismatching(1, 2).outcome() {
+    ///     Outcome::BothOk { matching } => assert!(!matching),
+    ///     Outcome::ControlPanicked | Outcome::ExperimentPanicked | Outcome::BothPanicked => {
+    ///         unreachable!()
+    ///     }
+    /// }
+    /// ```
+    pub fn outcome(&self) -> Outcome {
+        match (self.control_is_ok(), self.experiment_is_ok()) {
+            (true, true) => Outcome::BothOk {
+                matching: self.is_matching(),
+            },
+            (false, true) => Outcome::ControlPanicked,
+            (true, false) => Outcome::ExperimentPanicked,
+            (false, false) => Outcome::BothPanicked,
+        }
+    }
+
+    /// Size of the control value, if measured, see `with_size`.
+    pub fn control_size(&self) -> Option<usize> {
+        self.control_size
+    }
+
+    /// Size of the experiment value, if measured, see `with_experiment_size`.
+    pub fn experiment_size(&self) -> Option<usize> {
+        self.experiment_size
+    }
+
+    /// Ratio of the experiment size to the control size, below `1.0` if the experiment is smaller.
+    pub fn size_ratio(&self) -> Option<f64> {
+        self.control_size
+            .zip(self.experiment_size)
+            .map(|(control, experiment)| experiment as f64 / control as f64)
+    }
+
+    /// Whether the experiment was strictly faster than the control, if the experiment was run timed.
+    pub fn experiment_faster(&self) -> Option<bool> {
+        self.control_duration
+            .zip(self.experiment_duration)
+            .map(|(control, experiment)| experiment < control)
+    }
+
+    /// Compact one-line summary for logging, see `ObservationSummary::summary_line`.
+    pub fn summary_line(&self) -> String {
+        self.summary().summary_line()
+    }
+
+    /// Summarize the observation into an owned value independent of the observed types.
+    pub fn summary(&self) -> ObservationSummary {
+        ObservationSummary {
+            name: self.name,
+            version: self.version,
+            matching: self.is_matching(),
+            control_panicked: !self.control_is_ok(),
+            experiment_panicked: !self.experiment_is_ok(),
+            detail: self.detail.clone(),
+            control_duration: self.control_duration,
+            experiment_duration: self.experiment_duration,
         }
     }
 }
 
+/// Panic
+///
+/// Panic of a branch of the experiment, borrowed from the observation.
+#[derive(Debug, Clone, Copy)]
+pub struct Panic<'a>(&'a (dyn std::any::Any + Send));
+
+impl<'a> Panic<'a> {
+    /// The message of the panic, if it was raised with a string, e.g. using `panic!`.
+    pub fn message(&self) -> Option<&'a str> {
+        self.0
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| self.0.downcast_ref::<String>().map(String::as_str))
+    }
+
+    /// The raw payload of the panic.
+    pub fn payload(&self) -> &'a (dyn std::any::Any + Send) {
+        self.0
+    }
+
+    /// The payload of the panic, if it was raised with a value of the type `P`, e.g. using
+    /// `std::panic::panic_any`.
+    pub fn downcast<P: 'static>(&self) -> Option<&'a P> {
+        self.0.downcast_ref::<P>()
+    }
+}
+
+/// Outcome
+///
+/// Every state an observation can be in, for an exhaustive `match` in the publishers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Both of the branches returned a value, `matching` is the verdict of the comparison.
+    BothOk { matching: bool },
+    /// The control panicked, the experiment returned a value.
+    ControlPanicked,
+    /// The experiment panicked, the control returned a value.
+    ExperimentPanicked,
+    /// Both of the branches panicked.
+    BothPanicked,
+}
+
+/// Skip reason
+///
+/// Cause of skipping the experiment branch, in the order the causes are checked: the predicate of
+/// `run_if` first, then the `enabled_by_env` switch, the sampling and the shared budget last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The predicate of `run_if` returned `false`.
+    Predicate,
+    /// The `enabled_by_env` switch is off, or the experiments are disabled in the build.
+    Disabled,
+    /// The run was not sampled by `run_percentage` or `adaptive_sample`.
+    Sampled,
+    /// The shared sampling budget of `with_budget` was exhausted.
+    Budget,
+}
+
+/// Returned
+///
+/// Branch whose value was returned to the caller of the experiment, see `race_return`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Returned {
+    /// The value of the control was returned.
+    Control,
+    /// The value of the experiment was returned, because it completed first.
+    Experiment,
+}
+
+/// Payload left in `Observation::control` once the control was moved out to the caller, see
+/// `CompleteExperiment::run_observed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MovedOut;
+
+/// Observation summary
+///
+/// Owned summary of an `Observation`, which can be stored independently of the observed values.
+/// With the `serde` feature enabled, the summary is serializable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ObservationSummary {
+    pub name: &'static str,
+    pub version: Option<&'static str>,
+    pub matching: bool,
+    pub control_panicked: bool,
+    pub experiment_panicked: bool,
+    pub detail: Option<String>,
+    pub control_duration: Option<Duration>,
+    pub experiment_duration: Option<Duration>,
+}
+
+impl ObservationSummary {
+    /// Compact `key=value` line for logging, e.g. `experiment=foo matching=false control_ms=1.200`.
+    ///
+    /// The missing durations are left out, a panicked branch is logged as `control_panicked=true`
+    /// or `experiment_panicked=true`. The detail of the mismatch is quoted as the last field.
+    pub fn summary_line(&self) -> String {
+        let mut line = format!("experiment={}", self.name);
+        if let Some(version) = self.version {
+            line.push_str(&format!(" version={}", version));
+        }
+        line.push_str(&format!(" matching={}", self.matching));
+        if self.control_panicked {
+            line.push_str(" control_panicked=true");
+        }
+        if self.experiment_panicked {
+            line.push_str(" experiment_panicked=true");
+        }
+        if let Some(duration) = self.control_duration {
+            line.push_str(&format!(
+                " control_ms={:.3}",
+                duration.as_secs_f64() * 1000.0
+            ));
+        }
+        if let Some(duration) = self.experiment_duration {
+            line.push_str(&format!(
+                " experiment_ms={:.3}",
+                duration.as_secs_f64() * 1000.0
+            ));
+        }
+        if let Some(detail) = &self.detail {
+            line.push_str(&format!(" detail={:?}", detail));
+        }
+        line
+    }
+}
+
+/// Assert the summary of the observation matches the stored `insta` snapshot.
+///
+/// Available with the `serde` feature, the crate using the macro has to depend on `insta` with
+/// its `yaml` feature. The summary is serialized without the durations of the branches, so that
+/// the snapshot locks in the verdict, the panics and the detail of the mismatch only. The name of
+/// the snapshot is optional, like with `insta::assert_yaml_snapshot!`.
+///
+/// # Examples
+/// ```rust,ignore
+/// use scientisto::{assert_experiment_snapshot, Experiment};
+///
+/// #[test]
+/// fn rewrite_should_keep_rounding_the_totals() {
+///     Experiment::new("Totals")
+///         .control(|| 1.5_f64.round())
+///         .experiment(|| 1.5_f64.floor())
+///         .publish(|o: &scientisto::Observation<f64, f64>| assert_experiment_snapshot!(o))
+///         .run();
+/// }
+/// ```
+#[cfg(feature = "serde")]
+#[macro_export]
+macro_rules! assert_experiment_snapshot {
+    ($observation:expr) => {
+        ::insta::assert_yaml_snapshot!($crate::ObservationSummary {
+            control_duration: None,
+            experiment_duration: None,
+            ..$observation.summary()
+        })
+    };
+    ($name:expr, $observation:expr) => {
+        ::insta::assert_yaml_snapshot!(
+            $name,
+            $crate::ObservationSummary {
+                control_duration: None,
+                experiment_duration: None,
+                ..$observation.summary()
+            }
+        )
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn observation_should_derive_the_debug_trait() {
-        let observation = Observation::<i32, i32> {
-            control: Result::Ok(1),
-            experiment: Result::Ok(1),
-        };
+        let observation = Observation::<i32, i32>::new("Test", Result::Ok(1), Result::Ok(1));
 
         assert_ne!(format!("{:?}", observation), "");
     }
 
     #[test]
     fn observation_should_indicate_matching_when_comparable_types_have_matching_values() {
-        let observation = Observation::<i32, i32> {
-            control: Result::Ok(1),
-            experiment: Result::Ok(1),
-        };
+        let observation = Observation::<i32, i32>::new("Test", Result::Ok(1), Result::Ok(1));
 
         assert!(observation.is_matching())
     }
 
     #[test]
     fn observation_should_indicate_non_matching_when_comparable_types_have_non_matching_values() {
-        let observation = Observation::<i32, i32> {
-            control: Result::Ok(1),
-            experiment: Result::Ok(2),
-        };
+        let observation = Observation::<i32, i32>::new("Test", Result::Ok(1), Result::Ok(2));
 
         assert!(!observation.is_matching())
     }
 
     #[test]
     fn observation_should_indicate_non_matching_when_non_matching_result_values_are_measured() {
-        let observation = Observation::<i32, i32> {
-            control: Result::Ok(1),
-            experiment: Result::Err(Box::new("Error")),
-        };
+        let observation =
+            Observation::<i32, i32>::new("Test", Result::Ok(1), Result::Err(Box::new("Error")));
 
         assert!(!observation.is_matching())
     }
+
+    #[test]
+    fn observation_should_indicate_matching_when_the_comparator_matches_the_values() {
+        let observation = Observation::<i32, &str>::compared_by(
+            "Test",
+            Result::Ok(1),
+            Result::Ok("1"),
+            &|c: &i32, e: &&str| c.to_string() == *e,
+        );
+
+        assert!(observation.is_matching())
+    }
+
+    struct Described;
+
+    impl Comparator<i32, i32> for Described {
+        fn compare(&self, control: &i32, experiment: &i32) -> bool {
+            control == experiment
+        }
+
+        fn describe(&self, control: &i32, experiment: &i32) -> crate::compare::Comparison {
+            crate::compare::Comparison {
+                matching: self.compare(control, experiment),
+                detail: Some(format!("difference: {}", experiment - control)),
+            }
+        }
+    }
+
+    #[test]
+    fn observation_should_record_the_detail_of_the_comparison() {
+        let observation = Observation::<i32, i32>::compared_by("Test", Ok(1), Ok(3), &Described);
+
+        assert!(!observation.is_matching());
+        assert_eq!(observation.detail(), Some("difference: 2"));
+    }
+
+    #[test]
+    fn observation_should_have_no_detail_if_a_branch_panicked() {
+        let observation =
+            Observation::<i32, i32>::compared_by("Test", Ok(1), Err(Box::new("Error")), &Described);
+
+        assert_eq!(observation.detail(), None);
+    }
+
+    #[test]
+    fn observation_should_be_summarized_independently_of_the_observed_types() {
+        let observation = Observation::<i32, i32>::new("Test", Ok(1), Err(Box::new("Error")));
+
+        assert_eq!(observation.name(), "Test");
+        assert_eq!(
+            observation.summary(),
+            ObservationSummary {
+                name: "Test",
+                version: None,
+                matching: false,
+                control_panicked: false,
+                experiment_panicked: true,
+                detail: None,
+                control_duration: None,
+                experiment_duration: None,
+            }
+        );
+    }
+
+    #[test]
+    fn observation_should_record_the_durations_when_timed() {
+        let observation = Observation::<i32, i32>::new("Test", Ok(1), Ok(1))
+            .timed(Duration::from_millis(1), Duration::from_millis(2));
+
+        assert_eq!(
+            observation.control_duration(),
+            Some(Duration::from_millis(1))
+        );
+        assert_eq!(
+            observation.experiment_duration(),
+            Some(Duration::from_millis(2))
+        );
+    }
+
+    #[test]
+    fn observation_should_not_be_matching_nor_mismatching_if_not_compared() {
+        struct Incomparable;
+        let observation = Observation::uncompared("Test", Ok(Incomparable), Ok(Incomparable));
+
+        assert_eq!(observation.verdict(), None);
+        assert!(!observation.is_matching());
+        assert_eq!(
+            Observation::<i32, i32>::new("Test", Ok(1), Ok(2)).verdict(),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn observation_should_indicate_whether_the_experiment_was_faster_if_timed() {
+        let timed = |control, experiment| {
+            Observation::<i32, i32>::new("Test", Ok(1), Ok(1)).timed(
+                Duration::from_millis(control),
+                Duration::from_millis(experiment),
+            )
+        };
+
+        assert_eq!(timed(2, 1).experiment_faster(), Some(true));
+        assert_eq!(timed(1, 1).experiment_faster(), Some(false));
+        assert_eq!(timed(1, 2).experiment_faster(), Some(false));
+        assert_eq!(
+            Observation::<i32, i32>::new("Test", Ok(1), Ok(1)).experiment_faster(),
+            None
+        );
+    }
+
+    #[test]
+    fn observation_should_expose_the_recorded_wallclock_times() {
+        let start = SystemTime::UNIX_EPOCH;
+        let at = |secs| start + Duration::from_secs(secs);
+        let observation = Observation::<i32, i32>::new("Test", Ok(1), Ok(1))
+            .stamped(Some((at(1), at(2))), Some((at(3), at(4))));
+
+        assert_eq!(observation.control_started_at(), Some(at(1)));
+        assert_eq!(observation.control_finished_at(), Some(at(2)));
+        assert_eq!(observation.experiment_started_at(), Some(at(3)));
+        assert_eq!(observation.experiment_finished_at(), Some(at(4)));
+    }
+
+    #[test]
+    fn observation_should_be_constructible_manually_for_each_outcome() {
+        let matching = Observation::matching(1, 1).with_name("Test");
+        assert_eq!(matching.name(), "Test");
+        assert!(matching.is_matching());
+
+        assert_eq!(Observation::mismatching(1, 2).verdict(), Some(false));
+
+        let control_panicked = Observation::<i32, i32>::control_panicked("Oops", 1);
+        assert_eq!(
+            control_panicked
+                .control
+                .as_ref()
+                .err()
+                .and_then(|e| e.downcast_ref::<&str>()),
+            Some(&"Oops")
+        );
+        assert!(!control_panicked.is_matching());
+
+        let experiment_panicked = Observation::<i32, i32>::experiment_panicked(1, "Oops");
+        assert!(experiment_panicked.summary().experiment_panicked);
+    }
+
+    #[test]
+    fn observation_should_have_the_matching_outcome_if_both_branches_matched() {
+        assert_eq!(
+            Observation::matching(1, 1).outcome(),
+            Outcome::BothOk { matching: true }
+        );
+    }
+
+    #[test]
+    fn observation_should_have_the_mismatching_outcome_if_both_branches_differed() {
+        assert_eq!(
+            Observation::mismatching(1, 2).outcome(),
+            Outcome::BothOk { matching: false }
+        );
+    }
+
+    #[test]
+    fn observation_should_have_the_control_panicked_outcome() {
+        assert_eq!(
+            Observation::<i32, i32>::control_panicked("Oops", 1).outcome(),
+            Outcome::ControlPanicked
+        );
+    }
+
+    #[test]
+    fn observation_should_have_the_experiment_panicked_outcome() {
+        assert_eq!(
+            Observation::<i32, i32>::experiment_panicked(1, "Oops").outcome(),
+            Outcome::ExperimentPanicked
+        );
+    }
+
+    #[test]
+    fn observation_should_have_the_both_panicked_outcome() {
+        let observation =
+            Observation::<i32, i32>::new("Test", Err(Box::new("Oops")), Err(Box::new("Oops")));
+
+        assert_eq!(observation.outcome(), Outcome::BothPanicked);
+    }
+
+    #[test]
+    fn observation_should_provide_the_values_of_the_branches() {
+        let observation = Observation::matching(1, "1");
+
+        assert_eq!(observation.control().ok(), Some(&1));
+        assert_eq!(observation.experiment().ok(), Some(&"1"));
+    }
+
+    #[test]
+    fn observation_should_provide_the_panic_messages_of_the_branches() {
+        let observation = Observation::<i32, i32>::new(
+            "Test",
+            Result::Err(Box::new(String::from("Control"))),
+            Result::Err(Box::new("Experiment")),
+        );
+
+        assert_eq!(
+            observation.control().unwrap_err().message(),
+            Some("Control")
+        );
+        assert_eq!(
+            observation.experiment().unwrap_err().message(),
+            Some("Experiment")
+        );
+    }
+
+    #[test]
+    fn observation_should_provide_the_raw_payload_of_a_panic_without_a_message() {
+        let observation =
+            Observation::<i32, i32>::new("Test", Result::Ok(1), Result::Err(Box::new(7u8)));
+        let panic = observation.experiment().unwrap_err();
+
+        assert_eq!(panic.message(), None);
+        assert_eq!(panic.payload().downcast_ref::<u8>(), Some(&7));
+    }
+
+    #[test]
+    fn observation_should_provide_the_raw_payloads_of_both_panics() {
+        let observation = Observation::<i32, i32>::new(
+            "Test",
+            Result::Err(Box::new("Oops")),
+            Result::Err(Box::new(7u8)),
+        );
+        let (control, experiment) = observation.panic_payloads();
+
+        assert_eq!(control.unwrap().downcast_ref::<&str>(), Some(&"Oops"));
+        assert_eq!(experiment.unwrap().downcast_ref::<u8>(), Some(&7));
+        assert!(Observation::matching(1, 1).panic_payloads().0.is_none());
+    }
+
+    #[test]
+    fn observation_should_record_the_error_of_a_failed_comparison() {
+        let observation = Observation::<&str, &str>::compared_by(
+            "Test",
+            Result::Ok("1"),
+            Result::Ok("one"),
+            &crate::compare::fallible(|c: &&str, e: &&str| {
+                Ok::<_, std::num::ParseIntError>(c.parse::<i32>()? == e.parse::<i32>()?)
+            }),
+        );
+
+        assert_eq!(observation.verdict(), None);
+        assert!(!observation.is_matching());
+        assert_eq!(
+            observation.compare_error(),
+            Some("invalid digit found in string")
+        );
+    }
+
+    #[test]
+    fn observation_should_be_matching_or_ignored_unless_an_unignored_mismatch() {
+        let ignore_odd =
+            crate::compare::ignore_when(crate::compare::Equality, |_: &i32, e: &i32| e % 2 == 1);
+        let observe = |experiment: i32| {
+            Observation::<i32, i32>::compared_by("Test", Ok(1), Ok(experiment), &ignore_odd)
+        };
+
+        let cases = [
+            (observe(1), true, false, true),
+            (observe(3), false, true, true),
+            (observe(2), false, false, false),
+            (Observation::new("Test", Ok(1), Ok(1)), true, false, true),
+        ];
+
+        for (observation, matching, ignored, fine) in cases {
+            assert_eq!(observation.is_matching(), matching);
+            assert_eq!(observation.is_ignored(), ignored);
+            assert_eq!(observation.matching_or_ignored(), fine);
+        }
+    }
+
+    #[test]
+    fn observation_should_record_the_name_of_the_comparator() {
+        let compared = |comparator: &dyn Comparator<f64, f64>| {
+            Observation::compared_by("Test", Ok(1.0), Ok(1.5), comparator).comparator_name()
+        };
+
+        assert_eq!(
+            Observation::new("Test", Ok(1), Ok(1)).comparator_name(),
+            "PartialEq"
+        );
+        assert_eq!(compared(&crate::compare::approx(0.1)), "approx");
+        assert_eq!(
+            compared(&crate::compare::ignore_when(
+                crate::compare::nan_eq(),
+                |_: &f64, _: &f64| true
+            )),
+            "nan_eq"
+        );
+        assert_eq!(compared(&|c: &f64, e: &f64| c < e), "custom");
+    }
+
+    #[test]
+    fn observation_should_tell_which_branches_returned_a_value() {
+        let observation = Observation::<i32, i32>::control_panicked("Oops", 1);
+
+        assert!(!observation.control_is_ok());
+        assert!(observation.experiment_is_ok());
+    }
+
+    #[test]
+    fn summary_line_should_log_the_durations_and_the_panics() {
+        let timed = Observation::<i32, i32>::new("Test", Ok(1), Ok(2))
+            .timed(Duration::from_micros(1200), Duration::from_micros(3400));
+        let panicked = Observation::<i32, i32>::experiment_panicked(1, "Oops");
+
+        assert_eq!(
+            timed.summary_line(),
+            "experiment=Test matching=false control_ms=1.200 experiment_ms=3.400"
+        );
+        assert_eq!(
+            panicked.summary_line(),
+            "experiment=manual matching=false experiment_panicked=true"
+        );
+    }
+
+    #[test]
+    fn observation_should_downcast_the_panic_payloads() {
+        let observation = Observation::<i32, i32>::new("Test", Err(Box::new(42_u32)), Ok(1));
+
+        assert_eq!(observation.control_panic_downcast::<u32>(), Some(&42));
+        assert_eq!(observation.control_panic_downcast::<i32>(), None);
+        assert_eq!(observation.experiment_panic_downcast::<u32>(), None);
+    }
+
+    #[cfg(all(feature = "serde", feature = "serde_json"))]
+    #[test]
+    fn observation_summary_should_be_serializable() {
+        let summary = Observation::mismatching(1, 2).summary();
+
+        assert_eq!(
+            serde_json::to_value(summary).unwrap(),
+            serde_json::json!({
+                "name": "manual",
+                "version": null,
+                "matching": false,
+                "control_panicked": false,
+                "experiment_panicked": false,
+                "detail": null,
+                "control_duration": null,
+                "experiment_duration": null,
+            })
+        );
+    }
 }
@@ -0,0 +1,220 @@
+//! Single-run experiments whose **control** and **experiment** consume their captures.
+
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+use crate::compare::{Comparator, Equality};
+use crate::publisher::{DefaultPublisher, Publisher};
+use crate::Observation;
+
+/// Single-run experiment with the **control** defined only.
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct OnceControlOnly<FC, FP = DefaultPublisher> {
+    name: &'static str,
+    control: FC,
+    publish: FP,
+}
+
+impl<FC, FP> OnceControlOnly<FC, FP> {
+    pub(crate) fn new(name: &'static str, control: FC, publish: FP) -> Self {
+        Self {
+            name,
+            control,
+            publish,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn experiment_once<TE, F>(self, f: F) -> OnceExperiment<FC, F, FP>
+    where
+        F: FnOnce() -> TE,
+    {
+        OnceExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: f,
+            publish: self.publish,
+            comparator: Equality,
+        }
+    }
+
+    /// Use a precomputed result as the **experiment**, compared to the control without executing
+    /// anything.
+    ///
+    /// An `Err` is recorded in the observation like a panic of the branch.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let result = Experiment::new("Captured")
+    ///     .control_result(Ok(3))
+    ///     .experiment_result(Err(Box::new("timed out")))
+    ///     .publish(|o: &Observation<i32, i32>| assert!(!o.experiment_is_ok()))
+    ///     .run();
+    ///
+    /// assert_eq!(result, 3);
+    /// ```
+    pub fn experiment_result<TE>(
+        self,
+        result: std::thread::Result<TE>,
+    ) -> OnceExperiment<FC, impl FnOnce() -> TE, FP> {
+        self.experiment_once(move || result.unwrap_or_else(|e| resume_unwind(e)))
+    }
+}
+
+/// Single-run experiment
+///
+/// Experiment whose **control** and **experiment** are `FnOnce` closures, e.g. moving the captured
+/// values into the calls, so that the move-only values do not have to be wrapped into an `Option`
+/// or cloned to satisfy the `Fn` bound. Running the experiment consumes it.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::{Experiment,Observation};
+///
+/// let (input, copy) = (String::from("value"), String::from("value"));
+///
+/// let result = Experiment::new("Consumed")
+///     .control_once(move || input.into_bytes())
+///     .experiment_once(move || copy.as_bytes().to_vec())
+///     .publish(|o: &Observation<Vec<u8>, Vec<u8>>| assert!(o.is_matching()))
+///     .run();
+///
+/// assert_eq!(result, b"value");
+/// ```
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct OnceExperiment<FC, FE, FP = DefaultPublisher, FM = Equality> {
+    name: &'static str,
+    control: FC,
+    experiment: FE,
+    publish: FP,
+    comparator: FM,
+}
+
+impl<FC, FE, FP, FM> OnceExperiment<FC, FE, FP, FM> {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Compare the values using the custom comparator instead of `PartialEq`.
+    pub fn comparator<C>(self, c: C) -> OnceExperiment<FC, FE, FP, C> {
+        OnceExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            comparator: c,
+        }
+    }
+
+    pub fn publish<TC, TE, F>(self, f: F) -> OnceExperiment<FC, FE, F, FM>
+    where
+        FC: FnOnce() -> TC,
+        FE: FnOnce() -> TE,
+        F: Fn(&Observation<TC, TE>),
+    {
+        OnceExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: f,
+            comparator: self.comparator,
+        }
+    }
+
+    /// Run both of the branches once and publish the observation, returning the control value.
+    ///
+    /// # Panics
+    /// Panics if the **control** function panics, once the observation is published.
+    pub fn run<TC, TE>(self) -> TC
+    where
+        FC: FnOnce() -> TC,
+        FE: FnOnce() -> TE,
+        FM: Comparator<TC, TE>,
+        FP: Publisher<TC, TE>,
+    {
+        if cfg!(scientisto_disabled) {
+            return (self.control)();
+        }
+
+        let control = catch_unwind(AssertUnwindSafe(self.control));
+        let experiment = catch_unwind(AssertUnwindSafe(self.experiment));
+        let observation =
+            Observation::compared_by(self.name, control, experiment, &self.comparator);
+
+        self.publish.publish(&observation);
+
+        match observation.control {
+            Ok(value) => value,
+            Err(e) => resume_unwind(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Experiment, Observation};
+
+    #[test]
+    fn once_experiment_should_consume_the_captured_values() {
+        let (control, experiment) = (vec![1, 2], vec![2, 1]);
+
+        let actual = Experiment::new("Test")
+            .control_once(move || control)
+            .experiment_once(move || experiment)
+            .comparator(|c: &Vec<i32>, e: &Vec<i32>| c.len() == e.len())
+            .publish(|o: &Observation<Vec<i32>, Vec<i32>>| assert!(o.is_matching()))
+            .run();
+
+        assert_eq!(actual, vec![1, 2]);
+    }
+
+    #[test]
+    fn once_experiment_should_record_the_experiment_panic() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+
+        let actual = Experiment::new("Test")
+            .control_once(|| 1)
+            .experiment_once(|| -> i32 { panic!("Oops") })
+            .publish(|o: &Observation<i32, i32>| assert!(!o.experiment_is_ok()))
+            .run();
+
+        assert_eq!(actual, 1);
+    }
+
+    #[test]
+    fn once_experiment_should_compare_the_precomputed_results() {
+        let actual = Experiment::new("Test")
+            .control_result(Ok(vec![1, 2]))
+            .experiment_result(Ok(vec![1, 2]))
+            .publish(|o: &Observation<Vec<i32>, Vec<i32>>| assert!(o.is_matching()))
+            .run();
+
+        assert_eq!(actual, vec![1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "failed")]
+    fn once_experiment_should_resume_the_precomputed_control_error() {
+        Experiment::new("Test")
+            .control_result(Err(Box::new("failed")))
+            .experiment_result(Ok(1))
+            .publish(|o: &Observation<i32, i32>| assert!(!o.control_is_ok()))
+            .run();
+    }
+
+    #[test]
+    #[cfg(scientisto_disabled)]
+    fn disabled_once_experiment_should_only_run_the_control() {
+        let actual = Experiment::new("Test")
+            .control_once(|| 1)
+            .experiment_once(|| -> i32 { panic!("experiment must not run") })
+            .publish(|_: &Observation<i32, i32>| panic!("nothing must be published"))
+            .run();
+
+        assert_eq!(actual, 1);
+    }
+}
@@ -0,0 +1,129 @@
+//! Symmetric comparison of two implementations, neither of which is authoritative.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::compare::{Comparator, Equality};
+use crate::Observation;
+
+/// Pair
+///
+/// Two implementations compared without the "control wins" semantics, see `Experiment::pair`.
+/// Instead of returning one of the values, `compare` returns the observation itself, e.g. for an
+/// assertion in a test harness. The first implementation is recorded as the control, the second
+/// one as the experiment, a panic in either of them is recorded in the observation.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::Experiment;
+///
+/// let observation = Experiment::new("Parsers agree")
+///     .pair(|| "42".parse::<i32>().ok(), || Some(42))
+///     .compare();
+///
+/// assert!(observation.is_matching());
+/// ```
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct Pair<FA, FB, FM = Equality> {
+    name: &'static str,
+    a: FA,
+    b: FB,
+    comparator: FM,
+}
+
+impl<FA, FB> Pair<FA, FB> {
+    pub(crate) fn new(name: &'static str, a: FA, b: FB) -> Self {
+        Self {
+            name,
+            a,
+            b,
+            comparator: Equality,
+        }
+    }
+}
+
+impl<FA, FB, FM> Pair<FA, FB, FM> {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Compare the values using a custom comparator instead of `PartialEq`.
+    pub fn comparator<C>(self, c: C) -> Pair<FA, FB, C> {
+        Pair {
+            name: self.name,
+            a: self.a,
+            b: self.b,
+            comparator: c,
+        }
+    }
+
+    /// Run both of the implementations and return the observation of their comparison.
+    ///
+    /// Never panics, the panics of the implementations are recorded in the observation. The
+    /// disabled build only runs the first implementation, whose panic is not caught, and returns
+    /// the observation skipped as `SkipReason::Disabled`.
+    pub fn compare<TA, TB>(&self) -> Observation<TA, TB>
+    where
+        FA: Fn() -> TA,
+        FB: Fn() -> TB,
+        FM: Comparator<TA, TB>,
+    {
+        if cfg!(scientisto_disabled) {
+            return Observation::skipped(self.name, Ok((self.a)()), crate::SkipReason::Disabled);
+        }
+
+        let a = catch_unwind(AssertUnwindSafe(&self.a));
+        let b = catch_unwind(AssertUnwindSafe(&self.b));
+
+        Observation::compared_by(self.name, a, b, &self.comparator)
+    }
+
+    /// Whether both of the implementations return matching values.
+    pub fn agree<TA, TB>(&self) -> bool
+    where
+        FA: Fn() -> TA,
+        FB: Fn() -> TB,
+        FM: Comparator<TA, TB>,
+    {
+        self.compare().is_matching()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Experiment;
+
+    #[test]
+    fn pair_should_record_the_panic_of_either_implementation() {
+        let observation = Experiment::new("Test")
+            .pair(|| 1, || -> i32 { panic!("Oops") })
+            .compare();
+
+        assert!(!observation.is_matching());
+        assert!(observation.control_is_ok());
+        assert!(!observation.experiment_is_ok());
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn pair_should_agree_by_the_custom_comparator() {
+        let pair = Experiment::new("Test")
+            .pair(|| 1.0_f64, || 1.05_f64)
+            .comparator(crate::compare::approx(0.1));
+
+        assert!(pair.agree());
+    }
+
+    #[test]
+    #[cfg(scientisto_disabled)]
+    fn disabled_pair_should_only_run_the_first_implementation() {
+        let observation = Experiment::new("Test")
+            .pair(
+                || 1,
+                || -> i32 { panic!("second implementation must not run") },
+            )
+            .compare();
+
+        assert_eq!(observation.control().ok(), Some(&1));
+        assert_eq!(observation.skip_reason(), Some(crate::SkipReason::Disabled));
+    }
+}
@@ -0,0 +1,127 @@
+use std::cell::RefCell;
+// `PanicInfo` is deprecated in favour of `PanicHookInfo` since 1.82, newer than the toolchain
+#[allow(deprecated)]
+use std::panic::{self, PanicInfo};
+use std::sync::{Arc, Mutex, PoisonError};
+
+#[allow(deprecated)]
+type Hook = Box<dyn Fn(&PanicInfo<'_>) + Sync + Send + 'static>;
+
+thread_local! {
+    /// Location of the last panic on the current thread, recorded by the scoped hook.
+    static LOCATION: RefCell<Option<(String, u32)>> = const { RefCell::new(None) };
+}
+
+/// Hook replaced by the recording hook and the address of the recording hook, identifying it once
+/// the captures are finished.
+type Installed = (Arc<Hook>, usize);
+
+/// Number of the ongoing captures and the installed recording hook.
+static SCOPE: Mutex<(usize, Option<Installed>)> = Mutex::new((0, None));
+
+/// Execute the function catching the panics, capturing the file and line of the panic.
+///
+/// The panic hook is replaced for the duration of the call by a hook recording the location and
+/// then calling the previous hook, which is restored afterward. Since the panic hook is global to
+/// the process, overlapping captures on different threads share the recording hook and the
+/// previous hook is restored once the last of them is finished.
+pub(crate) fn capture<R>(
+    f: impl FnOnce() -> std::thread::Result<R>,
+) -> (std::thread::Result<R>, Option<(String, u32)>) {
+    LOCATION.with(|location| location.borrow_mut().take());
+
+    enter();
+    let result = f();
+    exit();
+
+    let location = LOCATION.with(|location| location.borrow_mut().take());
+    match result {
+        Ok(value) => (Ok(value), None),
+        Err(e) => (Err(e), location),
+    }
+}
+
+fn enter() {
+    let mut scope = SCOPE.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if scope.0 == 0 {
+        let previous = Arc::new(panic::take_hook());
+        let chained = previous.clone();
+        let recording: Hook = Box::new(move |info| {
+            if let Some(location) = info.location() {
+                let recorded = (location.file().to_owned(), location.line());
+                LOCATION.with(|location| *location.borrow_mut() = Some(recorded));
+            }
+            chained(info);
+        });
+        let address = address(&recording);
+        panic::set_hook(recording);
+        scope.1 = Some((previous, address));
+    }
+    scope.0 += 1;
+}
+
+fn exit() {
+    let mut scope = SCOPE.lock().unwrap_or_else(PoisonError::into_inner);
+
+    scope.0 -= 1;
+    if scope.0 == 0 {
+        let Some((previous, recording)) = scope.1.take() else {
+            return;
+        };
+
+        let current = panic::take_hook();
+        if address(&current) != recording {
+            // replaced during the captures, e.g. by the user, so it is left alone
+            panic::set_hook(current);
+            return;
+        }
+
+        drop(current);
+        match Arc::try_unwrap(previous) {
+            Ok(previous) => panic::set_hook(previous),
+            Err(previous) => panic::set_hook(Box::new(move |info| previous(info))),
+        }
+    }
+}
+
+/// Address of the boxed hook, telling the recording hook apart from the others.
+fn address(hook: &Hook) -> usize {
+    &**hook as *const _ as *const () as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_should_record_the_location_of_the_panic() {
+        let line = line!() + 1;
+        let (result, location) = capture(|| panic::catch_unwind(|| -> i32 { panic!("Oops") }));
+
+        assert!(result.is_err());
+        assert_eq!(location, Some((String::from(file!()), line)));
+    }
+
+    #[test]
+    fn capture_should_not_record_any_location_without_a_panic() {
+        let (result, location) = capture(|| panic::catch_unwind(|| 1));
+
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(location, None);
+    }
+
+    #[test]
+    fn capture_should_leave_alone_a_hook_replaced_during_the_capture() {
+        let mut user = 0;
+        let _ = capture(|| {
+            let hook: Hook = Box::new(|_| {});
+            user = address(&hook);
+            panic::set_hook(hook);
+            Ok(())
+        });
+
+        let current = panic::take_hook();
+        assert_eq!(address(&current), user);
+    }
+}
@@ -0,0 +1,171 @@
+//! Experiments comparing the **control** against its own previous result, detecting drift.
+
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::sync::{Mutex, PoisonError};
+
+use crate::publisher::{DefaultPublisher, Publisher};
+use crate::Observation;
+
+/// Previous-run experiment
+///
+/// Experiment comparing the current **control** value against the value of the previous run,
+/// instead of an experiment, to detect nondeterminism. The last value is cached behind a lock, so
+/// the experiment can be shared between threads. The observation carries the current value as the
+/// control and the previous value as the experiment, the first run has nothing to compare and
+/// publishes nothing.
+///
+/// # Examples
+/// ```rust
+/// use std::sync::atomic::{AtomicI32, Ordering};
+/// use scientisto::{Experiment,Observation};
+///
+/// let counter = AtomicI32::new(0);
+/// let experiment = Experiment::new("Drift")
+///     .control(|| counter.fetch_add(1, Ordering::SeqCst) / 2)
+///     .compare_with_previous()
+///     .publish(|o: &Observation<i32, i32>| println!("drifted: {}", !o.is_matching()));
+///
+/// assert_eq!(experiment.run(), 0);
+/// assert_eq!(experiment.run(), 0);
+/// assert_eq!(experiment.run(), 1);
+/// ```
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct PreviousExperiment<TC, FC, FP = DefaultPublisher>
+where
+    FC: Fn() -> TC,
+{
+    name: &'static str,
+    control: FC,
+    publish: FP,
+    previous: Mutex<Option<TC>>,
+}
+
+impl<TC, FC, FP> PreviousExperiment<TC, FC, FP>
+where
+    FC: Fn() -> TC,
+{
+    pub(crate) fn new(name: &'static str, control: FC, publish: FP) -> Self {
+        Self {
+            name,
+            control,
+            publish,
+            previous: Mutex::new(None),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn publish<F>(self, f: F) -> PreviousExperiment<TC, FC, F>
+    where
+        F: Fn(&Observation<TC, TC>),
+    {
+        PreviousExperiment {
+            name: self.name,
+            control: self.control,
+            publish: f,
+            previous: self.previous,
+        }
+    }
+
+    /// Run the control and compare its value against the cached value of the previous run.
+    ///
+    /// The previous value is replaced by the current one, unless the control panicked. The
+    /// disabled build only runs the control, leaving the previous value alone.
+    ///
+    /// # Panics
+    /// Panics if the **control** function panics, once the observation is published.
+    pub fn run(&self) -> TC
+    where
+        TC: Clone + PartialEq,
+        FP: Publisher<TC, TC>,
+    {
+        if cfg!(scientisto_disabled) {
+            return (self.control)();
+        }
+
+        let control = catch_unwind(AssertUnwindSafe(&self.control));
+
+        // the cached value stays consistent even if a holder of the lock panicked
+        let mut previous = self.previous.lock().unwrap_or_else(PoisonError::into_inner);
+        let last = match &control {
+            Ok(value) => previous.replace(value.clone()),
+            Err(_) => previous.clone(),
+        };
+        drop(previous);
+
+        let control = match last {
+            Some(last) => {
+                let observation = Observation::new(self.name, control, Ok(last));
+                self.publish.publish(&observation);
+                observation.control
+            }
+            None => control,
+        };
+
+        match control {
+            Ok(value) => value,
+            Err(e) => resume_unwind(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Experiment, Observation};
+    use std::cell::Cell;
+    #[cfg(not(scientisto_disabled))]
+    use std::cell::RefCell;
+
+    #[test]
+    fn previous_experiment_should_publish_nothing_on_the_first_run() {
+        let published = Cell::new(0);
+
+        let experiment = Experiment::new("Test")
+            .control(|| 1)
+            .compare_with_previous()
+            .publish(|_: &Observation<i32, i32>| published.set(published.get() + 1));
+
+        assert_eq!(experiment.run(), 1);
+        assert_eq!(published.get(), 0);
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn previous_experiment_should_report_consecutive_runs_differing() {
+        let values = std::sync::Mutex::new(vec![2, 1, 1]);
+        let published = RefCell::new(Vec::new());
+
+        let experiment = Experiment::new("Test")
+            .control(|| values.lock().unwrap().pop().unwrap())
+            .compare_with_previous()
+            .publish(|o: &Observation<i32, i32>| {
+                published.borrow_mut().push((
+                    *o.control().unwrap(),
+                    *o.experiment().unwrap(),
+                    o.is_matching(),
+                ))
+            });
+
+        experiment.run();
+        experiment.run();
+        experiment.run();
+
+        assert_eq!(published.into_inner(), vec![(1, 1, true), (2, 1, false)]);
+    }
+
+    #[test]
+    #[cfg(scientisto_disabled)]
+    fn disabled_previous_experiment_should_only_run_the_control() {
+        let published = Cell::new(0);
+
+        let experiment = Experiment::new("Test")
+            .control(|| 1)
+            .compare_with_previous()
+            .publish(|_: &Observation<i32, i32>| published.set(published.get() + 1));
+
+        assert_eq!((experiment.run(), experiment.run()), (1, 1));
+        assert_eq!(published.get(), 0);
+    }
+}
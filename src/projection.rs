@@ -0,0 +1,75 @@
+/// Projection
+///
+/// Projection of the raw control value onto the value recorded in the `Observation`. The raw value
+/// is retained by the projection, so that it can be returned from the experiment run unchanged.
+pub trait Projection<T> {
+    /// Type of the value recorded in the `Observation`.
+    type Output;
+    /// Type of the value retained until the experiment run returns.
+    type Retained;
+
+    /// Split the raw value into the recorded and the retained part.
+    fn split(&self, value: T) -> (Self::Output, Self::Retained);
+
+    /// Restore the raw value from the recorded and the retained part.
+    fn restore(&self, recorded: Self::Output, retained: Self::Retained) -> T;
+}
+
+/// Default projection recording the raw value itself.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Identity;
+
+impl<T> Projection<T> for Identity {
+    type Output = T;
+    type Retained = ();
+
+    fn split(&self, value: T) -> (T, ()) {
+        (value, ())
+    }
+
+    fn restore(&self, recorded: T, _retained: ()) -> T {
+        recorded
+    }
+}
+
+/// Projection recording the mapped value, while retaining the raw value.
+#[derive(Debug, Clone, Copy)]
+pub struct Mapped<M>(pub M);
+
+impl<T, R, M> Projection<T> for Mapped<M>
+where
+    M: Fn(&T) -> R,
+{
+    type Output = R;
+    type Retained = T;
+
+    fn split(&self, value: T) -> (R, T) {
+        ((self.0)(&value), value)
+    }
+
+    fn restore(&self, _recorded: R, retained: T) -> T {
+        retained
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_projection_should_record_and_restore_the_raw_value() {
+        let (recorded, retained) = Identity.split(String::from("raw"));
+
+        assert_eq!(recorded, "raw");
+        assert_eq!(Identity.restore(recorded, retained), "raw");
+    }
+
+    #[test]
+    fn mapped_projection_should_record_the_mapped_value_and_restore_the_raw_value() {
+        let projection = Mapped(|v: &String| v.trim_end().to_owned());
+        let (recorded, retained) = projection.split(String::from("raw  "));
+
+        assert_eq!(recorded, "raw");
+        assert_eq!(projection.restore(recorded, retained), "raw  ");
+    }
+}
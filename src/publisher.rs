@@ -0,0 +1,718 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+
+use crate::ObservationSummary;
+
+/// Publisher
+///
+/// Consumer of the `Observation` collected during each experiment run. Any function or closure
+/// accepting an `&Observation` is a `Publisher`, custom types can implement the trait directly.
+pub trait Publisher<T, TE> {
+    /// Publish the observation of a single experiment run.
+    fn publish(&self, observation: &crate::Observation<T, TE>);
+
+    /// Chain the other publisher, publishing each observation by this one first, see `AndThen`.
+    fn and_then<P>(self, other: P) -> AndThen<Self, P>
+    where
+        Self: Sized,
+        P: Publisher<T, TE>,
+    {
+        AndThen {
+            first: self,
+            second: other,
+        }
+    }
+
+    /// Publish only the observations satisfying the predicate, see `Filtered`.
+    fn filtered<F>(self, predicate: F) -> Filtered<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&crate::Observation<T, TE>) -> bool,
+    {
+        Filtered {
+            inner: self,
+            predicate,
+        }
+    }
+}
+
+impl<T, TE, F> Publisher<T, TE> for F
+where
+    F: Fn(&crate::Observation<T, TE>),
+{
+    fn publish(&self, observation: &crate::Observation<T, TE>) {
+        self(observation)
+    }
+}
+
+/// Publisher ignoring all of the observations.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopPublisher;
+
+impl<T, TE> Publisher<T, TE> for NoopPublisher {
+    fn publish(&self, _observation: &crate::Observation<T, TE>) {}
+}
+
+/// Publisher publishing each observation by two publishers in turn, see `Publisher::and_then`.
+///
+/// # Examples
+/// ```rust
+/// use std::cell::Cell;
+/// use scientisto::{Experiment,Observation,Publisher};
+///
+/// let (metrics, logs) = (Cell::new(0), Cell::new(0));
+/// let count = |o: &Observation<i32, i32>| metrics.set(metrics.get() + 1);
+/// let log = |o: &Observation<i32, i32>| logs.set(logs.get() + 1);
+/// let publisher = count.and_then(log).filtered(|o| !o.is_matching());
+///
+/// Experiment::new("Composed")
+///     .control(|| 1)
+///     .experiment(|| 2)
+///     .publish(|o: &Observation<i32, i32>| publisher.publish(o))
+///     .run();
+///
+/// assert_eq!((metrics.get(), logs.get()), (1, 1));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AndThen<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<T, TE, A, B> Publisher<T, TE> for AndThen<A, B>
+where
+    A: Publisher<T, TE>,
+    B: Publisher<T, TE>,
+{
+    fn publish(&self, observation: &crate::Observation<T, TE>) {
+        self.first.publish(observation);
+        self.second.publish(observation);
+    }
+}
+
+/// Publisher forwarding only the observations satisfying the predicate, see
+/// `Publisher::filtered`.
+#[derive(Debug, Clone, Copy)]
+pub struct Filtered<P, F> {
+    inner: P,
+    predicate: F,
+}
+
+impl<T, TE, P, F> Publisher<T, TE> for Filtered<P, F>
+where
+    P: Publisher<T, TE>,
+    F: Fn(&crate::Observation<T, TE>) -> bool,
+{
+    fn publish(&self, observation: &crate::Observation<T, TE>) {
+        if (self.predicate)(observation) {
+            self.inner.publish(observation);
+        }
+    }
+}
+
+/// Publisher routing each observation to one of the registered publishers, see `publish_router`.
+///
+/// The route decides the key of the publisher for each observation from the runtime data, e.g. the
+/// tier of the tenant, so that only the selected publisher runs. The observation is dropped when
+/// the route returns `None` or a key with no registered publisher.
+///
+/// # Examples
+/// ```rust
+/// use std::cell::Cell;
+/// use scientisto::publisher::PublishRouter;
+/// use scientisto::{Experiment,Observation};
+///
+/// let (premium, standard) = (Cell::new(0), Cell::new(0));
+/// let router = PublishRouter::new(|o: &Observation<i32, i32>| Some(*o.control().unwrap() > 100))
+///     .publisher(true, |_: &Observation<i32, i32>| premium.set(premium.get() + 1))
+///     .publisher(false, |_: &Observation<i32, i32>| standard.set(standard.get() + 1));
+///
+/// Experiment::new("Routed")
+///     .control(|| 1_000)
+///     .experiment(|| 1_000)
+///     .publish_router(router)
+///     .run();
+///
+/// assert_eq!((premium.get(), standard.get()), (1, 0));
+/// ```
+pub struct PublishRouter<'a, T, TE, K, R> {
+    route: R,
+    publishers: HashMap<K, Box<dyn Publisher<T, TE> + 'a>>,
+}
+
+impl<'a, T, TE, K, R> PublishRouter<'a, T, TE, K, R>
+where
+    K: Eq + Hash,
+    R: Fn(&crate::Observation<T, TE>) -> Option<K>,
+{
+    pub fn new(route: R) -> Self {
+        Self {
+            route,
+            publishers: HashMap::new(),
+        }
+    }
+
+    /// Register the publisher of the observations routed to the key, replacing the previous one.
+    pub fn publisher<P>(mut self, key: K, publisher: P) -> Self
+    where
+        P: Publisher<T, TE> + 'a,
+    {
+        self.publishers.insert(key, Box::new(publisher));
+        self
+    }
+}
+
+impl<T, TE, K, R> Publisher<T, TE> for PublishRouter<'_, T, TE, K, R>
+where
+    K: Eq + Hash,
+    R: Fn(&crate::Observation<T, TE>) -> Option<K>,
+{
+    fn publish(&self, observation: &crate::Observation<T, TE>) {
+        if let Some(publisher) = (self.route)(observation).and_then(|key| self.publishers.get(&key))
+        {
+            publisher.publish(observation);
+        }
+    }
+}
+
+/// Publisher of the summaries shared by the whole application.
+type GlobalPublisher = Box<dyn Fn(&ObservationSummary) + Send + Sync>;
+
+static DEFAULT_PUBLISHER: OnceLock<GlobalPublisher> = OnceLock::new();
+
+/// Error returned when the default publisher has already been set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefaultPublisherAlreadySet;
+
+impl std::fmt::Display for DefaultPublisherAlreadySet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the default publisher can only be set once")
+    }
+}
+
+impl std::error::Error for DefaultPublisherAlreadySet {}
+
+/// Set the publisher of the experiments which do not specify their own publisher.
+///
+/// The default publisher receives the `ObservationSummary` of each observation, since it is shared
+/// by experiments of all types. It can be set only once, typically in `main`, every later attempt
+/// returns an error. Setting and publishing is thread-safe, the publisher itself needs to be `Send`
+/// and `Sync`, since it is called from any thread running an experiment. Until it is set, the
+/// observations of such experiments are discarded.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::Experiment;
+///
+/// scientisto::set_default_publisher(|summary| {
+///     println!("{}: matching {}", summary.name, summary.matching)
+/// })
+/// .unwrap();
+///
+/// Experiment::new("Terse").control(|| 1).experiment(|| 1).run();
+///
+/// assert!(scientisto::set_default_publisher(|_| {}).is_err());
+/// ```
+pub fn set_default_publisher<F>(f: F) -> Result<(), DefaultPublisherAlreadySet>
+where
+    F: Fn(&ObservationSummary) + Send + Sync + 'static,
+{
+    DEFAULT_PUBLISHER
+        .set(Box::new(f))
+        .map_err(|_| DefaultPublisherAlreadySet)
+}
+
+/// Publisher used until a custom publisher is specified, forwarding to the default publisher.
+///
+/// See `set_default_publisher`, without the default publisher the observations are discarded.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultPublisher;
+
+impl<T, TE> Publisher<T, TE> for DefaultPublisher {
+    fn publish(&self, observation: &crate::Observation<T, TE>) {
+        if let Some(publisher) = DEFAULT_PUBLISHER.get() {
+            publisher(&observation.summary());
+        }
+    }
+}
+
+/// Publisher keeping the summaries of the most recent mismatched observations in memory.
+///
+/// Once the capacity is reached, the oldest summary is discarded. The buffer is protected by a
+/// lock, so the publisher can be shared between threads, e.g. to expose the recent mismatches on
+/// a debug endpoint.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::publisher::RingBufferPublisher;
+/// use scientisto::{Experiment,Publisher};
+///
+/// let publisher = RingBufferPublisher::new(10);
+///
+/// Experiment::new("Test")
+///     .control(|| 1)
+///     .experiment(|| 2)
+///     .publish(|o: &scientisto::Observation<i32, i32>| publisher.publish(o))
+///     .run();
+///
+/// assert_eq!(publisher.recent().len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct RingBufferPublisher {
+    capacity: usize,
+    buffer: Mutex<VecDeque<ObservationSummary>>,
+}
+
+impl RingBufferPublisher {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            buffer: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Summaries of the recent mismatches, from the oldest to the newest.
+    pub fn recent(&self) -> Vec<ObservationSummary> {
+        self.lock().iter().cloned().collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<ObservationSummary>> {
+        // the buffer stays consistent even if a holder of the lock panicked
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T, TE> Publisher<T, TE> for RingBufferPublisher {
+    fn publish(&self, observation: &crate::Observation<T, TE>) {
+        if observation.is_matching() || self.capacity == 0 {
+            return;
+        }
+
+        let mut buffer = self.lock();
+        if buffer.len() == self.capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(observation.summary());
+    }
+}
+
+/// Publisher appending a row per observation to a CSV or TSV file, e.g. for an offline analysis.
+///
+/// The columns are the name, the verdict, the durations in milliseconds and the sizes, with the
+/// unrecorded ones left empty. The header is written once, when the file is empty. Each row is
+/// written and flushed under a lock, so the publisher can be shared between threads. Publishing
+/// cannot fail, the rows which could not be written are counted by `failed_rows`.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::publisher::FilePublisher;
+/// use scientisto::{Experiment,Publisher};
+///
+/// let path = std::env::temp_dir().join("scientisto-doc-example.csv");
+/// # let _ = std::fs::remove_file(&path);
+/// let publisher = FilePublisher::new(&path).unwrap();
+///
+/// Experiment::new("Test")
+///     .control(|| 1)
+///     .experiment(|| 2)
+///     .publish(|o: &scientisto::Observation<i32, i32>| publisher.publish(o))
+///     .run();
+///
+/// let rows = std::fs::read_to_string(&path).unwrap();
+/// assert_eq!(rows.lines().count(), 2);
+/// ```
+#[cfg(feature = "file")]
+#[derive(Debug)]
+pub struct FilePublisher {
+    delimiter: char,
+    file: Mutex<std::fs::File>,
+    failed_rows: std::sync::atomic::AtomicU64,
+}
+
+#[cfg(feature = "file")]
+impl FilePublisher {
+    const COLUMNS: [&'static str; 6] = [
+        "name",
+        "matching",
+        "control_ms",
+        "experiment_ms",
+        "control_size",
+        "experiment_size",
+    ];
+
+    /// Append the observations to the CSV file, creating it if missing.
+    pub fn new(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Self::with_delimiter(path, ',')
+    }
+
+    /// Append the observations to the TSV file, creating it if missing.
+    pub fn tsv(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Self::with_delimiter(path, '\t')
+    }
+
+    fn with_delimiter(path: impl AsRef<std::path::Path>, delimiter: char) -> std::io::Result<Self> {
+        use std::io::Write;
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+
+        if file.metadata()?.len() == 0 {
+            let header = Self::COLUMNS.join(&delimiter.to_string());
+            writeln!(file, "{}", header)?;
+            file.flush()?;
+        }
+
+        Ok(Self {
+            delimiter,
+            file: Mutex::new(file),
+            failed_rows: std::sync::atomic::AtomicU64::new(0),
+        })
+    }
+
+    /// Number of the rows which could not be written.
+    pub fn failed_rows(&self) -> u64 {
+        self.failed_rows.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    fn row<T, TE>(&self, observation: &crate::Observation<T, TE>) -> String {
+        let millis = |duration: Option<std::time::Duration>| {
+            duration.map_or(String::new(), |d| {
+                format!("{:.3}", d.as_secs_f64() * 1000.0)
+            })
+        };
+        let size = |size: Option<usize>| size.map_or(String::new(), |s| s.to_string());
+
+        [
+            self.escape(observation.name()),
+            observation.is_matching().to_string(),
+            millis(observation.control_duration()),
+            millis(observation.experiment_duration()),
+            size(observation.control_size()),
+            size(observation.experiment_size()),
+        ]
+        .join(&self.delimiter.to_string())
+    }
+
+    /// Quote the field containing the delimiter, a quote or a line break.
+    fn escape(&self, field: &str) -> String {
+        if field.contains([self.delimiter, '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    }
+}
+
+#[cfg(feature = "file")]
+impl<T, TE> Publisher<T, TE> for FilePublisher {
+    fn publish(&self, observation: &crate::Observation<T, TE>) {
+        use std::io::Write;
+
+        let row = self.row(observation);
+        // the file stays usable even if a holder of the lock panicked
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        if writeln!(file, "{}", row)
+            .and_then(|_| file.flush())
+            .is_err()
+        {
+            self.failed_rows
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+}
+
+/// Publisher accumulating the durations of the branches into HDR histograms, with the
+/// `hdrhistogram` feature.
+///
+/// Gives the latency distributions of the control and the experiment without an external metrics
+/// system. The durations are recorded in nanoseconds with 3 significant digits, the observations
+/// without the durations, i.e. not run by `run_timed`, are skipped.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::publisher::HdrPublisher;
+/// use scientisto::{Experiment,Publisher};
+///
+/// let publisher = HdrPublisher::new();
+/// let experiment = Experiment::new("Latency")
+///     .control(|| 1)
+///     .experiment(|| 1)
+///     .publish(|o: &scientisto::Observation<i32, i32>| publisher.publish(o));
+///
+/// for _ in 0..100 {
+///     experiment.run_timed();
+/// }
+///
+/// let (control, experiment) = publisher.p99();
+/// println!("p99 of the control {:?}, of the experiment {:?}", control, experiment);
+/// assert_eq!(publisher.len(), 100);
+/// ```
+#[cfg(feature = "hdrhistogram")]
+#[derive(Debug)]
+pub struct HdrPublisher {
+    histograms: Mutex<(hdrhistogram::Histogram<u64>, hdrhistogram::Histogram<u64>)>,
+}
+
+#[cfg(feature = "hdrhistogram")]
+impl HdrPublisher {
+    pub fn new() -> Self {
+        let histogram = || hdrhistogram::Histogram::new(3).expect("3 significant digits are valid");
+
+        Self {
+            histograms: Mutex::new((histogram(), histogram())),
+        }
+    }
+
+    /// Number of the recorded observations.
+    pub fn len(&self) -> u64 {
+        self.lock().0.len()
+    }
+
+    /// Whether no observation was recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Durations of the control and the experiment at the quantile, e.g. `0.99`.
+    ///
+    /// `None` if no observation was recorded yet.
+    pub fn quantile(
+        &self,
+        quantile: f64,
+    ) -> (Option<std::time::Duration>, Option<std::time::Duration>) {
+        let histograms = self.lock();
+        let at = |histogram: &hdrhistogram::Histogram<u64>| {
+            (!histogram.is_empty())
+                .then(|| std::time::Duration::from_nanos(histogram.value_at_quantile(quantile)))
+        };
+
+        (at(&histograms.0), at(&histograms.1))
+    }
+
+    /// Median durations of the control and the experiment, see `quantile`.
+    pub fn p50(&self) -> (Option<std::time::Duration>, Option<std::time::Duration>) {
+        self.quantile(0.5)
+    }
+
+    /// 99th percentile durations of the control and the experiment, see `quantile`.
+    pub fn p99(&self) -> (Option<std::time::Duration>, Option<std::time::Duration>) {
+        self.quantile(0.99)
+    }
+
+    /// Snapshot of the histograms of the control and the experiment durations in nanoseconds.
+    pub fn histograms(&self) -> (hdrhistogram::Histogram<u64>, hdrhistogram::Histogram<u64>) {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, (hdrhistogram::Histogram<u64>, hdrhistogram::Histogram<u64>)> {
+        // the histograms stay consistent even if a holder of the lock panicked
+        self.histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(feature = "hdrhistogram")]
+impl Default for HdrPublisher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "hdrhistogram")]
+impl<T, TE> Publisher<T, TE> for HdrPublisher {
+    fn publish(&self, observation: &crate::Observation<T, TE>) {
+        let (Some(control), Some(experiment)) = (
+            observation.control_duration(),
+            observation.experiment_duration(),
+        ) else {
+            return;
+        };
+
+        let nanos =
+            |duration: std::time::Duration| u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        let mut histograms = self.lock();
+        // the histograms resize automatically, recording fails only beyond centuries of duration
+        let _ = histograms.0.record(nanos(control));
+        let _ = histograms.1.record(nanos(experiment));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn closure_should_be_usable_as_a_publisher() {
+        let calls = Cell::new(0);
+        let publisher = |_: &crate::Observation<i32, i32>| calls.set(calls.get() + 1);
+
+        publisher.publish(&crate::Observation::new("Test", Ok(1), Ok(1)));
+
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn noop_publisher_should_accept_any_observation() {
+        NoopPublisher.publish(&crate::Observation::<i32, i32>::new("Test", Ok(1), Ok(2)));
+    }
+
+    #[test]
+    fn and_then_should_publish_by_both_of_the_publishers_in_order() {
+        let calls = std::cell::RefCell::new(Vec::new());
+        let first = |_: &crate::Observation<i32, i32>| calls.borrow_mut().push("first");
+        let second = |_: &crate::Observation<i32, i32>| calls.borrow_mut().push("second");
+
+        first
+            .and_then(second)
+            .publish(&crate::Observation::new("Test", Ok(1), Ok(1)));
+
+        assert_eq!(calls.into_inner(), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn filtered_should_publish_only_the_observations_satisfying_the_predicate() {
+        let calls = Cell::new(0);
+        let publisher = (|_: &crate::Observation<i32, i32>| calls.set(calls.get() + 1))
+            .filtered(|o| !o.is_matching());
+
+        publisher.publish(&crate::Observation::new("Test", Ok(1), Ok(1)));
+        publisher.publish(&crate::Observation::new("Test", Ok(1), Ok(2)));
+
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn publish_router_should_drop_the_observations_without_a_route() {
+        let calls = Cell::new(0);
+        let router = PublishRouter::new(|o: &crate::Observation<i32, i32>| {
+            o.control().ok().filter(|&&c| c > 0).copied()
+        })
+        .publisher(1, |_: &crate::Observation<i32, i32>| {
+            calls.set(calls.get() + 1)
+        });
+
+        router.publish(&crate::Observation::new("Test", Ok(1), Ok(1)));
+        router.publish(&crate::Observation::new("Test", Ok(2), Ok(2)));
+        router.publish(&crate::Observation::new("Test", Ok(0), Ok(0)));
+
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn ring_buffer_publisher_should_keep_only_the_most_recent_mismatches() {
+        let publisher = RingBufferPublisher::new(2);
+
+        for experiment in 1..=4 {
+            publisher.publish(&crate::Observation::<i32, i32>::new(
+                "Test",
+                Ok(1),
+                Ok(experiment),
+            ));
+        }
+
+        let recent = publisher.recent();
+        assert_eq!(recent.len(), 2);
+        assert!(recent.iter().all(|summary| !summary.matching));
+    }
+
+    #[test]
+    fn ring_buffer_publisher_should_ignore_matching_observations() {
+        let publisher = RingBufferPublisher::new(2);
+
+        publisher.publish(&crate::Observation::<i32, i32>::new("Test", Ok(1), Ok(1)));
+
+        assert!(publisher.recent().is_empty());
+    }
+
+    #[test]
+    fn ring_buffer_publisher_with_zero_capacity_should_keep_nothing() {
+        let publisher = RingBufferPublisher::new(0);
+
+        publisher.publish(&crate::Observation::<i32, i32>::new("Test", Ok(1), Ok(2)));
+
+        assert!(publisher.recent().is_empty());
+    }
+
+    #[test]
+    fn default_publisher_should_forward_to_the_default_publisher_set_only_once() {
+        static PUBLISHED: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+        let set = set_default_publisher(|summary| {
+            if summary.name == "Default publisher" {
+                PUBLISHED.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            }
+        });
+
+        assert_eq!(set, Ok(()));
+        assert_eq!(
+            set_default_publisher(|_| {}),
+            Err(DefaultPublisherAlreadySet)
+        );
+
+        DefaultPublisher.publish(&crate::Observation::<i32, i32>::new(
+            "Default publisher",
+            Ok(1),
+            Ok(1),
+        ));
+        assert_eq!(PUBLISHED.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "file")]
+    #[test]
+    fn file_publisher_should_write_the_header_once_and_a_row_per_observation() {
+        let path = std::env::temp_dir().join(format!(
+            "scientisto-file-publisher-{}.csv",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        for _ in 0..2 {
+            let publisher = FilePublisher::new(&path).unwrap();
+            publisher.publish(&crate::Observation::<i32, i32>::new(
+                "Test, quoted",
+                Ok(1),
+                Ok(1),
+            ));
+        }
+
+        let rows = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            rows,
+            "name,matching,control_ms,experiment_ms,control_size,experiment_size\n\
+             \"Test, quoted\",true,,,,\n\
+             \"Test, quoted\",true,,,,\n"
+        );
+    }
+
+    #[cfg(feature = "hdrhistogram")]
+    #[test]
+    fn hdr_publisher_should_record_the_durations_of_the_timed_observations_only() {
+        let publisher = HdrPublisher::new();
+
+        for millis in 1..=100 {
+            let observation = crate::Observation::<i32, i32>::new("Test", Ok(1), Ok(1)).timed(
+                std::time::Duration::from_millis(millis),
+                std::time::Duration::from_millis(2 * millis),
+            );
+            publisher.publish(&observation);
+        }
+        publisher.publish(&crate::Observation::<i32, i32>::new("Test", Ok(1), Ok(1)));
+
+        let (control, experiment) = publisher.p50();
+        assert_eq!(publisher.len(), 100);
+        assert_eq!(control.map(|d| d.as_millis()), Some(50));
+        assert_eq!(experiment.map(|d| d.as_millis()), Some(100));
+    }
+
+    #[cfg(feature = "hdrhistogram")]
+    #[test]
+    fn hdr_publisher_should_have_no_quantiles_until_recorded() {
+        assert_eq!(HdrPublisher::new().p99(), (None, None));
+    }
+}
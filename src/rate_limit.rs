@@ -0,0 +1,82 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
+
+/// Token bucket limiting the number of publications per second.
+///
+/// The bucket holds at most one second worth of tokens, so that bursts up to the rate are allowed.
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    rate: f64,
+    state: Mutex<(f64, Instant)>,
+    dropped: AtomicU64,
+}
+
+impl TokenBucket {
+    pub fn new(max_per_sec: f64) -> Self {
+        let rate = max_per_sec.max(0.0);
+
+        Self {
+            rate,
+            state: Mutex::new((rate, Instant::now())),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Take a token if available, counting the denied acquisitions as dropped.
+    pub fn acquire(&self) -> bool {
+        self.acquire_at(Instant::now())
+    }
+
+    fn acquire_at(&self, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let (tokens, last) = &mut *state;
+
+        let elapsed = now.saturating_duration_since(*last).as_secs_f64();
+        *tokens = (*tokens + elapsed * self.rate).min(self.rate);
+        *last = now;
+
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            false
+        }
+    }
+
+    /// Number of the acquisitions denied so far.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn token_bucket_should_allow_a_burst_up_to_the_rate_and_count_the_dropped() {
+        let bucket = TokenBucket::new(2.0);
+        let now = Instant::now();
+
+        assert!(bucket.acquire_at(now));
+        assert!(bucket.acquire_at(now));
+        assert!(!bucket.acquire_at(now));
+        assert_eq!(bucket.dropped(), 1);
+    }
+
+    #[test]
+    fn token_bucket_should_refill_with_the_elapsed_time() {
+        let bucket = TokenBucket::new(2.0);
+        let now = Instant::now();
+
+        assert!(bucket.acquire_at(now));
+        assert!(bucket.acquire_at(now));
+        assert!(!bucket.acquire_at(now + Duration::from_millis(250)));
+        assert!(bucket.acquire_at(now + Duration::from_millis(500)));
+        assert!(!bucket.acquire_at(now + Duration::from_millis(500)));
+        assert_eq!(bucket.dropped(), 2);
+    }
+}
@@ -0,0 +1,93 @@
+//! Reconfiguration of the policy of an existing experiment, keeping its closures.
+
+use std::time::Duration;
+
+use crate::settings::Settings;
+
+/// Reconfigure
+///
+/// Mutable handle to the policy of a built experiment, see `CompleteExperiment::reconfigure`. The
+/// methods mirror the consuming builder methods of the same name, so that e.g. a long-lived
+/// experiment stored in the application state can change its sample rate at runtime.
+pub struct Reconfigure<'a> {
+    settings: &'a mut Settings,
+}
+
+impl<'a> Reconfigure<'a> {
+    pub(crate) fn new(settings: &'a mut Settings) -> Self {
+        Self { settings }
+    }
+
+    /// Run the experiment in the given percentage (`0.0..=100.0`) of the runs only.
+    pub fn run_percentage(&mut self, percentage: f64) -> &mut Self {
+        self.settings.sample_rate(percentage / 100.0);
+        self
+    }
+
+    /// Sample the runs with a probability decreasing as the load increases.
+    pub fn adaptive_sample<L>(&mut self, load_fn: L, max_rate: f64) -> &mut Self
+    where
+        L: Fn() -> f64 + Send + Sync + 'static,
+    {
+        self.settings.adaptive_sample(load_fn, max_rate);
+        self
+    }
+
+    /// Run the experiment in all of the runs again, dropping the sampling.
+    pub fn always_run(&mut self) -> &mut Self {
+        self.settings.sample_rate = None;
+        self
+    }
+
+    /// Seed the random generator deciding the sampled runs, so that they are reproducible.
+    pub fn with_seed(&mut self, seed: u64) -> &mut Self {
+        self.settings.seed(seed);
+        self
+    }
+
+    /// Switch the experiment off using the environment variable, `None` removes the switch.
+    pub fn enabled_by_env(&mut self, variable: Option<String>) -> &mut Self {
+        self.settings.enabled_by_env = variable;
+        self
+    }
+
+    /// Consider both of the branches panicking a match.
+    pub fn match_on_both_failed(&mut self, enabled: bool) -> &mut Self {
+        self.settings.match_on_both_failed = enabled;
+        self
+    }
+
+    /// Ignore the mismatches during the grace period, `None` makes every mismatch real.
+    pub fn strict_after(&mut self, grace_period: Option<Duration>) -> &mut Self {
+        self.settings.strict_after = grace_period;
+        self
+    }
+
+    /// Tag the observations with the version of the experiment implementation.
+    pub fn version(&mut self, version: &'static str) -> &mut Self {
+        self.settings.version = Some(version);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Experiment;
+
+    #[test]
+    fn reconfigure_should_change_the_policy_of_the_built_experiment() {
+        let mut experiment = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| 1)
+            .run_percentage(0.0);
+
+        experiment.reconfigure().run_percentage(50.0).version("2");
+
+        let description = experiment.describe();
+        assert_eq!(description.sample_rate, 0.5);
+        assert_eq!(description.version, Some("2"));
+
+        experiment.reconfigure().always_run();
+        assert_eq!(experiment.describe().sample_rate, 1.0);
+    }
+}
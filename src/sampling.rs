@@ -0,0 +1,120 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// Deterministic random generator (SplitMix64) used for reproducible sampling.
+#[derive(Debug, Clone)]
+pub(crate) struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Next uniformly distributed random number from the `[0, 1)` interval.
+    pub fn next_f64(&mut self) -> f64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        unit(z ^ (z >> 31))
+    }
+}
+
+/// Source of the random numbers deciding the sampled runs.
+pub(crate) trait Random: Send {
+    /// Next uniformly distributed random number from the `[0, 1)` interval.
+    fn next_f64(&mut self) -> f64;
+}
+
+impl Random for SeededRng {
+    fn next_f64(&mut self) -> f64 {
+        SeededRng::next_f64(self)
+    }
+}
+
+/// Random generator supplied by the user, see `with_rng`.
+#[cfg(feature = "rand")]
+pub(crate) struct UserRng<R>(pub R);
+
+#[cfg(feature = "rand")]
+impl<R> Random for UserRng<R>
+where
+    R: rand_core::RngCore + Send,
+{
+    fn next_f64(&mut self) -> f64 {
+        unit(self.0.next_u64())
+    }
+}
+
+/// Map the random bits into the `[0, 1)` interval.
+fn unit(bits: u64) -> f64 {
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Uniformly distributed random number from the `[0, 1)` interval.
+///
+/// Uses the randomly keyed hasher of the `std`, so that no external random generator is needed.
+pub(crate) fn random() -> f64 {
+    unit(RandomState::new().build_hasher().finish())
+}
+
+/// Decide whether to run the experiment with the given probability.
+///
+/// The rate `1.0` (or more) always samples, the rate `0.0` (or less, or `NaN`) never does.
+pub(crate) fn sample(rate: f64, random: impl FnOnce() -> f64) -> bool {
+    rate >= 1.0 || random() < rate
+}
+
+/// Probability of running the experiment under the load, see `adaptive_sample`.
+pub(crate) fn adaptive_rate(load: f64, max_rate: f64) -> f64 {
+    max_rate.clamp(0.0, 1.0) * (1.0 - load.clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_should_be_in_the_unit_interval() {
+        assert!((0..1000).map(|_| random()).all(|r| (0.0..1.0).contains(&r)));
+    }
+
+    #[test]
+    fn random_should_not_be_constant() {
+        let first = random();
+
+        assert!((0..10).map(|_| random()).any(|r| r != first));
+    }
+
+    #[test]
+    fn sample_should_always_run_with_full_rate_and_never_with_zero_rate() {
+        assert!((0..100).all(|_| sample(1.0, random)));
+        assert!((0..100).all(|_| !sample(0.0, random)));
+        assert!((0..100).all(|_| !sample(f64::NAN, random)));
+    }
+
+    #[test]
+    fn adaptive_rate_should_decrease_linearly_with_the_load() {
+        assert_eq!(adaptive_rate(0.0, 0.5), 0.5);
+        assert_eq!(adaptive_rate(0.5, 0.5), 0.25);
+        assert_eq!(adaptive_rate(1.0, 0.5), 0.0);
+        assert_eq!(adaptive_rate(2.0, 0.5), 0.0);
+        assert_eq!(adaptive_rate(-1.0, 2.0), 1.0);
+    }
+
+    #[test]
+    fn seeded_rng_should_repeat_the_sequence_for_the_same_seed() {
+        let mut first = SeededRng::new(42);
+        let mut second = SeededRng::new(42);
+        let sequence = (0..100).map(|_| first.next_f64()).collect::<Vec<_>>();
+
+        assert_eq!(
+            sequence,
+            (0..100).map(|_| second.next_f64()).collect::<Vec<_>>()
+        );
+        assert!(sequence.iter().all(|r| (0.0..1.0).contains(r)));
+        assert_ne!(SeededRng::new(7).next_f64(), SeededRng::new(42).next_f64());
+    }
+}
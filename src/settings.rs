@@ -0,0 +1,278 @@
+use std::any::Any;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::match_rate::MatchWindow;
+use crate::rate_limit::TokenBucket;
+use crate::sampling::{Random, SeededRng};
+
+/// Hook called with the panic payload of the control, see `Settings::resume`.
+pub(crate) type ResumeHook = dyn Fn(&Box<dyn Any + Send>) + Send + Sync;
+
+/// Settings
+///
+/// Non-generic configuration of an experiment carried through all of the builder stages.
+#[derive(Clone)]
+pub(crate) struct Settings {
+    /// Group of the experiment, reported in its description.
+    pub group: Option<&'static str>,
+    /// Version of the experiment implementation, recorded in the observations.
+    pub version: Option<&'static str>,
+    /// Key-value metadata of the experiment, recorded in the observations.
+    pub tags: Vec<(&'static str, String)>,
+    /// Enter a `tracing` span for each of the branches.
+    #[cfg(feature = "tracing")]
+    pub spans: bool,
+    /// Probability of running the experiment, evaluated before each run.
+    pub sample_rate: Option<Arc<dyn Fn() -> f64 + Send + Sync>>,
+    /// Environment variable switching the experiment off, read before each run.
+    pub enabled_by_env: Option<String>,
+    /// Window of the most recent verdicts, shared by the clones of the experiment.
+    pub match_window: Option<Arc<MatchWindow>>,
+    /// Random generator for the sampling, shared by the clones of the experiment.
+    pub rng: Option<Arc<Mutex<dyn Random>>>,
+    /// Capture the location of the panics in the branches.
+    pub panic_locations: bool,
+    /// Consider both of the branches panicking a match.
+    pub match_on_both_failed: bool,
+    /// Publish the runs whose experiment was skipped.
+    pub publish_skips: bool,
+    /// Grace period of the `async` experiment after the control completed, before abandoning it.
+    pub control_first: Option<Duration>,
+    /// Deadline of the `async` experiment shared by both of the branches.
+    pub deadline: Option<Instant>,
+    /// Grace period after the process start during which the mismatches are ignored.
+    pub strict_after: Option<Duration>,
+    /// Limit of the publications per second, shared by the clones of the experiment.
+    pub publish_limit: Option<Arc<TokenBucket>>,
+    /// Hook called with the panic payload of the control right before resuming the panic.
+    pub on_resume: Option<Arc<ResumeHook>>,
+    /// Budget of the experiment runs shared with other experiments, consulted after the sampling.
+    pub budget: Option<crate::budget::SamplingBudget>,
+}
+
+impl Settings {
+    /// Tag the experiment, replacing the value of a duplicate key in place.
+    pub fn tag(&mut self, key: &'static str, value: String) {
+        match self.tags.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => self.tags.push((key, value)),
+        }
+    }
+
+    /// Sample the runs with the probability decreasing with the load, see `adaptive_sample`.
+    pub fn adaptive_sample<L>(&mut self, load_fn: L, max_rate: f64)
+    where
+        L: Fn() -> f64 + Send + Sync + 'static,
+    {
+        self.sample_rate = Some(Arc::new(move || {
+            crate::sampling::adaptive_rate(load_fn(), max_rate)
+        }));
+    }
+
+    /// Sample the runs with the fixed probability.
+    pub fn sample_rate(&mut self, rate: f64) {
+        self.sample_rate = Some(Arc::new(move || rate));
+    }
+
+    /// Seed the random generator used for the sampling.
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = Some(Arc::new(Mutex::new(SeededRng::new(seed))));
+    }
+
+    /// Use the random generator of the user for the sampling.
+    #[cfg(feature = "rand")]
+    pub fn rng<R>(&mut self, rng: R)
+    where
+        R: rand_core::RngCore + Send + 'static,
+    {
+        self.rng = Some(Arc::new(Mutex::new(crate::sampling::UserRng(rng))));
+    }
+
+    /// Record the verdict of the observation into the match window, if tracked.
+    pub fn record<T, TE>(&self, observation: &crate::Observation<T, TE>) {
+        if let (Some(window), Some(matching)) = (&self.match_window, observation.verdict()) {
+            window.record(matching);
+        }
+    }
+
+    /// Publish the observation unless the publishing rate limit is exceeded.
+    pub fn publish<T, TE>(
+        &self,
+        publisher: &impl crate::Publisher<T, TE>,
+        observation: &crate::Observation<T, TE>,
+    ) {
+        if self
+            .publish_limit
+            .as_ref()
+            .map_or(true, |limit| limit.acquire())
+        {
+            publisher.publish(observation);
+        }
+    }
+
+    /// Whether the run is within the grace period of `strict_after`.
+    pub fn in_grace_period(&self) -> bool {
+        self.strict_after
+            .is_some_and(|grace| process_start().elapsed() < grace)
+    }
+
+    /// Number of the observations dropped by the publishing rate limit.
+    pub fn dropped_publications(&self) -> u64 {
+        self.publish_limit
+            .as_ref()
+            .map_or(0, |limit| limit.dropped())
+    }
+
+    /// Ratio of the matching runs in the match window, `NaN` if untracked or empty.
+    pub fn match_rate(&self) -> f64 {
+        self.match_window
+            .as_ref()
+            .map_or(f64::NAN, |window| window.rate())
+    }
+
+    /// Describe the configuration of the experiment, evaluating the current sample rate and switch.
+    pub fn describe(&self, name: &'static str) -> crate::ExperimentDescription {
+        crate::ExperimentDescription {
+            name,
+            group: self.group,
+            version: self.version,
+            sample_rate: self.sample_rate.as_ref().map_or(1.0, |rate| rate()),
+            enabled: self.enabled_by_env.as_deref().map_or(true, enabled_by_env),
+        }
+    }
+
+    /// Resume the panic of the control, calling the `on_resume` hook first.
+    pub fn resume(&self, payload: Box<dyn Any + Send>) -> ! {
+        if let Some(hook) = &self.on_resume {
+            hook(&payload);
+        }
+        std::panic::resume_unwind(payload)
+    }
+
+    /// Decide whether the experiment branch should run, according to the switch and the sampling.
+    pub fn should_run(&self) -> bool {
+        self.skip_reason().is_none()
+    }
+
+    /// Why the experiment branch should be skipped, checking the switch, the sampling and the
+    /// budget in this order. Always disabled in the build with the `disabled` feature.
+    pub fn skip_reason(&self) -> Option<crate::SkipReason> {
+        if cfg!(scientisto_disabled) || !self.enabled_by_env.as_deref().map_or(true, enabled_by_env)
+        {
+            Some(crate::SkipReason::Disabled)
+        } else if !self.sample() {
+            Some(crate::SkipReason::Sampled)
+        } else if !self.budget.as_ref().map_or(true, |budget| budget.acquire()) {
+            Some(crate::SkipReason::Budget)
+        } else {
+            None
+        }
+    }
+
+    fn sample(&self) -> bool {
+        match &self.sample_rate {
+            Some(rate) => crate::sampling::sample(rate(), || self.random()),
+            None => true,
+        }
+    }
+
+    fn random(&self) -> f64 {
+        match &self.rng {
+            Some(rng) => rng
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .next_f64(),
+            None => crate::sampling::random(),
+        }
+    }
+}
+
+/// Start of the process, approximated by the first experiment defined in the process.
+pub(crate) fn process_start() -> Instant {
+    static START: OnceLock<Instant> = OnceLock::new();
+
+    *START.get_or_init(Instant::now)
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        process_start();
+
+        Self {
+            group: None,
+            version: None,
+            tags: Vec::new(),
+            #[cfg(feature = "tracing")]
+            spans: true,
+            sample_rate: None,
+            rng: None,
+            enabled_by_env: None,
+            match_window: None,
+            match_on_both_failed: false,
+            publish_skips: false,
+            publish_limit: None,
+            strict_after: None,
+            control_first: None,
+            deadline: None,
+            panic_locations: false,
+            on_resume: None,
+            budget: None,
+        }
+    }
+}
+
+/// Whether the environment variable leaves the experiment enabled.
+///
+/// Unset variable enables it, `0`, `false`, `no` and `off` (trimmed, case insensitive) disable it.
+fn enabled_by_env(variable: &str) -> bool {
+    match std::env::var(variable) {
+        Ok(value) => !matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "0" | "false" | "no" | "off"
+        ),
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enabled_by_env_should_parse_the_falsy_values_only() {
+        let variable = "SCIENTISTO_TEST_ENABLED_BY_ENV_PARSING";
+
+        std::env::remove_var(variable);
+        assert!(enabled_by_env(variable));
+
+        for value in ["0", "false", " FALSE ", "no", "Off"] {
+            std::env::set_var(variable, value);
+            assert!(!enabled_by_env(variable), "{:?} should disable", value);
+        }
+
+        for value in ["1", "true", "yes", ""] {
+            std::env::set_var(variable, value);
+            assert!(enabled_by_env(variable), "{:?} should enable", value);
+        }
+
+        std::env::remove_var(variable);
+    }
+
+    #[test]
+    fn tag_should_replace_the_value_of_a_duplicate_key_in_place() {
+        let mut settings = Settings::default();
+
+        settings.tag("route", String::from("/a"));
+        settings.tag("region", String::from("eu"));
+        settings.tag("route", String::from("/b"));
+
+        assert_eq!(
+            settings.tags,
+            vec![
+                ("route", String::from("/b")),
+                ("region", String::from("eu"))
+            ]
+        );
+    }
+}
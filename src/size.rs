@@ -0,0 +1,70 @@
+//! Byte sizes of the **control** and **experiment** values recorded in the observations.
+
+/// Size
+///
+/// Measures the size of a value, e.g. the number of bytes of an encoded output.
+pub trait Size<T> {
+    fn size(&self, value: &T) -> Option<usize>;
+}
+
+impl<T, F> Size<T> for F
+where
+    F: Fn(&T) -> usize,
+{
+    fn size(&self, value: &T) -> Option<usize> {
+        Some(self(value))
+    }
+}
+
+/// Default size of the values, not measured.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoSize;
+
+impl<T> Size<T> for NoSize {
+    fn size(&self, _value: &T) -> Option<usize> {
+        None
+    }
+}
+
+/// Sizes
+///
+/// Measures of the sizes of both of the branches, see `CompleteExperiment::with_size`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Sizes<SC = NoSize, SE = NoSize> {
+    pub(crate) control: SC,
+    pub(crate) experiment: SE,
+}
+
+impl<SC, SE> Sizes<SC, SE> {
+    /// Measure the sizes of the values returned by the branches, a panicked branch has no size.
+    pub(crate) fn measure<T, TE>(
+        &self,
+        control: Option<&T>,
+        experiment: Option<&TE>,
+    ) -> (Option<usize>, Option<usize>)
+    where
+        SC: Size<T>,
+        SE: Size<TE>,
+    {
+        (
+            control.and_then(|value| self.control.size(value)),
+            experiment.and_then(|value| self.experiment.size(value)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_should_measure_only_the_sizes_configured_for_the_returned_values() {
+        let sizes = Sizes {
+            control: |v: &Vec<u8>| v.len(),
+            experiment: NoSize,
+        };
+
+        assert_eq!(sizes.measure(Some(&vec![1, 2]), Some(&1)), (Some(2), None));
+        assert_eq!(sizes.measure::<Vec<u8>, i32>(None, None), (None, None));
+    }
+}
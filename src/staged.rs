@@ -0,0 +1,100 @@
+//! Staged experiments, running the later experiments only if the earlier ones matched.
+
+/// Stage
+///
+/// Experiment which can be chained by `CompleteExperiment::then`, reporting whether it matched.
+pub trait Stage {
+    /// The value returned by running the stage, i.e. its control value.
+    type Output;
+
+    /// Run the stage, returning its control value and whether its observation matched.
+    ///
+    /// # Panics
+    /// Panics if the **control** panics, once the observation is published.
+    fn run_stage(&self) -> (Self::Output, bool);
+}
+
+/// Staged experiment
+///
+/// Chain of experiments where the next one runs only if the first one matched, e.g. a cheap
+/// experiment gating an expensive one. See `CompleteExperiment::then`.
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct Staged<A, B> {
+    first: A,
+    next: B,
+}
+
+impl<A, B> Staged<A, B>
+where
+    A: Stage,
+    B: Stage,
+{
+    pub(crate) fn new(first: A, next: B) -> Self {
+        Self { first, next }
+    }
+
+    /// Chain a further experiment, run only if all of the previous ones matched.
+    pub fn then<C>(self, next: C) -> Staged<Self, C>
+    where
+        C: Stage,
+    {
+        Staged::new(self, next)
+    }
+
+    /// Run the first experiment and the next one if the first matched, returning the first
+    /// control value.
+    ///
+    /// # Panics
+    /// Panics if the **control** of any of the run experiments panics.
+    pub fn run(&self) -> A::Output {
+        self.run_stage().0
+    }
+}
+
+impl<A, B> Stage for Staged<A, B>
+where
+    A: Stage,
+    B: Stage,
+{
+    type Output = A::Output;
+
+    fn run_stage(&self) -> (A::Output, bool) {
+        match self.first.run_stage() {
+            (result, true) => (result, self.next.run_stage().1),
+            (result, false) => (result, false),
+        }
+    }
+}
+
+#[cfg(all(test, not(scientisto_disabled)))]
+mod tests {
+    use crate::{Experiment, Observation};
+    use std::cell::RefCell;
+
+    #[test]
+    fn staged_experiment_should_run_the_next_stages_only_after_a_match() {
+        let published = RefCell::new(Vec::new());
+        let publish = |o: &Observation<i32, i32>| published.borrow_mut().push(o.name());
+
+        let result = Experiment::new("First")
+            .control(|| 1)
+            .experiment(|| 1)
+            .publish(publish)
+            .then(
+                Experiment::new("Second")
+                    .control(|| 2)
+                    .experiment(|| 3)
+                    .publish(publish),
+            )
+            .then(
+                Experiment::new("Third")
+                    .control(|| 3)
+                    .experiment(|| 3)
+                    .publish(publish),
+            )
+            .run();
+
+        assert_eq!(result, 1);
+        assert_eq!(published.into_inner(), vec!["First", "Second"]);
+    }
+}
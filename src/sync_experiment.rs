@@ -1,5 +1,53 @@
+use std::any::Any;
 use std::marker::PhantomData;
 use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+use crate::candidates::CandidateExperiment;
+use crate::compare::{self, Comparator, Equality};
+use crate::description::ExperimentDescription;
+use crate::error::{is_metric_name_char, validate_name, ExperimentError};
+use crate::instrument::{counted, in_branch, stamped, Branch};
+use crate::match_rate::MatchWindow;
+use crate::matrix::MatrixControlOnly;
+use crate::once::OnceControlOnly;
+use crate::pair::Pair;
+use crate::panic_location;
+use crate::previous::PreviousExperiment;
+use crate::projection::{Identity, Mapped, Projection};
+use crate::publisher::{DefaultPublisher, NoopPublisher, PublishRouter, Publisher};
+use crate::rate_limit::TokenBucket;
+use crate::reconfigure::Reconfigure;
+use crate::settings::Settings;
+use crate::size::{NoSize, Size, Sizes};
+use crate::staged::{Stage, Staged};
+use crate::verdict::VerdictHandle;
+use crate::SkipReason;
+
+type PanicMapping<T> = dyn Fn(Box<dyn Any + Send>) -> T + Send + Sync;
+
+struct Executable<T, F>
+where
+    F: Fn() -> T,
+{
+    phantom_return_type: PhantomData<T>,
+    pub f: F,
+    /// Conversion of the panic of the branch into a value, see `map_control_panic`.
+    pub recover: Option<Box<PanicMapping<T>>>,
+}
+
+impl<T, F> Executable<T, F>
+where
+    F: Fn() -> T,
+{
+    pub fn new(f: F) -> Self {
+        Self {
+            phantom_return_type: Default::default(),
+            f,
+            recover: None,
+        }
+    }
+}
 
 /// Experiment
 /// Basic struct defining the conducted experiment. Initialized using type definitions instead of
@@ -7,8 +55,11 @@ use std::panic::{catch_unwind, AssertUnwindSafe};
 /// functions defined for the experiment.
 ///
 /// The results of the experiment, if run, are input into the publisher. The default
-/// publisher is a `noop`, whereas a custom publisher can be used either as a passed function or
-/// closure. Publisher can contain any logic, as long as it returns a `Unit` type.
+/// publisher is the one set by `set_default_publisher` (a `noop` until set), whereas a custom
+/// publisher can be used either as a passed function or closure. Publisher can contain any logic,
+/// as long as it returns a `Unit` type.
+///
+/// The `Experiment` only runs blocking functions, use the `AsyncExperiment` for `async` code.
 ///
 /// # Operation
 /// - decides whether or not to run the experiment block
@@ -53,32 +104,12 @@ use std::panic::{catch_unwind, AssertUnwindSafe};
 ///      })
 ///     .run();
 /// ```
-///
-
-struct Executable<T, F>
-where
-    F: Fn() -> T,
-{
-    phantom_return_type: PhantomData<T>,
-    pub f: F,
-}
-
-impl<T, F> Executable<T, F>
-where
-    F: Fn() -> T,
-{
-    pub fn new(f: F) -> Self {
-        Self {
-            phantom_return_type: Default::default(),
-            f,
-        }
-    }
-}
-
 #[derive(Debug, Clone)]
-pub struct Experiment {
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct Experiment<FP = DefaultPublisher> {
     /// The name under which the experiment is registered.
     name: &'static str,
+    publish: FP,
 }
 
 impl Experiment {
@@ -87,33 +118,207 @@ impl Experiment {
             panic!("Experiment name cannot be empty");
         }
 
-        Self { name }
+        Self {
+            name,
+            publish: DefaultPublisher,
+        }
+    }
+
+    /// Create the experiment with a strict name of the `[a-zA-Z0-9_.-]` charset only.
+    ///
+    /// Unlike `new`, which accepts any non-empty name, the strict name is accepted by the metric
+    /// backends like Prometheus. See `try_new_with` for a custom charset.
+    ///
+    /// # Errors
+    /// Returns `ExperimentError::InvalidName` for a name with a disallowed character and
+    /// `ExperimentError::EmptyName` for an empty one.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,ExperimentError};
+    ///
+    /// assert!(Experiment::try_new("checkout.v2").is_ok());
+    /// assert!(matches!(
+    ///     Experiment::try_new("checkout v2"),
+    ///     Err(ExperimentError::InvalidName { character: ' ', .. })
+    /// ));
+    /// ```
+    pub fn try_new(name: &'static str) -> Result<Self, ExperimentError> {
+        Self::try_new_with(name, is_metric_name_char)
+    }
+
+    /// Create the experiment with a name of the characters satisfying the predicate only.
+    pub fn try_new_with(
+        name: &'static str,
+        allowed: impl Fn(char) -> bool,
+    ) -> Result<Self, ExperimentError> {
+        validate_name(name, allowed).map(Self::new)
     }
+}
 
+impl<FP> Experiment<FP> {
     pub fn name(&self) -> &'static str {
         self.name
     }
 
-    pub fn control<T, F>(self, f: F) -> ControlOnly<T, F>
+    /// Set the publisher before the blocks, it is carried through `control` and `experiment`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Publisher first")
+    ///     .publish(|o: &Observation<i32, i32>| assert!(o.is_matching()))
+    ///     .control(|| 1)
+    ///     .experiment(|| 1)
+    ///     .run();
+    /// ```
+    pub fn publish<F>(self, f: F) -> Experiment<F> {
+        Experiment {
+            name: self.name,
+            publish: f,
+        }
+    }
+
+    /// Compare two implementations symmetrically, returning the observation instead of a value.
+    ///
+    /// Neither of the implementations is authoritative, see `Pair`. The publisher is not used.
+    pub fn pair<FA, FB>(self, a: FA, b: FB) -> Pair<FA, FB> {
+        Pair::new(self.name, a, b)
+    }
+
+    pub fn control<T, F>(self, f: F) -> ControlOnly<T, F, FP>
     where
         F: Fn() -> T + std::panic::UnwindSafe,
     {
         ControlOnly {
             name: self.name,
             control: Executable::<T, F>::new(f),
+            publish: self.publish,
         }
     }
+
+    /// Define a `FnOnce` control of a single-run experiment, see `OnceExperiment`.
+    pub fn control_once<T, F>(self, f: F) -> OnceControlOnly<F, FP>
+    where
+        F: FnOnce() -> T,
+    {
+        OnceControlOnly::new(self.name, f, self.publish)
+    }
+
+    /// Use a precomputed result as the **control** of a single-run experiment.
+    ///
+    /// For the computations which happened elsewhere, e.g. captured from the logs, to compare and
+    /// publish them only. An `Err` is recorded like a panic of the control, see
+    /// `OnceControlOnly::experiment_result`.
+    pub fn control_result<T>(
+        self,
+        result: std::thread::Result<T>,
+    ) -> OnceControlOnly<impl FnOnce() -> T, FP> {
+        self.control_once(move || result.unwrap_or_else(|e| std::panic::resume_unwind(e)))
+    }
+
+    /// Run both of the branches on their own snapshot of the shared state, comparing the states.
+    ///
+    /// The mutex is only locked to clone the state for each of the branches, which then modify
+    /// their clones. The resulting states are compared and the state of the control is returned,
+    /// the shared state itself is never modified.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::sync::Mutex;
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let balances = Mutex::new(vec![10, 20]);
+    ///
+    /// let result = Experiment::new("Interest")
+    ///     .snapshot(
+    ///         &balances,
+    ///         |b: &mut Vec<i32>| b.iter_mut().for_each(|v| *v += *v / 10),
+    ///         |b: &mut Vec<i32>| b.iter_mut().for_each(|v| *v = *v * 11 / 10),
+    ///     )
+    ///     .publish(|o: &Observation<Vec<i32>, Vec<i32>>| assert!(o.is_matching()))
+    ///     .run();
+    ///
+    /// assert_eq!(result, vec![11, 22]);
+    /// assert_eq!(*balances.lock().unwrap(), vec![10, 20]);
+    /// ```
+    pub fn snapshot<'a, S, C, E>(
+        self,
+        state: &'a std::sync::Mutex<S>,
+        control: C,
+        experiment: E,
+    ) -> CompleteExperiment<
+        S,
+        impl Fn() -> S + std::panic::UnwindSafe + 'a,
+        S,
+        impl Fn() -> S + std::panic::UnwindSafe + 'a,
+        FP,
+    >
+    where
+        S: Clone,
+        C: Fn(&mut S) + std::panic::UnwindSafe + 'a,
+        E: Fn(&mut S) + std::panic::UnwindSafe + 'a,
+    {
+        let branch = move |f: &dyn Fn(&mut S)| {
+            let mut snapshot = state
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .clone();
+            f(&mut snapshot);
+            snapshot
+        };
+
+        self.control(move || branch(&control))
+            .experiment(move || branch(&experiment))
+    }
+
+    /// Use a **control** taking an input, to run the experiment over a matrix of inputs.
+    ///
+    /// See `MatrixExperiment::run_matrix`.
+    pub fn control_with<I, T, F>(self, f: F) -> MatrixControlOnly<I, T, F, FP>
+    where
+        F: Fn(&I) -> T,
+    {
+        MatrixControlOnly::new(self.name, f, self.publish)
+    }
+
+    /// Use a precomputed value as the **control**, cloned for every run.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::Experiment;
+    ///
+    /// let result = Experiment::new("Precomputed")
+    ///     .control_value(3)
+    ///     .experiment(|| 1 + 2)
+    ///     .publish(|o: &scientisto::Observation<i32, i32>| assert!(o.is_matching()))
+    ///     .run();
+    ///
+    /// assert_eq!(result, 3);
+    /// ```
+    pub fn control_value<T>(
+        self,
+        value: T,
+    ) -> ControlOnly<T, impl Fn() -> T + std::panic::UnwindSafe, FP>
+    where
+        T: Clone + std::panic::UnwindSafe,
+    {
+        self.control(move || value.clone())
+    }
 }
 
-pub struct ControlOnly<TC, FC>
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct ControlOnly<TC, FC, FP = DefaultPublisher>
 where
     FC: Fn() -> TC + std::panic::UnwindSafe,
 {
     name: &'static str,
     control: Executable<TC, FC>,
+    publish: FP,
 }
 
-impl<TC, FC> ControlOnly<TC, FC>
+impl<TC, FC, FP> ControlOnly<TC, FC, FP>
 where
     FC: Fn() -> TC + std::panic::UnwindSafe,
 {
@@ -121,10 +326,30 @@ where
         self.name
     }
 
-    pub fn experiment<T, F>(
+    /// Compare several weighted candidates to the control instead of a single experiment.
+    ///
+    /// Further candidates are added to the returned `CandidateExperiment`, all of them need to
+    /// return the same type as the control.
+    pub fn candidate<'a, F>(
         self,
+        name: &'static str,
+        weight: u32,
         f: F,
-    ) -> CompleteExperiment<TC, FC, T, F, impl Fn(&crate::Observation<TC, T>)>
+    ) -> CandidateExperiment<'a, TC, FC, FP>
+    where
+        F: Fn() -> TC + 'a,
+    {
+        CandidateExperiment::new(self.name, self.control.f, self.publish).candidate(name, weight, f)
+    }
+
+    /// Compare the control against its own value from the previous run, instead of an experiment.
+    ///
+    /// Detects the nondeterminism of the control, see `PreviousExperiment`.
+    pub fn compare_with_previous(self) -> PreviousExperiment<TC, FC, FP> {
+        PreviousExperiment::new(self.name, self.control.f, self.publish)
+    }
+
+    pub fn experiment<T, F>(self, f: F) -> CompleteExperiment<TC, FC, T, F, FP>
     where
         F: Fn() -> T + std::panic::UnwindSafe,
     {
@@ -132,13 +357,130 @@ where
             name: self.name,
             control: self.control,
             experiment: Executable::<T, F>::new(f),
-            publish: |_: &crate::Observation<TC, T>| {},
+            publish: self.publish,
+            projection: Identity,
+            comparator: Equality,
+            sizes: Sizes::default(),
+            settings: Settings::default(),
+        }
+    }
+
+    /// Define the **experiment** returning a `Result`, whose error is a mismatch, not a panic.
+    ///
+    /// The `Ok` value is compared to the control, an `Err` is recorded in the observation as the
+    /// experiment value and never matches, with its message as the detail. The control value is
+    /// returned regardless, so the experiment does not need to panic to signal a failure. The
+    /// comparator of the `Ok` value can be replaced by `comparator(compare::experiment_ok(c))`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let result = Experiment::new("Fallible rewrite")
+    ///     .control(|| 1)
+    ///     .try_experiment(|| "x".parse::<i32>())
+    ///     .publish(|o: &Observation<i32, Result<i32, std::num::ParseIntError>>| {
+    ///         assert!(!o.is_matching());
+    ///         assert!(o.detail().unwrap().starts_with("experiment error"));
+    ///     })
+    ///     .run();
+    ///
+    /// assert_eq!(result, 1);
+    /// ```
+    pub fn try_experiment<O, E, F>(
+        self,
+        f: F,
+    ) -> CompleteExperiment<TC, FC, Result<O, E>, F, FP, Identity, compare::ExperimentOk>
+    where
+        F: Fn() -> Result<O, E> + std::panic::UnwindSafe,
+        O: PartialEq<TC>,
+        E: std::fmt::Display,
+    {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: Executable::new(f),
+            publish: self.publish,
+            projection: Identity,
+            comparator: compare::experiment_ok(Equality),
+            sizes: Sizes::default(),
+            settings: Settings::default(),
         }
     }
+
+    /// Use a precomputed value as the **experiment**, cloned for every run.
+    pub fn experiment_value<T>(
+        self,
+        value: T,
+    ) -> CompleteExperiment<TC, FC, T, impl Fn() -> T + std::panic::UnwindSafe, FP>
+    where
+        T: Clone + std::panic::UnwindSafe,
+    {
+        self.experiment(move || value.clone())
+    }
+
+    /// Use several acceptable golden values as the **experiment**, matching any of them.
+    ///
+    /// For the nondeterministic, but bounded outputs, e.g. the valid orderings. The control is
+    /// compared to each of the goldens using `PartialEq`, the observation records the index of the
+    /// first matching golden as the detail, see `compare::AnyOf`. The goldens are cloned for every
+    /// run.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Orderings")
+    ///     .control(|| vec![2, 1])
+    ///     .expect_any(vec![vec![1, 2], vec![2, 1]])
+    ///     .publish(|o: &Observation<Vec<i32>, Vec<Vec<i32>>>| {
+    ///         assert!(o.is_matching());
+    ///         assert_eq!(o.detail(), Some("golden: 1"));
+    ///     })
+    ///     .run();
+    /// ```
+    pub fn expect_any<T>(
+        self,
+        goldens: Vec<T>,
+    ) -> CompleteExperiment<
+        TC,
+        FC,
+        Vec<T>,
+        impl Fn() -> Vec<T> + std::panic::UnwindSafe,
+        FP,
+        Identity,
+        compare::AnyOf,
+    >
+    where
+        T: Clone + PartialEq<TC> + std::panic::UnwindSafe,
+    {
+        self.experiment_value(goldens)
+            .comparator(compare::any_of(Equality))
+    }
 }
 
-pub struct CompleteExperiment<TC, FC, TE, FE, FP>
-where
+/// Experiment with both of the **control** and **experiment** defined, ready to be run.
+///
+/// The experiment, like the intermediate builder states, is `#[must_use]`, so a chain missing its
+/// `run` is reported by the `unused_must_use` lint:
+/// ```rust,compile_fail
+/// #![deny(unused_must_use)]
+/// use scientisto::Experiment;
+///
+/// Experiment::new("Forgotten").control(|| 1).experiment(|| 1);
+/// ```
+#[must_use = "experiments do nothing unless .run() is called"]
+pub struct CompleteExperiment<
+    TC,
+    FC,
+    TE,
+    FE,
+    FP,
+    PC = Identity,
+    FM = Equality,
+    SC = NoSize,
+    SE = NoSize,
+> where
     FC: Fn() -> TC + std::panic::UnwindSafe,
     FE: Fn() -> TE + std::panic::UnwindSafe,
 {
@@ -146,192 +488,2941 @@ where
     control: Executable<TC, FC>,
     experiment: Executable<TE, FE>,
     publish: FP,
+    projection: PC,
+    comparator: FM,
+    sizes: Sizes<SC, SE>,
+    settings: Settings,
 }
 
-impl<TC, FC, TE, FE, FP> CompleteExperiment<TC, FC, TE, FE, FP>
+impl<TC, FC, TE, FE, FP, PC, FM, SC, SE> CompleteExperiment<TC, FC, TE, FE, FP, PC, FM, SC, SE>
 where
     FC: Fn() -> TC + std::panic::UnwindSafe,
     FE: Fn() -> TE + std::panic::UnwindSafe,
+    PC: Projection<TC>,
+    SC: Size<PC::Output>,
+    SE: Size<TE>,
 {
     pub fn name(&self) -> &'static str {
         self.name
     }
 
-    pub fn publish<F>(self, f: F) -> CompleteExperiment<TC, FC, TE, FE, F>
+    /// Map the control value used for the comparison and publishing.
+    ///
+    /// Only the recorded copy is transformed, `run` still returns the raw control value. This
+    /// allows normalizing the control (e.g. trimming trailing whitespace) before it is compared
+    /// against the experiment, without changing the behavior observed by the caller. Unlike the
+    /// `clean` of github/scientist, which only cleans the published values after the comparison,
+    /// the mapped value is the one compared, so it decides `Observation::is_matching`.
+    ///
+    /// The mapping function is not guarded, a panic in the mapping propagates out of `run`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let result = Experiment::new("Trimmed")
+    ///     .control(|| String::from("value  "))
+    ///     .experiment(|| String::from("value"))
+    ///     .map_control(|c: &String| c.trim_end().to_owned())
+    ///     .publish(|o: &Observation<String, String>| assert!(o.is_matching()))
+    ///     .run();
+    ///
+    /// assert_eq!(result, "value  ");
+    /// ```
+    pub fn map_control<R, M>(
+        self,
+        m: M,
+    ) -> CompleteExperiment<TC, FC, TE, FE, FP, Mapped<M>, FM, SC, SE>
     where
-        TE: PartialEq<TC>,
-        F: Fn(&crate::Observation<TC, TE>),
+        M: Fn(&TC) -> R,
     {
-        CompleteExperiment::<TC, FC, TE, FE, F> {
+        CompleteExperiment {
             name: self.name,
             control: self.control,
             experiment: self.experiment,
-            publish: f,
+            publish: self.publish,
+            projection: Mapped(m),
+            comparator: self.comparator,
+            sizes: self.sizes,
+            settings: self.settings,
         }
     }
 
-    pub fn run(&self) -> TC
+    /// Map the experiment value used for the comparison and publishing.
+    ///
+    /// The mapping runs as a part of the experiment block, a panic in the mapping is recorded as
+    /// an experiment panic.
+    pub fn map_experiment<R, M>(
+        self,
+        m: M,
+    ) -> CompleteExperiment<TC, FC, R, impl Fn() -> R + std::panic::UnwindSafe, FP, PC, FM, SC, SE>
     where
-        TE: PartialEq<TC>,
-        FP: Fn(&crate::Observation<TC, TE>),
+        M: Fn(TE) -> R + std::panic::UnwindSafe,
     {
-        self.run_if(|| true)
+        let experiment = self.experiment.f;
+
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: Executable::new(move || m(experiment())),
+            publish: self.publish,
+            projection: self.projection,
+            comparator: self.comparator,
+            sizes: self.sizes,
+            settings: self.settings,
+        }
     }
 
-    pub fn run_if<P>(&self, predicate: P) -> TC
+    /// Run the experiment block on the `rayon` thread pool instead of the calling thread.
+    ///
+    /// The **control** still runs inline first, then the experiment is executed on the pool using
+    /// `ThreadPool::install`, so that the CPU-bound experiment does not compete with the other work
+    /// of the calling thread. A panic on the pool is recorded as an experiment panic.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+    ///
+    /// Experiment::new("Offloaded")
+    ///     .control(|| 1)
+    ///     .experiment(|| 1)
+    ///     .with_thread_pool(pool)
+    ///     .publish(|o: &Observation<i32, i32>| assert!(o.is_matching()))
+    ///     .run();
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn with_thread_pool(
+        self,
+        pool: impl Into<std::sync::Arc<rayon::ThreadPool>>,
+    ) -> CompleteExperiment<TC, FC, TE, impl Fn() -> TE + std::panic::UnwindSafe, FP, PC, FM, SC, SE>
     where
-        TE: PartialEq<TC>,
-        FP: Fn(&crate::Observation<TC, TE>),
-        P: Fn() -> bool,
+        FE: Sync,
+        TE: Send,
     {
-        if predicate() {
-            let observation = crate::Observation::<TC, TE> {
-                control: catch_unwind(AssertUnwindSafe(&self.control.f)),
-                experiment: catch_unwind(AssertUnwindSafe(&self.experiment.f)),
-            };
+        let experiment = self.experiment.f;
+        let pool = AssertUnwindSafe(pool.into());
 
-            (self.publish)(&observation);
-
-            match observation.control {
-                Ok(result) => result,
-                Err(e) => std::panic::resume_unwind(e),
-            }
-        } else {
-            (self.control.f)()
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: Executable::new(move || pool.install(&experiment)),
+            publish: self.publish,
+            projection: self.projection,
+            comparator: self.comparator,
+            sizes: self.sizes,
+            settings: self.settings,
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Record the size of the control value in the observations, e.g. the bytes of an encoding.
+    ///
+    /// The size is measured on the recorded control value, so `map_control` needs to be applied
+    /// first. See `Observation::size_ratio` to compare it with the size of the experiment value.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Smaller encoding")
+    ///     .control(|| vec![0u8; 8])
+    ///     .experiment(|| vec![0u8; 6])
+    ///     .with_size(|c: &Vec<u8>| c.len())
+    ///     .with_experiment_size(|e: &Vec<u8>| e.len())
+    ///     .publish(|o: &Observation<Vec<u8>, Vec<u8>>| assert_eq!(o.size_ratio(), Some(0.75)))
+    ///     .run();
+    /// ```
+    pub fn with_size<S>(self, s: S) -> CompleteExperiment<TC, FC, TE, FE, FP, PC, FM, S, SE>
+    where
+        S: Fn(&PC::Output) -> usize,
+    {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            projection: self.projection,
+            comparator: self.comparator,
+            sizes: Sizes {
+                control: s,
+                experiment: self.sizes.experiment,
+            },
+            settings: self.settings,
+        }
+    }
 
-    #[test]
-    fn experiment_should_derive_the_debug_trait() {
-        let experiment = Experiment::new("empty experiment");
+    /// Record the size of the experiment value in the observations, see `with_size`.
+    ///
+    /// The size is measured on the experiment value, so `map_experiment` needs to be applied first.
+    pub fn with_experiment_size<S>(
+        self,
+        s: S,
+    ) -> CompleteExperiment<TC, FC, TE, FE, FP, PC, FM, SC, S>
+    where
+        S: Fn(&TE) -> usize,
+    {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            projection: self.projection,
+            comparator: self.comparator,
+            sizes: Sizes {
+                control: self.sizes.control,
+                experiment: s,
+            },
+            settings: self.settings,
+        }
+    }
 
-        assert_ne!(format!("{:?}", experiment), "");
+    /// Compare the values under the tolerances of the config instead of `PartialEq`.
+    ///
+    /// The config replaces the comparator and vice versa, so the last one of `comparator` and
+    /// `with_compare_config` called wins. The wrapping combinators, e.g. `ignore_when`, wrap the
+    /// config just like any other comparator. See `compare::CompareConfig`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::compare::CompareConfig;
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Configured")
+    ///     .control(|| vec![0.1 + 0.2, 1.0])
+    ///     .experiment(|| vec![1.0, 0.3])
+    ///     .with_compare_config(CompareConfig {
+    ///         float_rel_eps: 1e-9,
+    ///         ignore_collection_order: true,
+    ///         ..CompareConfig::default()
+    ///     })
+    ///     .publish(|o: &Observation<Vec<f64>, Vec<f64>>| assert!(o.is_matching()))
+    ///     .run();
+    /// ```
+    pub fn with_compare_config(
+        self,
+        config: compare::CompareConfig,
+    ) -> CompleteExperiment<TC, FC, TE, FE, FP, PC, compare::CompareConfig, SC, SE>
+    where
+        compare::CompareConfig: Comparator<PC::Output, TE>,
+    {
+        self.comparator(config)
     }
 
-    #[test]
-    #[should_panic]
-    fn experiment_should_panic_on_empty_string_name() {
-        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+    /// Compare the control and experiment values using a custom comparator instead of `PartialEq`.
+    ///
+    /// Any `Fn(&TC, &TE) -> bool` closure or a ready-made comparator from the `compare` module can
+    /// be used. The comparator is only invoked when neither of the blocks panicked.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Tolerant")
+    ///     .control(|| 3.00_f32)
+    ///     .experiment(|| 3.01_f32)
+    ///     .comparator(|c: &f32, e: &f32| (c - e).abs() < 0.1)
+    ///     .publish(|o: &Observation<f32, f32>| assert!(o.is_matching()))
+    ///     .run();
+    /// ```
+    pub fn comparator<C>(self, c: C) -> CompleteExperiment<TC, FC, TE, FE, FP, PC, C, SC, SE>
+    where
+        C: Comparator<PC::Output, TE>,
+    {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            projection: self.projection,
+            comparator: c,
+            sizes: self.sizes,
+            settings: self.settings,
+        }
+    }
 
-        Experiment::new("");
+    /// Ignore the mismatches satisfying the predicate, e.g. the known and accepted differences.
+    ///
+    /// The ignored mismatches are still not matching, but `Observation::matching_or_ignored` treats
+    /// them as fine. Wraps the current comparator, so it needs to be called after `comparator`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Known rounding")
+    ///     .control(|| 10)
+    ///     .experiment(|| 11)
+    ///     .ignore(|c: &i32, e: &i32| (c - e).abs() == 1)
+    ///     .publish(|o: &Observation<i32, i32>| {
+    ///         assert!(!o.is_matching());
+    ///         assert!(o.matching_or_ignored());
+    ///     })
+    ///     .run();
+    /// ```
+    pub fn ignore<P>(
+        self,
+        predicate: P,
+    ) -> CompleteExperiment<TC, FC, TE, FE, FP, PC, compare::IgnoreWhen<FM, P>, SC, SE>
+    where
+        P: Fn(&PC::Output, &TE) -> bool,
+    {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            projection: self.projection,
+            comparator: compare::ignore_when(self.comparator, predicate),
+            sizes: self.sizes,
+            settings: self.settings,
+        }
     }
 
-    #[test]
-    fn experiment_should_return_name_if_it_is_valid() {
-        let actual_name: &str = "Any ľšýžľš is OK";
-        let experiment = Experiment::new(actual_name);
+    /// Assign the experiment to a group, e.g. the team or the feature owning it.
+    ///
+    /// The group is reported by `describe` only, it does not affect the runs.
+    pub fn group(mut self, group: &'static str) -> Self {
+        self.settings.group = Some(group);
+        self
+    }
 
-        assert_eq!(experiment.name(), actual_name);
+    /// Tag the experiment with the key-value metadata recorded in the observations.
+    ///
+    /// Unlike the `group` and `version`, the tags are arbitrary, e.g. the route or the region,
+    /// for the publishers to segment the observations by, e.g. as the labels of the metrics. A
+    /// duplicate key replaces the value of the earlier tag, keeping its position.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let route = String::from("/users");
+    ///
+    /// Experiment::new("Tagged")
+    ///     .control(|| 1)
+    ///     .experiment(|| 1)
+    ///     .tag("route", route)
+    ///     .tag("region", "eu")
+    ///     .tag("region", "us")
+    ///     .publish(|o: &Observation<i32, i32>| {
+    ///         assert_eq!(o.tag("region"), Some("us"));
+    ///         assert_eq!(o.tags()[0], ("route", String::from("/users")));
+    ///     })
+    ///     .run();
+    /// ```
+    pub fn tag(mut self, key: &'static str, value: impl Into<String>) -> Self {
+        self.settings.tag(key, value.into());
+        self
     }
 
-    #[test]
-    fn experiment_should_return_name_with_control_specified() {
-        let actual_name: &str = "Only control callback";
-        let experiment = Experiment::new(actual_name).control(|| false);
+    /// Publish also the runs whose experiment was skipped, with the reason of the skip.
+    ///
+    /// Off by default, a skipped run is published uncompared, with `Observation::skip_reason`
+    /// telling whether the predicate of `run_if`, the `enabled_by_env` switch or the sampling
+    /// skipped it, checked in this order. The skipped runs do not count into the `match_rate`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation,SkipReason};
+    ///
+    /// Experiment::new("Diagnosed")
+    ///     .control(|| 1)
+    ///     .experiment(|| 1)
+    ///     .run_percentage(0.0)
+    ///     .publish_skips(true)
+    ///     .publish(|o: &Observation<i32, i32>| {
+    ///         assert_eq!(o.skip_reason(), Some(SkipReason::Sampled));
+    ///     })
+    ///     .run();
+    /// ```
+    pub fn publish_skips(mut self, enabled: bool) -> Self {
+        self.settings.publish_skips = enabled;
+        self
+    }
 
-        assert_eq!(experiment.name(), actual_name);
+    /// Call the hook with the panic payload of the control right before the panic is resumed.
+    ///
+    /// Runs at the exact resume point of `run` and the other methods panicking with the control,
+    /// after the observation is published, e.g. to count the control panics or flush a log without
+    /// a surrounding `catch_unwind`. Not called by `run_safe`, which returns the payload instead.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use scientisto::Experiment;
+    ///
+    /// static CONTROL_PANICS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// let result = std::panic::catch_unwind(|| {
+    ///     Experiment::new("Counted")
+    ///         .control(|| -> i32 { panic!("Oops") })
+    ///         .experiment(|| 1)
+    ///         .on_resume(|_| {
+    ///             CONTROL_PANICS.fetch_add(1, Ordering::Relaxed);
+    ///         })
+    ///         .run()
+    /// });
+    ///
+    /// assert!(result.is_err());
+    /// assert_eq!(CONTROL_PANICS.load(Ordering::Relaxed), 1);
+    /// ```
+    pub fn on_resume<R>(mut self, hook: R) -> Self
+    where
+        R: Fn(&Box<dyn Any + Send>) + Send + Sync + 'static,
+    {
+        self.settings.on_resume = Some(std::sync::Arc::new(hook));
+        self
     }
 
-    #[test]
-    fn experiment_should_return_name_if_control_and_experiment_are_fully_specified() {
-        let name: &str = "Only control callback";
-        let experiment = Experiment::new(name).control(|| 1).experiment(|| 1);
+    /// Convert a panic of the control into the value returned instead of resuming the panic.
+    ///
+    /// The panic is still recorded in the published observation, only the caller receives the
+    /// mapped value, e.g. a fallback of the primary path. Applies to `run` and the other methods
+    /// returning the control value, `run_safe` returns the mapped value as `Ok`. The `on_resume`
+    /// hook is not called for the mapped panics. The mapping survives a later `map_control`, which
+    /// only projects the recorded copy of the control value.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let result = Experiment::new("Fallback")
+    ///     .control(|| -> i32 { panic!("Oops") })
+    ///     .experiment(|| 1)
+    ///     .map_control_panic(|_| -1)
+    ///     .publish(|o: &Observation<i32, i32>| assert!(!o.control_is_ok()))
+    ///     .run();
+    ///
+    /// assert_eq!(result, -1);
+    /// ```
+    pub fn map_control_panic<M>(mut self, m: M) -> Self
+    where
+        M: Fn(Box<dyn Any + Send>) -> TC + Send + Sync + 'static,
+    {
+        self.control.recover = Some(Box::new(m));
+        self
+    }
 
-        assert_eq!(experiment.name(), name);
+    /// Return the default value instead of the panic of the control.
+    ///
+    /// The single place to configure the safe value of the experiment in the degraded conditions,
+    /// a shorthand of `map_control_panic` ignoring the payload. It only covers the panics of the
+    /// control: a run skipped by `run_if` or the sampling still runs the control and returns its
+    /// value, so there is no missing value to default, only its panic is replaced the same way.
+    ///
+    /// The value is cloned for each of the runs, since `run` takes `&self` and can be repeated.
+    /// It is stored in the boxed panic mapping of `map_control_panic`, which is `Send + Sync` to
+    /// keep the experiment shareable across threads and `'static` as it borrows nothing, hence the
+    /// bounds on `TC`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::Experiment;
+    ///
+    /// let result = Experiment::new("Safe value")
+    ///     .control(|| -> Vec<i32> { panic!("Oops") })
+    ///     .experiment(|| vec![1])
+    ///     .with_default(Vec::new())
+    ///     .run_safe();
+    ///
+    /// assert_eq!(result.ok(), Some(Vec::new()));
+    /// ```
+    pub fn with_default(self, value: TC) -> Self
+    where
+        TC: Clone + Send + Sync + 'static,
+    {
+        self.map_control_panic(move |_| value.clone())
     }
 
-    #[test]
-    fn experiment_should_always_return_the_control_value() {
-        let expected = 1;
-        let actual = Experiment::new("Test")
-            .control(|| expected)
-            .experiment(|| expected)
-            .run();
+    /// Reconfigure the policy of the built experiment in place, keeping its closures.
+    ///
+    /// The consuming builder methods require rebuilding the experiment, whereas the returned
+    /// handle changes e.g. the sampling of a long-lived experiment stored in the application state.
+    /// A shared experiment needs to be behind a lock to be reconfigured, `run` takes `&self`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::Experiment;
+    ///
+    /// let mut experiment = Experiment::new("Long-lived")
+    ///     .control(|| 1)
+    ///     .experiment(|| 1)
+    ///     .run_percentage(1.0);
+    ///
+    /// experiment.reconfigure().run_percentage(10.0).match_on_both_failed(true);
+    ///
+    /// assert_eq!(experiment.describe().sample_rate, 0.1);
+    /// assert_eq!(experiment.run(), 1);
+    /// ```
+    pub fn reconfigure(&mut self) -> Reconfigure<'_> {
+        Reconfigure::new(&mut self.settings)
+    }
 
-        assert_eq!(actual, expected);
+    /// Tag the observations with the version of the experiment implementation.
+    ///
+    /// Bumping the version when the experiment changes keeps the mismatches of the old and the new
+    /// implementation apart, e.g. to segment the mismatch rates across deploys.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Versioned")
+    ///     .control(|| 1)
+    ///     .experiment(|| 1)
+    ///     .version("2024-06-rewrite")
+    ///     .publish(|o: &Observation<i32, i32>| assert_eq!(o.version(), Some("2024-06-rewrite")))
+    ///     .run();
+    /// ```
+    pub fn version(mut self, version: &'static str) -> Self {
+        self.settings.version = Some(version);
+        self
     }
 
-    #[test]
-    fn experiment_should_not_run_the_experiment_if_conditioned_not_to() {
-        let expected = 1;
-        let actual = Experiment::new("Test")
-            .control(|| expected)
-            .experiment(|| expected)
-            .run_if(|| false);
+    /// Describe the configuration of the experiment, without its closures.
+    ///
+    /// The sample rate and the `enabled_by_env` switch are evaluated at the time of the call, so
+    /// that e.g. a service can list its experiments on an introspection endpoint.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::Experiment;
+    ///
+    /// let experiment = Experiment::new("Pricing")
+    ///     .control(|| 1)
+    ///     .experiment(|| 1)
+    ///     .group("checkout")
+    ///     .run_percentage(10.0);
+    ///
+    /// let description = experiment.describe();
+    /// assert_eq!(description.name, "Pricing");
+    /// assert_eq!(description.group, Some("checkout"));
+    /// assert_eq!(description.sample_rate, 0.1);
+    /// ```
+    pub fn describe(&self) -> ExperimentDescription {
+        self.settings.describe(self.name)
+    }
 
-        assert_eq!(actual, expected);
+    /// Ignore the mismatches during the grace period after the process start, e.g. in a rollout.
+    ///
+    /// The process start is approximated by the first experiment defined in the process. The
+    /// mismatches before the deadline are ignored (see `Observation::matching_or_ignored`), after
+    /// it they are real mismatches. `Observation::in_grace_period` records the applied policy.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Rollout")
+    ///     .control(|| 1)
+    ///     .experiment(|| 2)
+    ///     .strict_after(Duration::from_secs(3600))
+    ///     .publish(|o: &Observation<i32, i32>| {
+    ///         assert!(o.in_grace_period());
+    ///         assert!(o.matching_or_ignored());
+    ///     })
+    ///     .run();
+    /// ```
+    pub fn strict_after(mut self, grace_period: Duration) -> Self {
+        self.settings.strict_after = Some(grace_period);
+        self
     }
 
-    #[test]
-    fn experiment_should_publish_the_results_when_publish_method_is_specified() {
-        let expected = 1;
-        Experiment::new("Test")
-            .control(|| expected)
-            .experiment(|| expected)
-            .publish(|o: &crate::Observation<i32, i32>| assert!(o.is_matching()))
-            .run();
+    /// Compare the control and experiment values by a comparison which can fail.
+    ///
+    /// Shorthand for `comparator(compare::fallible(c))`. A failed comparison is neither a match nor
+    /// a mismatch, its error is recorded as the `compare_error` of the observation.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Parsed")
+    ///     .control(|| String::from("1"))
+    ///     .experiment(|| String::from("one"))
+    ///     .try_comparator(|c: &String, e: &String| {
+    ///         Ok::<_, std::num::ParseIntError>(c.parse::<i32>()? == e.parse::<i32>()?)
+    ///     })
+    ///     .publish(|o: &Observation<String, String>| {
+    ///         assert_eq!(o.verdict(), None);
+    ///         assert!(o.compare_error().is_some());
+    ///     })
+    ///     .run();
+    /// ```
+    pub fn try_comparator<C, E>(
+        self,
+        c: C,
+    ) -> CompleteExperiment<TC, FC, TE, FE, FP, PC, compare::Fallible<C>, SC, SE>
+    where
+        C: Fn(&PC::Output, &TE) -> Result<bool, E>,
+        E: std::fmt::Display,
+    {
+        self.comparator(compare::fallible(c))
     }
 
-    #[derive(PartialEq, Copy, Clone)]
-    struct TestI64 {
-        value: i64,
+    /// Match identical `Arc`s, `Rc`s or references without comparing the values they point to.
+    ///
+    /// The comparator is only invoked for distinct pointers, saving a deep comparison when the
+    /// control and the experiment often share the value. Only valid if identical pointers imply
+    /// matching values, see `compare::PtrEqFastpath`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let cached = Arc::new(vec![1; 1_000]);
+    ///
+    /// Experiment::new("Cached")
+    ///     .control(|| cached.clone())
+    ///     .experiment(|| cached.clone())
+    ///     .with_ptr_eq_fastpath()
+    ///     .publish(|o: &Observation<Arc<Vec<i32>>, Arc<Vec<i32>>>| assert!(o.is_matching()))
+    ///     .run();
+    /// ```
+    pub fn with_ptr_eq_fastpath(
+        self,
+    ) -> CompleteExperiment<TC, FC, TE, FE, FP, PC, compare::PtrEqFastpath<FM>, SC, SE> {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            projection: self.projection,
+            comparator: compare::ptr_eq_fastpath(self.comparator),
+            sizes: self.sizes,
+            settings: self.settings,
+        }
     }
 
-    impl PartialEq<i32> for TestI64 {
-        fn eq(&self, other: &i32) -> bool {
-            self.value as i32 == *other
+    /// Compare `Result` values with unrelated error types by their `Ok` values only.
+    ///
+    /// Shorthand for `comparator(compare::ok_only())`, the error messages of both branches are
+    /// recorded as the detail of the observation.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Parsing")
+    ///     .control(|| "1".parse::<i32>())
+    ///     .experiment(|| -> Result<i32, String> { Err(String::from("not implemented")) })
+    ///     .compare_ok_only()
+    ///     .publish(|o: &Observation<_, Result<i32, String>>| {
+    ///         assert_eq!(o.detail(), Some("experiment error: not implemented"))
+    ///     })
+    ///     .run();
+    /// ```
+    pub fn compare_ok_only(
+        self,
+    ) -> CompleteExperiment<TC, FC, TE, FE, FP, PC, compare::OkOnly, SC, SE> {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            projection: self.projection,
+            comparator: compare::ok_only(),
+            sizes: self.sizes,
+            settings: self.settings,
         }
     }
 
-    #[test]
-    fn experiment_should_work_with_different_return_types_if_they_are_comparable() {
+    /// Compare the values only by their projections, e.g. a selected field.
+    ///
+    /// The recorded control value, i.e. the output of `map_control` if any, is projected by `p`
+    /// and the experiment value by the same accessor, so the recorded control has to be of the
+    /// experiment type. Shorthand for `comparator(compare::projected(p))`, use
+    /// `compare::projected_each` to project values of different types.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// struct User { id: u32, cached_at: u64 }
+    ///
+    /// Experiment::new("Same user")
+    ///     .control(|| User { id: 1, cached_at: 10 })
+    ///     .experiment(|| User { id: 1, cached_at: 20 })
+    ///     .compare_projection(|user: &User| user.id)
+    ///     .publish(|o: &Observation<User, User>| assert!(o.is_matching()))
+    ///     .run();
+    /// ```
+    pub fn compare_projection<P, K>(
+        self,
+        p: P,
+    ) -> CompleteExperiment<TC, FC, TE, FE, FP, PC, compare::Projected<P>, SC, SE>
+    where
+        P: Fn(&PC::Output) -> K,
+        K: PartialEq,
+    {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            projection: self.projection,
+            comparator: compare::projected(p),
+            sizes: self.sizes,
+            settings: self.settings,
+        }
+    }
+
+    /// Compare the canonical forms of the values, e.g. sorted collections or normalized text.
+    ///
+    /// The same canonicalization is applied to both of the values, which are then compared by the
+    /// current comparator (`PartialEq` by default), see `compare::Canonicalized`. It wraps the
+    /// comparator, so it needs to be called after `comparator`, a later `comparator` replaces it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Unordered")
+    ///     .control(|| vec!["b", "a"])
+    ///     .experiment(|| vec!["a", "b"])
+    ///     .canonicalize(|v: &Vec<&str>| {
+    ///         let mut v = v.clone();
+    ///         v.sort();
+    ///         v
+    ///     })
+    ///     .publish(|o: &Observation<Vec<&str>, Vec<&str>>| assert!(o.is_matching()))
+    ///     .run();
+    /// ```
+    pub fn canonicalize<C, K>(
+        self,
+        c: C,
+    ) -> CompleteExperiment<TC, FC, TE, FE, FP, PC, compare::Canonicalized<C, FM>, SC, SE>
+    where
+        C: Fn(&TE) -> K,
+        FM: Comparator<K, K>,
+    {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            projection: self.projection,
+            comparator: compare::canonicalized(c, self.comparator),
+            sizes: self.sizes,
+            settings: self.settings,
+        }
+    }
+
+    /// Compare the collections sorted by the key, e.g. query results differing in order only.
+    ///
+    /// Replaces the comparator like `comparator`, see `compare::SortedBy`. A key which is not
+    /// ordered, e.g. `NaN`, fails the comparison and is recorded as the `compare_error`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Query")
+    ///     .control(|| vec![(2, "b"), (1, "a")])
+    ///     .experiment(|| vec![(1, "a"), (2, "b")])
+    ///     .compare_sorted_by(|(id, _): &(u32, &str)| *id)
+    ///     .publish(|o: &Observation<Vec<(u32, &str)>, Vec<(u32, &str)>>| assert!(o.is_matching()))
+    ///     .run();
+    /// ```
+    pub fn compare_sorted_by<F>(
+        self,
+        key: F,
+    ) -> CompleteExperiment<TC, FC, TE, FE, FP, PC, compare::SortedBy<F>, SC, SE>
+    where
+        compare::SortedBy<F>: Comparator<PC::Output, TE>,
+    {
+        self.comparator(compare::sorted_by(key))
+    }
+
+    /// Record the diff of the `Debug` representations of the mismatching values.
+    ///
+    /// The diff is computed for the mismatches only and exposed as `Observation::diff`, see
+    /// `compare::diff::Diffed`. It wraps the comparator, so it needs to be called after
+    /// `comparator`, a later `comparator` replaces it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Diffed")
+    ///     .control(|| vec!["a", "b"])
+    ///     .experiment(|| vec!["a", "c"])
+    ///     .with_diff()
+    ///     .publish(|o: &Observation<Vec<&str>, Vec<&str>>| {
+    ///         assert!(o.diff().unwrap().contains("+    \"c\","))
+    ///     })
+    ///     .run();
+    /// ```
+    #[cfg(feature = "similar")]
+    pub fn with_diff(
+        self,
+    ) -> CompleteExperiment<TC, FC, TE, FE, FP, PC, compare::diff::Diffed<FM>, SC, SE>
+    where
+        PC::Output: std::fmt::Debug,
+        TE: std::fmt::Debug,
+        FM: Comparator<PC::Output, TE>,
+    {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            projection: self.projection,
+            comparator: compare::diff::diffed(self.comparator),
+            sizes: self.sizes,
+            settings: self.settings,
+        }
+    }
+
+    /// Compare the values by their digests produced by the hasher built by the `BuildHasher`.
+    ///
+    /// Shorthand for `comparator(compare::by_hash(hasher))`, cheaper for large values than a
+    /// direct comparison. Both digests are recorded as the detail of the observation.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Large blobs")
+    ///     .control(|| vec![0u8; 1 << 20])
+    ///     .experiment(|| vec![0u8; 1 << 20])
+    ///     .compare_by_hash(BuildHasherDefault::<DefaultHasher>::default())
+    ///     .publish(|o: &Observation<Vec<u8>, Vec<u8>>| assert!(o.is_matching()))
+    ///     .run();
+    /// ```
+    pub fn compare_by_hash<H>(
+        self,
+        hasher: H,
+    ) -> CompleteExperiment<TC, FC, TE, FE, FP, PC, compare::ByHash<H>, SC, SE>
+    where
+        H: std::hash::BuildHasher,
+    {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: self.publish,
+            projection: self.projection,
+            comparator: compare::by_hash(hasher),
+            sizes: self.sizes,
+            settings: self.settings,
+        }
+    }
+
+    /// Enable or disable entering a `tracing` span for each of the branches.
+    ///
+    /// The spans are named after the branch (`control` or `experiment`) and carry the experiment
+    /// name in the `experiment` field. Spans are enabled by default.
+    #[cfg(feature = "tracing")]
+    pub fn with_spans(mut self, enabled: bool) -> Self {
+        self.settings.spans = enabled;
+        self
+    }
+
+    /// Enable or disable capturing the file and line where a branch panicked.
+    ///
+    /// A panic hook recording the location is installed around each branch and the previous hook
+    /// is restored afterward, see `Observation::experiment_panic_location`. Disabled by default,
+    /// since the panic hook is global to the process.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// Experiment::new("Located")
+    ///     .control(|| 1)
+    ///     .experiment(|| -> i32 { panic!("Oops") })
+    ///     .capture_panic_locations(true)
+    ///     .publish(|o: &Observation<i32, i32>| {
+    ///         let (file, _line) = o.experiment_panic_location().unwrap();
+    ///         assert!(file.ends_with(".rs"));
+    ///     })
+    ///     .run();
+    /// ```
+    pub fn capture_panic_locations(mut self, enabled: bool) -> Self {
+        self.settings.panic_locations = enabled;
+        self
+    }
+
+    /// Consider both of the branches panicking a match, e.g. in resilience experiments.
+    ///
+    /// A panic on one side only is still a mismatch. The `Err` values are compared by the
+    /// comparator, see `compare::OkOnly` to match any two errors.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let result = std::panic::catch_unwind(|| {
+    ///     Experiment::new("Failing alike")
+    ///         .control(|| -> i32 { panic!("Unavailable") })
+    ///         .experiment(|| -> i32 { panic!("Unavailable") })
+    ///         .match_on_both_failed(true)
+    ///         .publish(|o: &Observation<i32, i32>| assert!(o.is_matching()))
+    ///         .run()
+    /// });
+    ///
+    /// assert!(result.is_err());
+    /// ```
+    pub fn match_on_both_failed(mut self, enabled: bool) -> Self {
+        self.settings.match_on_both_failed = enabled;
+        self
+    }
+
+    /// Switch the experiment off using the environment variable, e.g. in an emergency.
+    ///
+    /// The variable is read before each run, nothing is cached, so that it can be flipped while the
+    /// program is running. The values `0`, `false`, `no` and `off` (trimmed, case insensitive)
+    /// skip the experiment, any other value or an unset variable leave it enabled. The **control**
+    /// is always run.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::Experiment;
+    ///
+    /// std::env::set_var("SCIENTISTO_NEW_PRICING", "false");
+    ///
+    /// let result = Experiment::new("New pricing")
+    ///     .control(|| 1)
+    ///     .experiment(|| -> i32 { panic!("Switched off") })
+    ///     .enabled_by_env("SCIENTISTO_NEW_PRICING")
+    ///     .run();
+    ///
+    /// assert_eq!(result, 1);
+    /// ```
+    pub fn enabled_by_env(mut self, variable: impl Into<String>) -> Self {
+        self.settings.enabled_by_env = Some(variable.into());
+        self
+    }
+
+    /// Track the ratio of the matching runs over a sliding window of the last `window` runs.
+    ///
+    /// The window is lock-free, see `match_rate`. Uncompared runs are not tracked.
+    pub fn track_match_rate(mut self, window: usize) -> Self {
+        self.settings.match_window = Some(std::sync::Arc::new(MatchWindow::new(window)));
+        self
+    }
+
+    /// Ratio of the matching runs within the tracked window, e.g. to reduce the sampling once the
+    /// experiment is stable.
+    ///
+    /// Returns `NaN` until a run is recorded or if the match rate is not tracked, so that any
+    /// threshold comparison fails.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::Experiment;
+    ///
+    /// let experiment = Experiment::new("Stabilizing")
+    ///     .control(|| 1)
+    ///     .experiment(|| 1)
+    ///     .track_match_rate(1000);
+    ///
+    /// experiment.run();
+    ///
+    /// assert!(experiment.match_rate() > 0.999);
+    /// ```
+    pub fn match_rate(&self) -> f64 {
+        self.settings.match_rate()
+    }
+
+    /// Call the publisher at most `max_per_sec` times per second, dropping the excess observations.
+    ///
+    /// Unlike the sampling, the experiment still runs, only the publishing is throttled by a token
+    /// bucket allowing bursts up to the rate. See `dropped_publications` for the dropped count.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let experiment = Experiment::new("Throttled")
+    ///     .control(|| 1)
+    ///     .experiment(|| 1)
+    ///     .publish_rate_limit(1.0)
+    ///     .publish(|o: &Observation<i32, i32>| println!("{:?}", o.summary()));
+    ///
+    /// experiment.run();
+    /// experiment.run();
+    ///
+    /// assert_eq!(experiment.dropped_publications(), 1);
+    /// ```
+    pub fn publish_rate_limit(mut self, max_per_sec: f64) -> Self {
+        self.settings.publish_limit = Some(std::sync::Arc::new(TokenBucket::new(max_per_sec)));
+        self
+    }
+
+    /// Take each run of the experiment branch from the budget shared with other experiments.
+    ///
+    /// The budget is consulted after the sampling, so that the runs not sampled do not spend it,
+    /// see `SamplingBudget`.
+    pub fn with_budget(mut self, budget: &crate::budget::SamplingBudget) -> Self {
+        self.settings.budget = Some(budget.clone());
+        self
+    }
+
+    /// Number of the observations dropped by the `publish_rate_limit`.
+    pub fn dropped_publications(&self) -> u64 {
+        self.settings.dropped_publications()
+    }
+
+    /// Run the experiment in the given percentage (`0.0..=100.0`) of the runs only.
+    ///
+    /// The **control** is always run. Use `with_seed` to make the sampled runs reproducible.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::Experiment;
+    ///
+    /// let experiment = Experiment::new("Rarely")
+    ///     .control(|| 1)
+    ///     .experiment(|| 1)
+    ///     .run_percentage(5.0);
+    ///
+    /// assert_eq!(experiment.run(), 1);
+    /// ```
+    pub fn run_percentage(mut self, percentage: f64) -> Self {
+        self.settings.sample_rate(percentage / 100.0);
+        self
+    }
+
+    /// Seed the random generator deciding the sampled runs, so that they are reproducible.
+    ///
+    /// Without a seed, each decision uses a fresh random number. Applies to both `run_percentage`
+    /// and `adaptive_sample`.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.settings.seed(seed);
+        self
+    }
+
+    /// Use the random generator deciding the sampled runs, e.g. a deterministic or a cryptographic
+    /// source, instead of the built-in one.
+    ///
+    /// Applies to both `run_percentage` and `adaptive_sample`, replacing `with_seed`. The generator
+    /// is shared by all of the runs behind a mutex, so it needs to be `Send`, but not `Sync`.
+    /// Concurrent runs interleave their draws from the single sequence.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::Experiment;
+    ///
+    /// /// Generator of the maximal numbers, never sampling a partial rate.
+    /// struct Max;
+    ///
+    /// impl rand_core::RngCore for Max {
+    ///     fn next_u32(&mut self) -> u32 { u32::MAX }
+    ///     fn next_u64(&mut self) -> u64 { u64::MAX }
+    ///     fn fill_bytes(&mut self, dest: &mut [u8]) { dest.fill(u8::MAX) }
+    ///     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+    ///         self.fill_bytes(dest);
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// Experiment::new("Never")
+    ///     .control(|| 1)
+    ///     .experiment(|| -> i32 { unreachable!() })
+    ///     .run_percentage(99.0)
+    ///     .with_rng(Max)
+    ///     .run();
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn with_rng<R>(mut self, rng: R) -> Self
+    where
+        R: rand_core::RngCore + Send + 'static,
+    {
+        self.settings.rng(rng);
+        self
+    }
+
+    /// Sample the runs of the experiment with a probability decreasing as the load increases.
+    ///
+    /// Before each run, the `load_fn` is called and the experiment is run with the probability
+    /// `max_rate * (1.0 - load)`, where both the `load` and the `max_rate` are clamped into the
+    /// `0.0..=1.0` range. An idle system (`0.0`) runs the experiment at the `max_rate`, a fully
+    /// loaded one (`1.0`, or a `NaN` load) never runs it. The **control** is always run.
+    ///
+    /// The sampling applies to all of the runs, e.g. `run`, `run_if`, `run_safe` and `run_timed`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    /// use std::sync::Arc;
+    /// use scientisto::Experiment;
+    ///
+    /// let load = Arc::new(AtomicU64::new(1.0_f64.to_bits()));
+    /// let current = load.clone();
+    ///
+    /// let result = Experiment::new("Under load")
+    ///     .control(|| 1)
+    ///     .experiment(|| -> i32 { panic!("Never run under the full load") })
+    ///     .adaptive_sample(move || f64::from_bits(current.load(Ordering::Relaxed)), 0.5)
+    ///     .run();
+    ///
+    /// assert_eq!(result, 1);
+    /// ```
+    pub fn adaptive_sample<L>(mut self, load_fn: L, max_rate: f64) -> Self
+    where
+        L: Fn() -> f64 + Send + Sync + 'static,
+    {
+        self.settings.adaptive_sample(load_fn, max_rate);
+        self
+    }
+
+    /// Opt out of publishing the observations explicitly.
+    ///
+    /// The publisher is a part of the type of the experiment: `DefaultPublisher` until specified,
+    /// the given publisher after `publish` and `NoopPublisher` after `no_publish`. Running with the
+    /// `DefaultPublisher` forwards to `set_default_publisher`, which discards the observations when
+    /// unset, so `no_publish` makes such an omission intentional and visible in the type.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::publisher::NoopPublisher;
+    /// use scientisto::sync_experiment::CompleteExperiment;
+    /// use scientisto::Experiment;
+    ///
+    /// let experiment: CompleteExperiment<_, _, _, _, NoopPublisher> = Experiment::new("Silent")
+    ///     .control(|| 1)
+    ///     .experiment(|| 1)
+    ///     .no_publish();
+    ///
+    /// assert_eq!(experiment.run(), 1);
+    /// ```
+    pub fn no_publish(self) -> CompleteExperiment<TC, FC, TE, FE, NoopPublisher, PC, FM, SC, SE> {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: NoopPublisher,
+            projection: self.projection,
+            comparator: self.comparator,
+            sizes: self.sizes,
+            settings: self.settings,
+        }
+    }
+
+    /// Route each observation to one of the publishers selected at runtime, see `PublishRouter`.
+    #[allow(clippy::type_complexity)]
+    pub fn publish_router<K, R>(
+        self,
+        router: PublishRouter<'_, PC::Output, TE, K, R>,
+    ) -> CompleteExperiment<TC, FC, TE, FE, PublishRouter<'_, PC::Output, TE, K, R>, PC, FM, SC, SE>
+    where
+        K: Eq + std::hash::Hash,
+        R: Fn(&crate::Observation<PC::Output, TE>) -> Option<K>,
+    {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: router,
+            projection: self.projection,
+            comparator: self.comparator,
+            sizes: self.sizes,
+            settings: self.settings,
+        }
+    }
+
+    pub fn publish<F>(self, f: F) -> CompleteExperiment<TC, FC, TE, FE, F, PC, FM, SC, SE>
+    where
+        F: Fn(&crate::Observation<PC::Output, TE>),
+    {
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: self.experiment,
+            publish: f,
+            projection: self.projection,
+            comparator: self.comparator,
+            sizes: self.sizes,
+            settings: self.settings,
+        }
+    }
+
+    /// Run the experiment, returning the control value.
+    pub fn run(&self) -> TC
+    where
+        FM: Comparator<PC::Output, TE>,
+        FP: Publisher<PC::Output, TE>,
+    {
+        self.run_if(|| true)
+    }
+
+    pub fn run_if<P>(&self, predicate: P) -> TC
+    where
+        FM: Comparator<PC::Output, TE>,
+        FP: Publisher<PC::Output, TE>,
+        P: Fn() -> bool,
+    {
+        match self
+            .conduct(
+                || {
+                    if predicate() {
+                        self.settings.skip_reason()
+                    } else {
+                        Some(SkipReason::Predicate)
+                    }
+                },
+                false,
+                Some(&self.comparator),
+            )
+            .0
+        {
+            Ok(result) => result,
+            Err(e) => self.recover(e),
+        }
+    }
+
+    /// Chain the next experiment, run only if this one matched, e.g. an expensive second stage.
+    ///
+    /// Each of the experiments publishes its own observation. Running the chain returns the
+    /// control value of this experiment, the control value of the next one is discarded. An
+    /// experiment which is not sampled does not match, so the next one is skipped as well.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let staged = Experiment::new("Cheap")
+    ///     .control(|| 1)
+    ///     .experiment(|| 2)
+    ///     .then(
+    ///         Experiment::new("Expensive")
+    ///             .control(|| 1)
+    ///             .experiment(|| unreachable!("gated by the mismatch of the cheap stage"))
+    ///             .publish(|_: &Observation<i32, i32>| {}),
+    ///     );
+    ///
+    /// assert_eq!(staged.run(), 1);
+    /// ```
+    pub fn then<B>(self, next: B) -> Staged<Self, B>
+    where
+        FM: Comparator<PC::Output, TE>,
+        FP: Publisher<PC::Output, TE>,
+        B: Stage,
+    {
+        Staged::new(self, next)
+    }
+
+    /// Run the experiment, measuring the duration of both of the branches.
+    ///
+    /// Returns the control value together with the durations of the **control** and the
+    /// **experiment**, which are also recorded in the published observation. The plain `run`
+    /// does not measure anything. If the experiment is skipped, e.g. by `enabled_by_env` or the
+    /// sampling, only the control is measured and the experiment duration is zero. Nothing is
+    /// measured in the disabled build, both of the durations are zero.
+    ///
+    /// # Panics
+    /// Panics if the **control** function panics, just like `run`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let (result, control, experiment) = Experiment::new("Timed")
+    ///     .control(|| 1)
+    ///     .experiment(|| 1)
+    ///     .publish(|o: &Observation<i32, i32>| assert!(o.control_duration().is_some()))
+    ///     .run_timed();
+    ///
+    /// println!("control took {:?}, experiment took {:?}", control, experiment);
+    /// ```
+    pub fn run_timed(&self) -> (TC, Duration, Duration)
+    where
+        FM: Comparator<PC::Output, TE>,
+        FP: Publisher<PC::Output, TE>,
+    {
+        match self.conduct(|| self.settings.skip_reason(), true, Some(&self.comparator)) {
+            (Ok(result), Some((control, experiment))) => (result, control, experiment),
+            (Err(e), durations) => {
+                let (control, experiment) = durations.unwrap_or_default();
+                (self.recover(e), control, experiment)
+            }
+            (Ok(_), None) => unreachable!("the conducted experiment is always timed"),
+        }
+    }
+
+    /// Run the experiment without ever unwinding out of the call.
+    ///
+    /// Instead of resuming the panic of the **control** function, the boxed panic payload is
+    /// returned as an `Err`, unless mapped to a value by `map_control_panic` or `with_default`. The
+    /// panic of the **experiment** function is recorded in the observation as usual.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::Experiment;
+    ///
+    /// let result = Experiment::new("Supervised")
+    ///     .control(|| -> i32 { panic!("Oops") })
+    ///     .experiment(|| 1)
+    ///     .run_safe();
+    ///
+    /// assert!(result.is_err());
+    /// ```
+    pub fn run_safe(&self) -> Result<TC, Box<dyn Any + Send>>
+    where
+        FM: Comparator<PC::Output, TE>,
+        FP: Publisher<PC::Output, TE>,
+    {
+        self.conduct(
+            || self.settings.skip_reason(),
+            false,
+            Some(&self.comparator),
+        )
+        .0
+        .or_else(|e| match &self.control.recover {
+            Some(recover) => Ok(recover(e)),
+            None => Err(e),
+        })
+    }
+
+    /// Run both of the branches and measure them without comparing their values.
+    ///
+    /// The values of the **control** and **experiment** need not be comparable at all, the
+    /// published observation records the durations of both of the branches and reports `None`
+    /// from `Observation::verdict`. The comparator of the experiment is not used.
+    ///
+    /// # Panics
+    /// Panics if the **control** function panics, just like `run`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// struct Report;
+    ///
+    /// let result = Experiment::new("Timing only")
+    ///     .control(|| 1)
+    ///     .experiment(|| Report)
+    ///     .publish(|o: &Observation<i32, Report>| {
+    ///         assert_eq!(o.verdict(), None);
+    ///         assert!(o.experiment_duration().is_some());
+    ///     })
+    ///     .run_no_compare();
+    ///
+    /// assert_eq!(result, 1);
+    /// ```
+    pub fn run_no_compare(&self) -> TC
+    where
+        FP: Publisher<PC::Output, TE>,
+    {
+        match self.conduct(|| self.settings.skip_reason(), true, None).0 {
+            Ok(result) => result,
+            Err(e) => self.recover(e),
+        }
+    }
+
+    /// Run the experiment whose verdict is decided later, e.g. by a downstream event.
+    ///
+    /// The values are not compared, the observation is published once the returned handle is
+    /// confirmed, or dropped undecided. If the experiment is not sampled, the handle has nothing to
+    /// publish.
+    ///
+    /// # Panics
+    /// Panics if the **control** function panics, the observation is published undecided first.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let experiment = Experiment::new("Confirmed later")
+    ///     .control(|| 1)
+    ///     .experiment(|| 2)
+    ///     .publish(|o: &Observation<i32, i32>| assert_eq!(o.verdict(), Some(true)));
+    ///
+    /// let (result, handle) = experiment.run_deferred();
+    /// // ... the downstream consumer accepted the experiment value
+    /// handle.confirm(true);
+    ///
+    /// assert_eq!(result, 1);
+    /// ```
+    pub fn run_deferred(&self) -> (TC, VerdictHandle<'_, PC::Output, TE, FP>)
+    where
+        PC::Output: Clone,
+        FP: Publisher<PC::Output, TE>,
+    {
+        if cfg!(scientisto_disabled) {
+            return ((self.control.f)(), VerdictHandle::skipped(&self.publish));
+        }
+
+        if !self.settings.should_run() {
+            return match self.execute_control().0 {
+                Ok(result) => (result, VerdictHandle::skipped(&self.publish)),
+                Err(e) => (self.recover(e), VerdictHandle::skipped(&self.publish)),
+            };
+        }
+
+        let (observation, retained, _) = self.observe_branches(false, None);
+
+        match (&observation.control, retained) {
+            (Ok(recorded), Some(retained)) => {
+                let result = self.projection.restore(recorded.clone(), retained);
+                let handle = VerdictHandle::new(observation, &self.publish, &self.settings);
+
+                (result, handle)
+            }
+            _ => {
+                self.publish.publish(&observation);
+
+                match observation.control {
+                    Err(e) => (self.recover(e), VerdictHandle::skipped(&self.publish)),
+                    Ok(_) => unreachable!("the raw control value is retained on success"),
+                }
+            }
+        }
+    }
+
+    /// Conduct the experiment, returning the control result and the durations if timed.
+    ///
+    /// The values are compared using the comparator, if any.
+    fn conduct<P>(
+        &self,
+        skip: P,
+        timed: bool,
+        comparator: Option<&dyn Comparator<PC::Output, TE>>,
+    ) -> (std::thread::Result<TC>, Option<(Duration, Duration)>)
+    where
+        FP: Publisher<PC::Output, TE>,
+        P: Fn() -> Option<SkipReason>,
+    {
+        let (result, durations, _) = self.conduct_verdict(skip, timed, comparator);
+        (result, durations)
+    }
+
+    /// Conduct the experiment like `conduct`, also returning whether the observation matched.
+    #[allow(clippy::type_complexity)]
+    fn conduct_verdict<P>(
+        &self,
+        skip: P,
+        timed: bool,
+        comparator: Option<&dyn Comparator<PC::Output, TE>>,
+    ) -> (std::thread::Result<TC>, Option<(Duration, Duration)>, bool)
+    where
+        FP: Publisher<PC::Output, TE>,
+        P: Fn() -> Option<SkipReason>,
+    {
+        if cfg!(scientisto_disabled) {
+            let durations = timed.then_some((Duration::ZERO, Duration::ZERO));
+            return (Ok((self.control.f)()), durations, false);
+        }
+
+        match skip() {
+            None => {
+                let (observation, retained, durations) = self.observe_branches(timed, comparator);
+
+                self.settings.record(&observation);
+                self.settings.publish(&self.publish, &observation);
+
+                let matching = observation.is_matching();
+                (
+                    self.restore(observation.control, retained),
+                    durations,
+                    matching,
+                )
+            }
+            Some(reason) if self.settings.publish_skips => {
+                let ((control, _), duration) = measure(timed, || self.execute_control());
+                let (control, retained) = self.split(control);
+                let observation = crate::Observation::skipped(self.name, control, reason)
+                    .versioned(self.settings.version)
+                    .tagged(&self.settings.tags);
+
+                self.settings.publish(&self.publish, &observation);
+
+                (
+                    self.restore(observation.control, retained),
+                    duration.map(|control| (control, Duration::ZERO)),
+                    false,
+                )
+            }
+            Some(_) => {
+                let ((control, _), duration) = measure(timed, || self.execute_control());
+                (
+                    control,
+                    duration.map(|control| (control, Duration::ZERO)),
+                    false,
+                )
+            }
+        }
+    }
+
+    /// Execute both of the branches, collecting the observation with the retained control value.
+    #[allow(clippy::type_complexity)]
+    fn observe_branches(
+        &self,
+        timed: bool,
+        comparator: Option<&dyn Comparator<PC::Output, TE>>,
+    ) -> (
+        crate::Observation<PC::Output, TE>,
+        Option<PC::Retained>,
+        Option<(Duration, Duration)>,
+    ) {
+        let ((((control, control_location), control_allocs), control_duration), control_wallclock) =
+            stamped(|| measure(timed, || counted(|| self.execute_control())));
+        let (control, retained) = self.split(control);
+        let experiment = || {
+            execute(
+                &self.settings,
+                self.name,
+                Branch::Experiment,
+                &self.experiment.f,
+            )
+        };
+        let (
+            (((experiment, experiment_location), experiment_allocs), experiment_duration),
+            experiment_wallclock,
+        ) = stamped(|| measure(timed, || counted(experiment)));
+        let durations = control_duration.zip(experiment_duration);
+
+        let mut observation = match comparator {
+            Some(comparator) => {
+                crate::Observation::compared_by(self.name, control, experiment, comparator)
+            }
+            None => crate::Observation::uncompared(self.name, control, experiment),
+        };
+        if let Some((control, experiment)) = durations {
+            observation = observation.timed(control, experiment);
+        }
+        let (control_size, experiment_size) = self.sizes.measure(
+            observation.control.as_ref().ok(),
+            observation.experiment.as_ref().ok(),
+        );
+        observation = observation
+            .sized(control_size, experiment_size)
+            .graced(self.settings.in_grace_period())
+            .versioned(self.settings.version)
+            .tagged(&self.settings.tags)
+            .matching_both_failed(self.settings.match_on_both_failed)
+            .panicked_at(control_location, experiment_location)
+            .stamped(control_wallclock, experiment_wallclock)
+            .counted(control_allocs, experiment_allocs);
+
+        (observation, retained, durations)
+    }
+
+    /// Split the control value into the recorded and the retained part, unless it panicked.
+    fn split(
+        &self,
+        control: std::thread::Result<TC>,
+    ) -> (std::thread::Result<PC::Output>, Option<PC::Retained>) {
+        match control {
+            Ok(value) => {
+                let (recorded, retained) = self.projection.split(value);
+                (Ok(recorded), Some(retained))
+            }
+            Err(e) => (Err(e), None),
+        }
+    }
+
+    /// Restore the raw control value from the recorded and the retained part.
+    fn restore(
+        &self,
+        recorded: std::thread::Result<PC::Output>,
+        retained: Option<PC::Retained>,
+    ) -> std::thread::Result<TC> {
+        match (recorded, retained) {
+            (Ok(recorded), Some(retained)) => Ok(self.projection.restore(recorded, retained)),
+            (Err(e), _) => Err(e),
+            (Ok(_), None) => unreachable!("the raw control value is retained on success"),
+        }
+    }
+
+    fn execute_control(&self) -> (std::thread::Result<TC>, Option<(String, u32)>) {
+        execute(&self.settings, self.name, Branch::Control, &self.control.f)
+    }
+
+    /// Map the panic of the control by `map_control_panic`, resuming it otherwise.
+    fn recover(&self, payload: Box<dyn Any + Send>) -> TC {
+        match &self.control.recover {
+            Some(recover) => recover(payload),
+            None => self.settings.resume(payload),
+        }
+    }
+}
+
+impl<FC, FE, FP> CompleteExperiment<(), FC, (), FE, FP, Identity, Equality, NoSize, NoSize>
+where
+    FC: Fn() + std::panic::UnwindSafe,
+    FE: Fn() + std::panic::UnwindSafe,
+{
+    /// Compare the side effects of the branches returning `()` instead of their values.
+    ///
+    /// The observer is called right after each of the branches to capture the observable state,
+    /// e.g. by draining a sink the branch wrote to, and the captured states are compared using
+    /// `PartialEq` and published. Call `comparator` afterwards to compare the states otherwise.
+    /// The run still returns `()`. The observation of the control is recorded like a `map_control`
+    /// projection, a panic of the observer after the control propagates out of the run.
+    ///
+    /// It is available only before `comparator`, `map_control` and the size measurements, since
+    /// those apply to the `()` values the observer replaces.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::sync::Mutex;
+    /// use scientisto::{Experiment,Observation};
+    ///
+    /// let sink = Mutex::new(Vec::new());
+    ///
+    /// Experiment::new("Void")
+    ///     .control(|| sink.lock().unwrap().extend([1, 2]))
+    ///     .experiment(|| sink.lock().unwrap().extend([1, 2]))
+    ///     .observe(|| std::mem::take(&mut *sink.lock().unwrap()))
+    ///     .publish(|o: &Observation<Vec<i32>, Vec<i32>>| assert!(o.is_matching()))
+    ///     .run();
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn observe<S, O>(
+        self,
+        o: O,
+    ) -> CompleteExperiment<
+        (),
+        FC,
+        S,
+        impl Fn() -> S + std::panic::UnwindSafe,
+        FP,
+        Mapped<impl Fn(&()) -> S>,
+    >
+    where
+        O: Fn() -> S + Clone + std::panic::UnwindSafe,
+    {
+        let experiment = self.experiment.f;
+        let after_experiment = o.clone();
+
+        CompleteExperiment {
+            name: self.name,
+            control: self.control,
+            experiment: Executable::new(move || {
+                experiment();
+                after_experiment()
+            }),
+            publish: self.publish,
+            projection: Mapped(move |_: &()| o()),
+            comparator: Equality,
+            sizes: self.sizes,
+            settings: self.settings,
+        }
+    }
+}
+
+impl<TC, FC, TE, FE, FP, FM, SC, SE> CompleteExperiment<TC, FC, TE, FE, FP, Identity, FM, SC, SE>
+where
+    FC: Fn() -> TC + std::panic::UnwindSafe,
+    FE: Fn() -> TE + std::panic::UnwindSafe,
+    SC: Size<TC>,
+    SE: Size<TE>,
+{
+    /// Run the experiment, handing the control value to the function by reference.
+    ///
+    /// Unlike `run`, the control value is not moved out of the published observation back to the
+    /// caller, the function inspects it in place. This saves the copies of a large control value
+    /// stored inline, e.g. a big array, which only needs to be inspected. The value is dropped
+    /// once the function returns, a heap-allocated value (e.g. `Box`) is cheap to move anyway.
+    ///
+    /// # Panics
+    /// Panics if the **control** function panics, just like `run`, without calling the function,
+    /// unless the panic is mapped by `map_control_panic`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::Experiment;
+    ///
+    /// let checksum = Experiment::new("Large")
+    ///     .control(|| [1_u8; 4096])
+    ///     .experiment(|| [1_u8; 4096])
+    ///     .run_with(|control| control.iter().map(|&b| u64::from(b)).sum::<u64>());
+    ///
+    /// assert_eq!(checksum, 4096);
+    /// ```
+    pub fn run_with<R>(&self, f: impl FnOnce(&TC) -> R) -> R
+    where
+        FM: Comparator<TC, TE>,
+        FP: Publisher<TC, TE>,
+    {
+        match self.observed().control {
+            Ok(ref control) => f(control),
+            Err(e) => f(&self.recover(e)),
+        }
+    }
+
+    /// Run the experiment, returning the control value together with the observation by value.
+    ///
+    /// The moves happen in this order: the observation is published first, holding the control
+    /// value, then the control is moved out of it to the caller and the observation is handed
+    /// back, so no `Clone` is needed. The returned observation keeps everything else, e.g. the
+    /// verdict and the experiment panic payload, while its control is an `Err` with the
+    /// `observation::MovedOut` payload. A skipped run is returned as the observation with the
+    /// `skip_reason`, published only with `publish_skips`. The disabled build never publishes it,
+    /// the run is skipped as `SkipReason::Disabled`.
+    ///
+    /// # Panics
+    /// Panics if the **control** function panics, once the observation is published, unless
+    /// mapped by `map_control_panic` or `with_default` like in `run`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use scientisto::Experiment;
+    ///
+    /// let (control, observation) = Experiment::new("Observed")
+    ///     .control(|| String::from("value"))
+    ///     .experiment(|| String::from("other"))
+    ///     .run_observed();
+    ///
+    /// assert_eq!(control, "value");
+    /// assert!(!observation.is_matching());
+    /// ```
+    pub fn run_observed(self) -> (TC, crate::Observation<TC, TE>)
+    where
+        FM: Comparator<TC, TE>,
+        FP: Publisher<TC, TE>,
+    {
+        let mut observation = self.observed();
+        let control = std::mem::replace(
+            &mut observation.control,
+            Err(Box::new(crate::observation::MovedOut)),
+        );
+
+        match control {
+            Ok(control) => (control, observation),
+            Err(e) => (self.recover(e), observation),
+        }
+    }
+
+    /// Conduct the experiment, publishing and returning the observation of the run.
+    fn observed(&self) -> crate::Observation<TC, TE>
+    where
+        FM: Comparator<TC, TE>,
+        FP: Publisher<TC, TE>,
+    {
+        if cfg!(scientisto_disabled) {
+            let control = Ok((self.control.f)());
+            return crate::Observation::skipped(self.name, control, SkipReason::Disabled);
+        }
+
+        match self.settings.skip_reason() {
+            None => {
+                let (observation, _, _) = self.observe_branches(false, Some(&self.comparator));
+                self.settings.record(&observation);
+                self.settings.publish(&self.publish, &observation);
+                observation
+            }
+            Some(reason) => {
+                let observation =
+                    crate::Observation::skipped(self.name, self.execute_control().0, reason)
+                        .versioned(self.settings.version)
+                        .tagged(&self.settings.tags);
+                if self.settings.publish_skips {
+                    self.settings.publish(&self.publish, &observation);
+                }
+                observation
+            }
+        }
+    }
+}
+
+impl<TC, FC, TE, FE, FP, PC, FM, SC, SE> Stage
+    for CompleteExperiment<TC, FC, TE, FE, FP, PC, FM, SC, SE>
+where
+    FC: Fn() -> TC + std::panic::UnwindSafe,
+    FE: Fn() -> TE + std::panic::UnwindSafe,
+    PC: Projection<TC>,
+    SC: Size<PC::Output>,
+    SE: Size<TE>,
+    FM: Comparator<PC::Output, TE>,
+    FP: Publisher<PC::Output, TE>,
+{
+    type Output = TC;
+
+    fn run_stage(&self) -> (TC, bool) {
+        match self.conduct_verdict(
+            || self.settings.skip_reason(),
+            false,
+            Some(&self.comparator),
+        ) {
+            (Ok(result), _, matching) => (result, matching),
+            (Err(e), _, matching) => (self.recover(e), matching),
+        }
+    }
+}
+
+/// Execute the branch catching its panic, capturing the location of the panic if configured.
+fn execute<R>(
+    settings: &Settings,
+    name: &'static str,
+    branch: Branch,
+    f: &impl Fn() -> R,
+) -> (std::thread::Result<R>, Option<(String, u32)>) {
+    in_branch(settings, name, branch, || {
+        if settings.panic_locations {
+            panic_location::capture(|| catch_unwind(AssertUnwindSafe(f)))
+        } else {
+            (catch_unwind(AssertUnwindSafe(f)), None)
+        }
+    })
+}
+
+/// Execute the function, measuring its duration if timed.
+fn measure<R>(timed: bool, f: impl FnOnce() -> R) -> (R, Option<Duration>) {
+    if timed {
+        let start = Instant::now();
+        let result = f();
+        (result, Some(start.elapsed()))
+    } else {
+        (f(), None)
+    }
+}
+
+/// Define and run an `Experiment` in a single expression.
+///
+/// The `control` and `experiment` expressions are wrapped into closures and evaluated by the
+/// experiment, the optional `publish` argument is the publisher and the optional `run_if`
+/// argument the predicate deciding whether to run the experiment. Evaluates to the control value.
+///
+/// # Examples
+/// ```rust
+/// use scientisto::{experiment, Observation};
+///
+/// fn production() -> i32 { 1 }
+/// fn alternative() -> i32 { 1 }
+///
+/// let result = experiment!(
+///     "Concise",
+///     control = production(),
+///     experiment = alternative(),
+///     publish = |o: &Observation<i32, i32>| assert!(o.is_matching()),
+///     run_if = || true,
+/// );
+///
+/// assert_eq!(result, 1);
+/// ```
+#[macro_export]
+macro_rules! experiment {
+    (@predicate) => {
+        || true
+    };
+    (@predicate $predicate:expr) => {
+        $predicate
+    };
+    (
+        $name:expr,
+        control = $control:expr,
+        experiment = $experiment:expr
+        $(, publish = $publish:expr)?
+        $(, run_if = $predicate:expr)?
+        $(,)?
+    ) => {
+        $crate::Experiment::new($name)
+            .control(|| $control)
+            .experiment(|| $experiment)
+            $(.publish($publish))?
+            .run_if($crate::experiment!(@predicate $($predicate)?))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn experiment_should_derive_the_debug_trait() {
+        let experiment = Experiment::new("empty experiment");
+
+        assert_ne!(format!("{:?}", experiment), "");
+    }
+
+    #[test]
+    #[should_panic]
+    fn experiment_should_panic_on_empty_string_name() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+
+        let _ = Experiment::new("");
+    }
+
+    #[test]
+    fn experiment_should_return_name_if_it_is_valid() {
+        let actual_name: &str = "Any ľšýžľš is OK";
+        let experiment = Experiment::new(actual_name);
+
+        assert_eq!(experiment.name(), actual_name);
+    }
+
+    #[test]
+    fn experiment_should_return_name_with_control_specified() {
+        let actual_name: &str = "Only control callback";
+        let experiment = Experiment::new(actual_name).control(|| false);
+
+        assert_eq!(experiment.name(), actual_name);
+    }
+
+    #[test]
+    fn experiment_should_return_name_if_control_and_experiment_are_fully_specified() {
+        let name: &str = "Only control callback";
+        let experiment = Experiment::new(name).control(|| 1).experiment(|| 1);
+
+        assert_eq!(experiment.name(), name);
+    }
+
+    #[test]
+    fn experiment_should_always_return_the_control_value() {
+        let expected = 1;
+        let actual = Experiment::new("Test")
+            .control(|| expected)
+            .experiment(|| expected)
+            .run();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn experiment_should_not_run_the_experiment_if_conditioned_not_to() {
+        let expected = 1;
+        let actual = Experiment::new("Test")
+            .control(|| expected)
+            .experiment(|| expected)
+            .run_if(|| false);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn experiment_should_publish_the_results_when_publish_method_is_specified() {
+        let expected = 1;
+        Experiment::new("Test")
+            .control(|| expected)
+            .experiment(|| expected)
+            .publish(|o: &crate::Observation<i32, i32>| assert!(o.is_matching()))
+            .run();
+    }
+
+    #[derive(PartialEq, Copy, Clone)]
+    struct TestI64 {
+        value: i64,
+    }
+
+    impl PartialEq<i32> for TestI64 {
+        fn eq(&self, other: &i32) -> bool {
+            self.value as i32 == *other
+        }
+    }
+
+    #[test]
+    fn experiment_should_work_with_different_return_types_if_they_are_comparable() {
         let expected: i32 = 1;
         let expected_as_i64 = TestI64 {
             value: expected as i64,
         };
 
-        assert!(expected_as_i64 == expected_as_i64); // implements PartialEq
+        assert!(expected_as_i64 == expected_as_i64); // implements PartialEq
+
+        Experiment::new("Test")
+            .control(move || expected)
+            .experiment(move || expected_as_i64)
+            .publish(|o: &crate::Observation<i32, TestI64>| assert!(o.is_matching()))
+            .run();
+    }
+
+    #[test]
+    #[should_panic]
+    fn experiment_should_panic_if_control_panics() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+
+        let expected: i32 = 1;
+        Experiment::new("Test")
+            .control(|| -> i32 { panic!("Oops") })
+            .experiment(|| expected)
+            .run();
+    }
+
+    #[test]
+    fn experiment_should_return_control_value_if_the_experiment_value_is_different() {
+        let expected: i32 = 1;
+        Experiment::new("Test")
+            .control(|| expected)
+            .experiment(|| expected + 1)
+            .publish(|o: &crate::Observation<i32, i32>| assert!(!o.is_matching()))
+            .run();
+    }
+
+    #[test]
+    fn experiment_should_return_control_value_if_the_experiment_panics() {
+        let expected: i32 = 1;
+        Experiment::new("Test")
+            .control(|| expected)
+            .experiment(|| -> i32 { panic!("Yikes") })
+            .run();
+    }
+
+    #[test]
+    fn experiment_should_compare_the_mapped_control_but_return_the_raw_control_value() {
+        let actual = Experiment::new("Test")
+            .control(|| String::from("value \n"))
+            .experiment(|| String::from("value"))
+            .map_control(|c: &String| c.trim_end().to_owned())
+            .publish(|o: &crate::Observation<String, String>| assert!(o.is_matching()))
+            .run();
+
+        assert_eq!(actual, "value \n");
+    }
+
+    #[test]
+    fn experiment_should_compare_the_mapped_experiment_value() {
+        let actual = Experiment::new("Test")
+            .control(|| 2)
+            .experiment(|| "2")
+            .map_experiment(|e: &str| e.parse::<i32>().unwrap())
+            .publish(|o: &crate::Observation<i32, i32>| assert!(o.is_matching()))
+            .run();
+
+        assert_eq!(actual, 2);
+    }
+
+    #[test]
+    fn experiment_should_record_a_panic_in_the_experiment_mapping_as_an_experiment_panic() {
+        let actual = Experiment::new("Test")
+            .control(|| 2)
+            .experiment(|| "not a number")
+            .map_experiment(|e: &str| e.parse::<i32>().unwrap())
+            .publish(|o: &crate::Observation<i32, i32>| assert!(!o.experiment_is_ok()))
+            .run();
+
+        assert_eq!(actual, 2);
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_return_the_control_panic_as_error_when_run_safely() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+
+        let actual = Experiment::new("Test")
+            .control(|| -> i32 { panic!("Oops") })
+            .experiment(|| 1)
+            .publish(|o: &crate::Observation<i32, i32>| assert!(o.experiment_is_ok()))
+            .run_safe();
+
+        assert_eq!(actual.unwrap_err().downcast_ref::<&str>(), Some(&"Oops"));
+    }
+
+    #[test]
+    fn experiment_should_record_the_experiment_panic_when_run_safely() {
+        let actual = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| -> i32 { panic!("Yikes") })
+            .publish(|o: &crate::Observation<i32, i32>| assert!(!o.experiment_is_ok()))
+            .run_safe();
+
+        assert_eq!(actual.ok(), Some(1));
+    }
+
+    #[test]
+    fn experiment_should_compare_the_values_using_the_custom_comparator() {
+        let actual = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| "1")
+            .comparator(|c: &i32, e: &&str| c.to_string() == *e)
+            .publish(|o: &crate::Observation<i32, &str>| assert!(o.is_matching()))
+            .run();
+
+        assert_eq!(actual, 1);
+    }
+
+    #[test]
+    fn experiment_should_compare_options_by_variant_only() {
+        Experiment::new("Test")
+            .control(|| Some(1))
+            .experiment(|| Some(2))
+            .comparator(crate::compare::by_variant())
+            .publish(|o: &crate::Observation<Option<i32>, Option<i32>>| assert!(o.is_matching()))
+            .run();
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_run_each_branch_inside_of_its_span() {
+        let subscriber = crate::instrument::tests::EnteredSpans::default();
+        let spans = subscriber.spans.clone();
+
+        tracing::subscriber::with_default(subscriber, || {
+            Experiment::new("Test").control(|| 1).experiment(|| 1).run()
+        });
+
+        assert_eq!(*spans.lock().unwrap(), vec!["control", "experiment"]);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn experiment_should_not_enter_spans_if_disabled() {
+        let subscriber = crate::instrument::tests::EnteredSpans::default();
+        let spans = subscriber.spans.clone();
+
+        tracing::subscriber::with_default(subscriber, || {
+            Experiment::new("Test")
+                .control(|| 1)
+                .experiment(|| 1)
+                .with_spans(false)
+                .run()
+        });
+
+        assert!(spans.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn experiment_should_compare_results_with_different_error_types_by_ok_values_only() {
+        let actual = Experiment::new("Test")
+            .control(|| "1".parse::<i32>())
+            .experiment(|| -> Result<i32, String> { Ok(1) })
+            .compare_ok_only()
+            .publish(|o: &crate::Observation<_, Result<i32, String>>| assert!(o.is_matching()))
+            .run();
+
+        assert_eq!(actual, Ok(1));
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_measure_the_durations_of_both_branches_when_run_timed() {
+        let pause = Duration::from_millis(5);
+        let (actual, control, experiment) = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| {
+                std::thread::sleep(pause);
+                1
+            })
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert!(o.control_duration().is_some());
+                assert!(o.experiment_duration() >= Some(pause));
+            })
+            .run_timed();
+
+        assert_eq!(actual, 1);
+        assert!(experiment >= pause);
+        assert!(control < experiment);
+    }
+
+    #[test]
+    fn run_timed_should_skip_the_experiment_if_not_sampled() {
+        let (actual, _, experiment) = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| -> i32 { panic!("experiment must not run") })
+            .run_percentage(0.0)
+            .publish(|_: &crate::Observation<i32, i32>| panic!("nothing must be published"))
+            .run_timed();
+
+        assert_eq!((actual, experiment), (1, Duration::ZERO));
+    }
+
+    #[test]
+    fn experiment_should_not_measure_the_durations_when_run() {
+        Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| 1)
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert_eq!(o.control_duration(), None);
+                assert_eq!(o.experiment_duration(), None);
+            })
+            .run();
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_sample_the_runs_according_to_the_injected_load() {
+        let load = std::sync::Arc::new(std::sync::Mutex::new(1.0));
+        let current = load.clone();
+        let runs = std::cell::Cell::new(0);
+        let experiment = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| 1)
+            .adaptive_sample(move || *current.lock().unwrap(), 1.0)
+            .publish(|_: &crate::Observation<i32, i32>| runs.set(runs.get() + 1));
+
+        (0..10).for_each(|_| assert_eq!(experiment.run(), 1));
+        assert_eq!(runs.get(), 0);
+
+        *load.lock().unwrap() = 0.0;
+        (0..10).for_each(|_| assert_eq!(experiment.run(), 1));
+        assert_eq!(runs.get(), 10);
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_macro_should_run_the_experiment_and_return_the_control_value() {
+        let published = std::cell::Cell::new(false);
+        let actual = crate::experiment!(
+            "Test",
+            control = 1,
+            experiment = 2,
+            publish = |o: &crate::Observation<i32, i32>| {
+                assert!(!o.is_matching());
+                published.set(true)
+            },
+        );
+
+        assert_eq!(actual, 1);
+        assert!(published.get());
+    }
+
+    #[test]
+    fn experiment_macro_should_not_run_the_experiment_if_conditioned_not_to() {
+        let run = std::sync::atomic::AtomicBool::new(false);
+        let actual = crate::experiment!(
+            "Test",
+            control = 1,
+            experiment = run.swap(true, std::sync::atomic::Ordering::SeqCst) as i32,
+            run_if = || false
+        );
+
+        assert_eq!(actual, 1);
+        assert!(!run.into_inner());
+    }
+
+    #[test]
+    fn experiment_should_compare_the_values_by_their_digests() {
+        Experiment::new("Test")
+            .control(|| String::from("large"))
+            .experiment(|| String::from("other"))
+            .compare_by_hash(std::collections::hash_map::RandomState::new())
+            .publish(|o: &crate::Observation<String, String>| {
+                assert!(!o.is_matching());
+                assert!(o.detail().unwrap().starts_with("control digest: "));
+            })
+            .run();
+    }
+
+    #[test]
+    fn experiment_should_time_both_branches_without_comparing_when_run_no_compare() {
+        struct Incomparable;
+
+        let actual = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| Incomparable)
+            .publish(|o: &crate::Observation<i32, Incomparable>| {
+                assert_eq!(o.verdict(), None);
+                assert!(o.control_duration().is_some());
+                assert!(o.experiment_duration().is_some());
+            })
+            .run_no_compare();
+
+        assert_eq!(actual, 1);
+    }
+
+    #[test]
+    fn experiment_should_capture_the_location_of_the_experiment_panic_if_enabled() {
+        let line = line!() + 3;
+        Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| -> i32 { panic!("Oops") })
+            .capture_panic_locations(true)
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert_eq!(o.control_panic_location(), None);
+                assert_eq!(
+                    o.experiment_panic_location(),
+                    Some((String::from(file!()), line))
+                );
+            })
+            .run();
+    }
+
+    #[test]
+    fn experiment_should_not_capture_the_panic_location_by_default() {
+        Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| -> i32 { panic!("Oops") })
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert_eq!(o.experiment_panic_location(), None)
+            })
+            .run();
+    }
+
+    #[test]
+    fn experiment_should_compare_the_projections_of_the_values() {
+        Experiment::new("Test")
+            .control(|| (1, "control"))
+            .experiment(|| (1, "experiment"))
+            .compare_projection(|pair: &(i32, &str)| pair.0)
+            .publish(|o: &crate::Observation<(i32, &str), (i32, &str)>| assert!(o.is_matching()))
+            .run();
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn experiment_should_run_the_experiment_on_the_thread_pool() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .thread_name(|_| String::from("experiment-pool"))
+            .build()
+            .unwrap();
+
+        let actual = Experiment::new("Test")
+            .control(|| std::thread::current().name().map(String::from))
+            .experiment(|| std::thread::current().name().map(String::from))
+            .with_thread_pool(pool)
+            .publish(
+                |o: &crate::Observation<Option<String>, Option<String>>| match &o.experiment {
+                    Ok(name) => assert_eq!(name.as_deref(), Some("experiment-pool")),
+                    Err(_) => panic!("The experiment should not panic"),
+                },
+            )
+            .run();
+
+        assert_ne!(actual.as_deref(), Some("experiment-pool"));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn experiment_should_record_the_panic_on_the_thread_pool() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+
+        let actual = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| -> i32 { panic!("Oops") })
+            .with_thread_pool(pool)
+            .publish(|o: &crate::Observation<i32, i32>| assert!(!o.experiment_is_ok()))
+            .run();
+
+        assert_eq!(actual, 1);
+    }
+
+    #[cfg(feature = "wallclock")]
+    #[test]
+    fn experiment_should_record_the_wallclock_times_of_both_branches() {
+        let before = std::time::SystemTime::now();
+
+        Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| 1)
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert!(o.control_started_at() >= Some(before));
+                assert!(o.control_finished_at() >= o.control_started_at());
+                assert!(o.experiment_started_at() >= o.control_finished_at());
+                assert!(o.experiment_finished_at() >= o.experiment_started_at());
+            })
+            .run();
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_sample_the_same_runs_with_the_same_seed() {
+        let sampled_runs = |seed| {
+            let runs = std::cell::RefCell::new(Vec::new());
+            let run = std::cell::Cell::new(0);
+            let experiment = Experiment::new("Test")
+                .control(|| 1)
+                .experiment(|| 1)
+                .run_percentage(30.0)
+                .with_seed(seed)
+                .publish(|_: &crate::Observation<i32, i32>| runs.borrow_mut().push(run.get()));
+
+            for i in 0..100 {
+                run.set(i);
+                experiment.run();
+            }
+
+            runs.into_inner()
+        };
+
+        let sampled = sampled_runs(42);
+        assert_eq!(sampled, sampled_runs(42));
+        assert!((15..45).contains(&sampled.len()));
+        assert_ne!(sampled, sampled_runs(7));
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_read_the_environment_switch_on_each_run() {
+        let variable = "SCIENTISTO_TEST_EXPERIMENT_SWITCH";
+        let runs = std::cell::Cell::new(0);
+        let experiment = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| 1)
+            .enabled_by_env(variable)
+            .publish(|_: &crate::Observation<i32, i32>| runs.set(runs.get() + 1));
+
+        std::env::set_var(variable, "0");
+        assert_eq!(experiment.run(), 1);
+        assert_eq!(runs.get(), 0);
+
+        std::env::set_var(variable, "1");
+        assert_eq!(experiment.run(), 1);
+        assert_eq!(runs.get(), 1);
+
+        std::env::remove_var(variable);
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_track_the_match_rate_over_the_window() {
+        let value = std::sync::atomic::AtomicI32::new(0);
+        let experiment = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| value.load(std::sync::atomic::Ordering::SeqCst))
+            .track_match_rate(4);
+
+        assert!(experiment.match_rate().is_nan());
+
+        for verdict in [2, 2, 1, 2, 1, 2] {
+            value.store(verdict, std::sync::atomic::Ordering::SeqCst);
+            experiment.run();
+        }
+
+        assert_eq!(experiment.match_rate(), 0.5);
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_compare_the_precomputed_values() {
+        let verdict = std::sync::Mutex::new(None);
+
+        let (result, _, _) = Experiment::new("Test")
+            .control_value(String::from("control"))
+            .experiment_value(String::from("experiment"))
+            .publish(|o: &crate::Observation<String, String>| {
+                *verdict.lock().unwrap() = Some((o.is_matching(), o.control_duration()));
+            })
+            .run_timed();
+
+        assert_eq!(result, "control");
+        let (matching, duration) = verdict.lock().unwrap().take().unwrap();
+        assert!(!matching);
+        assert!(duration.is_some());
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_carry_the_publisher_set_before_the_blocks() {
+        let published = std::sync::atomic::AtomicUsize::new(0);
 
         Experiment::new("Test")
-            .control(move || expected)
-            .experiment(move || expected_as_i64)
-            .publish(|o: &crate::Observation<i32, TestI64>| assert!(o.is_matching()))
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert!(o.is_matching());
+                published.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            })
+            .control(|| 1)
+            .experiment(|| 1)
             .run();
+
+        assert_eq!(published.load(std::sync::atomic::Ordering::SeqCst), 1);
     }
 
     #[test]
-    #[should_panic]
-    fn experiment_should_panic_if_control_panics() {
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_match_both_panicking_branches_if_enabled() {
         std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
 
-        let expected: i32 = 1;
+        for (enabled, expected) in [(false, false), (true, true)] {
+            let matching = std::sync::atomic::AtomicBool::new(!expected);
+
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                Experiment::new("Test")
+                    .control(|| -> i32 { panic!("Control") })
+                    .experiment(|| -> i32 { panic!("Experiment") })
+                    .match_on_both_failed(enabled)
+                    .publish(|o: &crate::Observation<i32, i32>| {
+                        matching.store(o.is_matching(), std::sync::atomic::Ordering::SeqCst)
+                    })
+                    .run()
+            }));
+
+            assert!(result.is_err());
+            assert_eq!(matching.load(std::sync::atomic::Ordering::SeqCst), expected);
+        }
+    }
+
+    #[test]
+    fn experiment_should_not_match_a_single_panicking_branch_if_both_failed_match() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+
+        Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| -> i32 { panic!("Experiment") })
+            .match_on_both_failed(true)
+            .publish(|o: &crate::Observation<i32, i32>| assert!(!o.is_matching()))
+            .run();
+    }
+
+    /// Generator counting up from zero in the upper bits.
+    #[cfg(all(feature = "rand", not(scientisto_disabled)))]
+    struct Counting(u64);
+
+    #[cfg(all(feature = "rand", not(scientisto_disabled)))]
+    impl rand_core::RngCore for Counting {
+        fn next_u32(&mut self) -> u32 {
+            (self.next_u64() >> 32) as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 += 1;
+            (self.0 % 4) << 62
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            rand_core::impls::fill_bytes_via_next(self, dest)
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_sample_the_runs_using_the_user_rng() {
+        let runs = std::sync::atomic::AtomicUsize::new(0);
+        let experiment = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| runs.fetch_add(1, std::sync::atomic::Ordering::SeqCst) as i32)
+            .run_percentage(50.0)
+            .with_rng(Counting(0));
+
+        for _ in 0..8 {
+            experiment.run();
+        }
+
+        // the draws cycle through 0.25, 0.5, 0.75 and 0.0, half of them below the rate
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_publish_the_deferred_verdict_once_confirmed() {
+        let published = std::cell::RefCell::new(Vec::new());
+        let experiment = Experiment::new("Test")
+            .control(|| String::from("control  "))
+            .experiment(|| 2)
+            .map_control(|c: &String| c.trim_end().to_owned())
+            .publish(|o: &crate::Observation<String, i32>| {
+                published.borrow_mut().push(o.verdict())
+            });
+
+        let (result, handle) = experiment.run_deferred();
+        assert_eq!(result, "control  ");
+        assert!(published.borrow().is_empty());
+
+        handle.confirm(false);
+        assert_eq!(*published.borrow(), vec![Some(false)]);
+
+        drop(experiment.run_deferred());
+        assert_eq!(*published.borrow(), vec![Some(false), None]);
+    }
+
+    #[test]
+    fn experiment_should_not_publish_the_deferred_verdict_if_not_sampled() {
+        let published = std::cell::Cell::new(false);
+        let experiment = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| 1)
+            .run_percentage(0.0)
+            .publish(|_: &crate::Observation<i32, i32>| published.set(true));
+
+        let (result, handle) = experiment.run_deferred();
+        handle.confirm(true);
+
+        assert_eq!(result, 1);
+        assert!(!published.get());
+    }
+
+    #[test]
+    fn experiment_should_switch_to_the_noop_publisher_with_no_publish() {
+        let experiment: CompleteExperiment<_, _, _, _, NoopPublisher> = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| 2)
+            .publish(|_: &crate::Observation<i32, i32>| panic!("Published"))
+            .no_publish();
+
+        assert_eq!(experiment.run(), 1);
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_record_the_sizes_of_the_returned_values_only() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+        let sizes = std::cell::RefCell::new(Vec::new());
+
+        let experiment = |fail: bool| {
+            Experiment::new("Test")
+                .control(|| String::from("control"))
+                .experiment(move || -> String {
+                    if fail {
+                        panic!("Experiment")
+                    }
+                    String::from("ctl")
+                })
+                .with_size(String::len)
+                .with_experiment_size(String::len)
+                .publish(|o: &crate::Observation<String, String>| {
+                    sizes
+                        .borrow_mut()
+                        .push((o.control_size(), o.experiment_size(), o.size_ratio()))
+                })
+                .run()
+        };
+        experiment(false);
+        experiment(true);
+
+        assert_eq!(
+            sizes.into_inner(),
+            vec![(Some(7), Some(3), Some(3.0 / 7.0)), (Some(7), None, None)]
+        );
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_match_shared_arcs_by_their_identity() {
+        let shared = std::sync::Arc::new(f64::NAN);
+        let verdicts = std::cell::RefCell::new(Vec::new());
+        let publish = |o: &crate::Observation<std::sync::Arc<f64>, std::sync::Arc<f64>>| {
+            verdicts.borrow_mut().push(o.is_matching())
+        };
+
+        Experiment::new("Test")
+            .control(|| shared.clone())
+            .experiment(|| shared.clone())
+            .with_ptr_eq_fastpath()
+            .publish(publish)
+            .run();
+        Experiment::new("Test")
+            .control(|| shared.clone())
+            .experiment(|| std::sync::Arc::new(f64::NAN))
+            .with_ptr_eq_fastpath()
+            .publish(publish)
+            .run();
+
+        assert_eq!(verdicts.into_inner(), vec![true, false]);
+    }
+
+    #[test]
+    fn experiment_should_compare_the_snapshots_without_modifying_the_shared_state() {
+        let state = std::sync::Mutex::new(vec![1, 2]);
+
+        let result = Experiment::new("Test")
+            .snapshot(
+                &state,
+                |s: &mut Vec<i32>| s.push(3),
+                |s: &mut Vec<i32>| s.clear(),
+            )
+            .publish(|o: &crate::Observation<Vec<i32>, Vec<i32>>| {
+                assert_eq!(o.experiment().ok(), Some(&vec![]));
+                assert!(!o.is_matching());
+            })
+            .run();
+
+        assert_eq!(result, vec![1, 2, 3]);
+        assert!(state.try_lock().is_ok());
+        assert_eq!(*state.lock().unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_be_strict_after_the_grace_period() {
+        let ignored = std::cell::Cell::new(None);
+
         Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| 2)
+            .strict_after(Duration::ZERO)
+            .publish(|o: &crate::Observation<i32, i32>| {
+                ignored.set(Some((o.in_grace_period(), o.matching_or_ignored())))
+            })
+            .run();
+
+        assert_eq!(ignored.get(), Some((false, false)));
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_publish_the_skip_of_the_predicate_before_the_sampling() {
+        let reason = std::cell::Cell::new(None);
+
+        let experiment = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| 1)
+            .run_percentage(0.0)
+            .publish_skips(true)
+            .publish(|o: &crate::Observation<i32, i32>| {
+                assert_eq!(o.verdict(), None);
+                reason.set(o.skip_reason());
+            });
+
+        assert_eq!(experiment.run_if(|| false), 1);
+        assert_eq!(reason.get(), Some(SkipReason::Predicate));
+        assert_eq!(experiment.run(), 1);
+        assert_eq!(reason.get(), Some(SkipReason::Sampled));
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_hand_the_published_control_value_by_reference() {
+        let published = std::cell::Cell::new(false);
+
+        let actual = Experiment::new("Test")
+            .control(|| vec![1, 2, 3])
+            .experiment(|| vec![1, 2, 3])
+            .publish(|o: &crate::Observation<Vec<i32>, Vec<i32>>| published.set(o.is_matching()))
+            .run_with(|control| control.iter().sum::<i32>());
+
+        assert_eq!(actual, 6);
+        assert!(published.get());
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_call_the_resume_hook_with_the_control_panic() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+        let resumed = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let hook = resumed.clone();
+
+        let actual = std::panic::catch_unwind(|| {
+            Experiment::new("Test")
+                .control(|| -> i32 { panic!("Oops") })
+                .experiment(|| 1)
+                .on_resume(move |payload| {
+                    *hook.lock().unwrap() = payload.downcast_ref::<&str>().copied()
+                })
+                .run()
+        });
+
+        assert!(actual.is_err());
+        assert_eq!(*resumed.lock().unwrap(), Some("Oops"));
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_return_the_mapped_control_panic() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+        let published = std::cell::Cell::new(false);
+        let experiment = Experiment::new("Test")
+            .control(|| -> String { panic!("Oops") })
+            .experiment(|| String::from("1"))
+            .map_control_panic(|payload| format!("{}!", payload.downcast_ref::<&str>().unwrap()))
+            .publish(|o: &crate::Observation<String, String>| published.set(!o.control_is_ok()));
+
+        assert_eq!(experiment.run(), "Oops!");
+        assert!(published.get());
+        assert_eq!(experiment.run_with(|c| c.len()), 5);
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_keep_the_mapped_control_panic_after_map_control() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+        let actual = Experiment::new("Test")
             .control(|| -> i32 { panic!("Oops") })
-            .experiment(|| expected)
+            .experiment(|| 1)
+            .map_control_panic(|_| -1)
+            .map_control(|c: &i32| c + 1)
+            .publish(|o: &crate::Observation<i32, i32>| assert!(!o.control_is_ok()))
             .run();
+
+        assert_eq!(actual, -1);
     }
 
     #[test]
-    fn experiment_should_return_control_value_if_the_experiment_value_is_different() {
-        let expected: i32 = 1;
+    #[cfg(not(scientisto_disabled))]
+    #[cfg(feature = "similar")]
+    fn experiment_should_record_the_diff_of_the_mismatching_values_only() {
+        let diffs = std::cell::RefCell::new(Vec::new());
+
+        let experiment = |e: i32| {
+            Experiment::new("Test")
+                .control(|| vec![1, 2])
+                .experiment(move || vec![1, e])
+                .with_diff()
+                .publish(|o: &crate::Observation<Vec<i32>, Vec<i32>>| {
+                    diffs.borrow_mut().push(o.diff().map(String::from))
+                })
+                .run()
+        };
+        experiment(2);
+        experiment(3);
+
+        assert_eq!(
+            diffs.into_inner(),
+            vec![
+                None,
+                Some(String::from(
+                    "--- control\n+++ experiment\n@@ -1,4 +1,4 @@\n [\n     1,\n-    2,\n+    3,\n ]\n"
+                ))
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_return_the_default_value_instead_of_the_control_panic() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+        let experiment = Experiment::new("Test")
+            .control(|| -> i32 { panic!("Oops") })
+            .experiment(|| 1)
+            .with_default(-1);
+
+        assert_eq!(experiment.run_if(|| false), -1);
+        assert_eq!(experiment.run_safe().ok(), Some(-1));
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_return_the_control_and_the_published_observation() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+        let published = std::cell::Cell::new(false);
+
+        let (control, observation) = Experiment::new("Test")
+            .control(|| -> i32 { panic!("Oops") })
+            .experiment(|| 1)
+            .map_control_panic(|_| -1)
+            .publish(|o: &crate::Observation<i32, i32>| {
+                published.set(o.control_panic_downcast::<&str>() == Some(&"Oops"))
+            })
+            .run_observed();
+
+        assert!(published.get());
+        assert_eq!(control, -1);
+        assert_eq!(
+            observation.control_panic_downcast::<crate::observation::MovedOut>(),
+            Some(&crate::observation::MovedOut)
+        );
+        assert_eq!(observation.experiment().ok(), Some(&1));
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_skip_the_runs_exceeding_the_shared_budget() {
+        let budget = crate::budget::SamplingBudget::per_second(1.0);
+        let reasons = std::cell::RefCell::new(Vec::new());
+        let publish = |o: &crate::Observation<i32, i32>| reasons.borrow_mut().push(o.skip_reason());
+
+        let first = Experiment::new("First")
+            .control(|| 1)
+            .experiment(|| 1)
+            .with_budget(&budget)
+            .publish_skips(true)
+            .publish(publish);
+        let second = Experiment::new("Second")
+            .control(|| 2)
+            .experiment(|| 2)
+            .with_budget(&budget)
+            .publish_skips(true)
+            .publish(publish);
+
+        assert_eq!((first.run(), second.run()), (1, 2));
+        assert_eq!(reasons.into_inner(), vec![None, Some(SkipReason::Budget)]);
+        assert_eq!(budget.exhausted(), 1);
+    }
+
+    #[test]
+    #[deny(unused_must_use)]
+    fn experiment_run_or_bound_should_not_be_reported_unused() {
+        let experiment = Experiment::new("Test").control(|| 1).experiment(|| 1);
+        experiment.run();
+
         Experiment::new("Test")
-            .control(|| expected)
-            .experiment(|| expected + 1)
-            .publish(|o: &crate::Observation<i32, i32>| assert!(!o.is_matching()))
+            .control(|| 1)
+            .experiment(|| 1)
+            .publish(|_: &crate::Observation<i32, i32>| {})
             .run();
     }
 
     #[test]
-    fn experiment_should_return_control_value_if_the_experiment_panics() {
-        let expected: i32 = 1;
+    fn experiment_should_compare_the_projections_of_the_mapped_control() {
+        let actual = Experiment::new("Test")
+            .control(|| String::from("1 control"))
+            .experiment(|| (1, "experiment"))
+            .map_control(|c: &String| (c.len() as i32 - 8, "mapped"))
+            .compare_projection(|pair: &(i32, &str)| pair.0)
+            .publish(|o: &crate::Observation<(i32, &str), (i32, &str)>| assert!(o.is_matching()))
+            .run();
+
+        assert_eq!(actual, "1 control");
+    }
+
+    #[test]
+    fn experiment_should_resume_the_control_panic_of_run_observed() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+        let result = std::panic::catch_unwind(|| {
+            Experiment::new("Test")
+                .control(|| -> i32 { panic!("Oops") })
+                .experiment(|| 1)
+                .no_publish()
+                .run_observed()
+        });
+
+        assert_eq!(result.unwrap_err().downcast_ref::<&str>(), Some(&"Oops"));
+    }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_compare_the_observed_side_effects() {
+        let sink = std::sync::Mutex::new(Vec::new());
+        let matching = std::cell::Cell::new(true);
+
         Experiment::new("Test")
-            .control(|| expected)
-            .experiment(|| -> i32 { panic!("Yikes") })
+            .control(|| sink.lock().unwrap().push(2))
+            .experiment(|| sink.lock().unwrap().push(3))
+            .observe(|| std::mem::take(&mut *sink.lock().unwrap()))
+            .publish(|o: &crate::Observation<Vec<i32>, Vec<i32>>| {
+                assert_eq!(o.control().ok(), Some(&vec![2]));
+                matching.set(o.is_matching());
+            })
+            .run();
+
+        assert!(!matching.get());
+    }
+
+    #[test]
+    fn experiment_should_compare_the_observed_side_effects_by_the_comparator() {
+        let sink = std::sync::Mutex::new(Vec::new());
+
+        Experiment::new("Test")
+            .control(|| sink.lock().unwrap().push(2))
+            .experiment(|| sink.lock().unwrap().push(3))
+            .observe(|| std::mem::take(&mut *sink.lock().unwrap()))
+            .comparator(|c: &Vec<i32>, e: &Vec<i32>| c.len() == e.len())
+            .publish(|o: &crate::Observation<Vec<i32>, Vec<i32>>| assert!(o.is_matching()))
             .run();
     }
+
+    #[test]
+    #[cfg(not(scientisto_disabled))]
+    fn experiment_should_return_the_default_value_of_a_skipped_run_with_the_control_panic() {
+        std::panic::set_hook(Box::new(|_| {})); // hide traces from panic
+        let experiment = Experiment::new("Test")
+            .control(|| -> i32 { panic!("Oops") })
+            .experiment(|| -> i32 { panic!("experiment must not run") })
+            .with_default(-1);
+
+        assert_eq!(experiment.run_if(|| false), -1);
+    }
+
+    #[test]
+    #[cfg(scientisto_disabled)]
+    fn disabled_experiment_should_only_run_the_control() {
+        let actual = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| -> i32 { panic!("experiment must not run") })
+            .publish(|_: &crate::Observation<i32, i32>| panic!("nothing must be published"))
+            .run();
+
+        assert_eq!(actual, 1);
+    }
+
+    #[test]
+    #[cfg(scientisto_disabled)]
+    fn disabled_experiment_should_return_the_unpublished_skipped_observation() {
+        let (actual, observation) = Experiment::new("Test")
+            .control(|| 1)
+            .experiment(|| -> i32 { panic!("experiment must not run") })
+            .publish_skips(true)
+            .publish(|_: &crate::Observation<i32, i32>| panic!("nothing must be published"))
+            .run_observed();
+
+        assert_eq!(actual, 1);
+        assert_eq!(observation.skip_reason(), Some(SkipReason::Disabled));
+    }
 }
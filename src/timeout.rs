@@ -0,0 +1,182 @@
+//! Timeouts of the `async` **experiment** branch, replacing a slow experiment by a fallback value
+//! or abandoning it once the **control** completed, and the deadline shared by both branches.
+
+use std::time::{Duration, Instant};
+
+use futures::future::Either;
+
+/// Timeout
+///
+/// Deadline of the experiment branch and the fallback value recorded once it is exceeded, see
+/// `AsyncCompleteExperiment::experiment_timeout_or`.
+pub trait Timeout<TE> {
+    /// The deadline of the experiment branch and its fallback value, if it is timed out.
+    fn timeout(&self) -> Option<(Duration, TE)>;
+}
+
+/// Default timeout of the experiment branch, waiting for the experiment indefinitely.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoTimeout;
+
+impl<TE> Timeout<TE> for NoTimeout {
+    fn timeout(&self) -> Option<(Duration, TE)> {
+        None
+    }
+}
+
+/// Timeout of the experiment branch recording a copy of the fallback value.
+#[derive(Debug, Clone)]
+pub struct TimeoutOr<TE> {
+    duration: Duration,
+    fallback: TE,
+}
+
+impl<TE> TimeoutOr<TE> {
+    pub fn new(duration: Duration, fallback: TE) -> Self {
+        Self { duration, fallback }
+    }
+}
+
+impl<TE> Timeout<TE> for TimeoutOr<TE>
+where
+    TE: Clone,
+{
+    fn timeout(&self) -> Option<(Duration, TE)> {
+        Some((self.duration, self.fallback.clone()))
+    }
+}
+
+/// Await the experiment branch within the timeout, returning the output and whether it timed out.
+///
+/// Once the deadline is exceeded, the experiment future is dropped and the fallback is returned.
+pub(crate) async fn within<F, TE>(
+    experiment: F,
+    timeout: &impl Timeout<TE>,
+) -> (std::thread::Result<TE>, bool)
+where
+    F: std::future::Future<Output = std::thread::Result<TE>>,
+{
+    match timeout.timeout() {
+        Some((duration, fallback)) => {
+            let experiment = std::pin::pin!(experiment);
+
+            match futures::future::select(experiment, futures_timer::Delay::new(duration)).await {
+                Either::Left((experiment, _)) => (experiment, false),
+                Either::Right(_) => (Ok(fallback), true),
+            }
+        }
+        None => (experiment.await, false),
+    }
+}
+
+/// Payload of a branch which has not completed by the deadline, see
+/// `AsyncCompleteExperiment::deadline`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadlineExceeded;
+
+/// Await the branch until the deadline, if any, returning the `exceeded` output past it.
+///
+/// Once the deadline is exceeded, the branch future is dropped.
+pub(crate) async fn until<F>(
+    branch: F,
+    deadline: Option<Instant>,
+    exceeded: impl FnOnce() -> F::Output,
+) -> F::Output
+where
+    F: std::future::Future,
+{
+    let Some(deadline) = deadline else {
+        return branch.await;
+    };
+
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    let branch = std::pin::pin!(branch);
+    match futures::future::select(branch, futures_timer::Delay::new(remaining)).await {
+        Either::Left((output, _)) => output,
+        Either::Right(_) => exceeded(),
+    }
+}
+
+/// Payload of the experiment abandoned after the grace period of `control_first`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Abandoned;
+
+/// Await both of the branches, abandoning the experiment once the grace period after the control
+/// completed is exceeded, if any.
+///
+/// Without the grace period, both of the branches are awaited to completion.
+pub(crate) async fn join_control_first<C, E>(
+    control: C,
+    experiment: E,
+    grace: Option<Duration>,
+    abandoned: impl FnOnce() -> E::Output,
+) -> (C::Output, E::Output)
+where
+    C: std::future::Future,
+    E: std::future::Future,
+{
+    let Some(grace) = grace else {
+        return futures::join!(control, experiment);
+    };
+
+    let control = std::pin::pin!(control);
+    let experiment = std::pin::pin!(experiment);
+
+    match futures::future::select(control, experiment).await {
+        Either::Left((control, experiment)) => {
+            match futures::future::select(experiment, futures_timer::Delay::new(grace)).await {
+                Either::Left((experiment, _)) => (control, experiment),
+                Either::Right(_) => (control, abandoned()),
+            }
+        }
+        Either::Right((experiment, control)) => (control.await, experiment),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_timeout_should_await_the_experiment() {
+        let (experiment, timed_out) =
+            futures::executor::block_on(within(async { Ok(1) }, &NoTimeout));
+
+        assert_eq!(experiment.unwrap(), 1);
+        assert!(!timed_out);
+    }
+
+    #[test]
+    fn timeout_should_return_the_fallback_for_a_pending_experiment() {
+        let (experiment, timed_out) = futures::executor::block_on(within(
+            futures::future::pending(),
+            &TimeoutOr::new(Duration::from_millis(10), 2),
+        ));
+
+        assert_eq!(experiment.unwrap(), 2);
+        assert!(timed_out);
+    }
+
+    #[test]
+    fn control_first_should_abandon_the_experiment_after_the_grace_period() {
+        let (control, experiment) = futures::executor::block_on(join_control_first(
+            async { 1 },
+            futures::future::pending::<i32>(),
+            Some(Duration::from_millis(10)),
+            || 0,
+        ));
+
+        assert_eq!((control, experiment), (1, 0));
+    }
+
+    #[test]
+    fn deadline_should_drop_a_branch_pending_past_it() {
+        let deadline = Some(Instant::now() + Duration::from_millis(10));
+
+        let pending =
+            futures::executor::block_on(until(futures::future::pending::<i32>(), deadline, || 0));
+        let ready = futures::executor::block_on(until(async { 1 }, deadline, || 0));
+
+        assert_eq!((pending, ready), (0, 1));
+    }
+}
@@ -0,0 +1,73 @@
+//! Verdicts of the experiments decided after the run.
+
+use crate::publisher::Publisher;
+use crate::settings::Settings;
+use crate::Observation;
+
+/// Verdict handle
+///
+/// Pending verdict of an experiment run by `run_deferred`. The observation is published once the
+/// verdict is confirmed, or undecided if the handle is dropped without a confirmation.
+#[must_use = "the observation is published undecided unless the verdict is confirmed"]
+pub struct VerdictHandle<'a, T, TE, FP>
+where
+    FP: Publisher<T, TE>,
+{
+    observation: Option<Observation<T, TE>>,
+    publish: &'a FP,
+    settings: Option<&'a Settings>,
+}
+
+impl<'a, T, TE, FP> VerdictHandle<'a, T, TE, FP>
+where
+    FP: Publisher<T, TE>,
+{
+    pub(crate) fn new(
+        observation: Observation<T, TE>,
+        publish: &'a FP,
+        settings: &'a Settings,
+    ) -> Self {
+        Self {
+            observation: Some(observation),
+            publish,
+            settings: Some(settings),
+        }
+    }
+
+    /// Handle of a run which was not sampled, with nothing to publish.
+    pub(crate) fn skipped(publish: &'a FP) -> Self {
+        Self {
+            observation: None,
+            publish,
+            settings: None,
+        }
+    }
+
+    /// Whether the run was sampled and there is an observation to publish.
+    pub fn is_pending(&self) -> bool {
+        self.observation.is_some()
+    }
+
+    /// Decide the verdict and publish the observation.
+    pub fn confirm(mut self, matching: bool) {
+        if let Some(observation) = self.observation.take() {
+            let observation = observation.decided_as(matching);
+
+            if let Some(settings) = self.settings {
+                settings.record(&observation);
+            }
+            self.publish.publish(&observation);
+        }
+    }
+}
+
+impl<'a, T, TE, FP> Drop for VerdictHandle<'a, T, TE, FP>
+where
+    FP: Publisher<T, TE>,
+{
+    fn drop(&mut self) {
+        if let Some(observation) = self.observation.take() {
+            self.publish.publish(&observation);
+        }
+    }
+}